@@ -0,0 +1,536 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use async_trait::async_trait;
+use eyre::Result;
+use reqwest::Client;
+use serde_json::Value;
+use sui_sdk::SuiClient;
+use sui_types::{TypeTag, base_types::ObjectID, dynamic_field::DynamicFieldName};
+use tracing::{debug, warn};
+
+use crate::{
+    collectors::parse_market_quote,
+    config::{
+        AdapterConfig, Asset, Config, DeepBookPool, MarketQuote, Protocol, ProtocolApiConfig,
+        RegistryEntry,
+    },
+    rpc::{read_dynamic_field, read_move_fields},
+};
+
+/// Gas overhead assumed for registry adapters without a configured `gas_overhead`
+pub const DEFAULT_ADAPTER_GAS_OVERHEAD: u64 = 2_000_000;
+
+/// Gas of a flash loan before any protocol overhead, in MIST
+const BASE_LOAN_GAS: u64 = 3_000_000;
+
+/// Clients and config an adapter may read while fetching a quote
+pub struct FetchContext<'a> {
+    pub config: &'a Config,
+    pub http: &'a Client,
+    pub sui: &'a SuiClient,
+}
+
+/// A flash loan source the bot can price and route to
+///
+/// Implementations are registered in an [`AdapterRegistry`]; the collector, strategy
+/// and executor only ever reach protocols through it.
+#[async_trait]
+pub trait ProtocolAdapter: Send + Sync {
+    fn protocol(&self) -> Protocol;
+
+    /// Whether `config` sets this protocol up; disabled adapters are not quoted
+    fn enabled(&self, _config: &Config) -> bool {
+        true
+    }
+
+    /// Assets this protocol can lend
+    fn supported_assets(&self, _config: &Config) -> Vec<Asset> {
+        Asset::ALL.to_vec()
+    }
+
+    /// Id passed to the router's `main::flash_loan`; `None` while execution is unavailable
+    fn router_id(&self, _config: &Config) -> Option<u64> {
+        self.protocol().id()
+    }
+
+    /// API data source for this protocol; without one it is quoted from chain only
+    fn api<'a>(&self, config: &'a Config) -> Option<&'a ProtocolApiConfig> {
+        config.protocol_api(self.protocol())
+    }
+
+    /// Quote `asset` from `response`, this round's answer of the protocol's API
+    fn api_quote(&self, config: &Config, response: &Value, asset: Asset) -> Result<MarketQuote> {
+        let api = self.api(config).ok_or_else(|| {
+            eyre::eyre!("No API data source configured for {:?}", self.protocol())
+        })?;
+        parse_market_quote(response, api, config.asset_types.get(asset))
+    }
+
+    /// Quote `asset` from on-chain state, used when the API fails
+    async fn fetch_onchain_quote(
+        &self,
+        _ctx: &FetchContext<'_>,
+        _asset: Asset,
+    ) -> Result<MarketQuote> {
+        eyre::bail!("No on-chain reader for {:?}", self.protocol())
+    }
+
+    /// Gas added on top of the base flash loan cost, in MIST; ranks the protocol until a
+    /// dry run has measured its loans
+    fn gas_overhead(&self, config: &Config) -> u64;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NaviAdapter;
+
+#[async_trait]
+impl ProtocolAdapter for NaviAdapter {
+    fn protocol(&self) -> Protocol {
+        Protocol::Navi
+    }
+
+    fn gas_overhead(&self, _config: &Config) -> u64 {
+        1_500_000
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BucketAdapter;
+
+#[async_trait]
+impl ProtocolAdapter for BucketAdapter {
+    fn protocol(&self) -> Protocol {
+        Protocol::Bucket
+    }
+
+    fn gas_overhead(&self, _config: &Config) -> u64 {
+        1_200_000
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScallopAdapter;
+
+#[async_trait]
+impl ProtocolAdapter for ScallopAdapter {
+    fn protocol(&self) -> Protocol {
+        Protocol::Scallop
+    }
+
+    fn gas_overhead(&self, _config: &Config) -> u64 {
+        1_800_000
+    }
+}
+
+/// Fee-free flash loans from DeepBook v3 pool vaults
+///
+/// Each quote reads the vault of every configured pool holding the asset and offers the
+/// deepest one's balance. The router's DeepBook adapter picks the pool it borrows from,
+/// so the bot keeps no pool choice between quote and execution.
+#[derive(Debug, Clone, Copy)]
+pub struct DeepBookAdapter;
+
+impl DeepBookAdapter {
+    fn pools(config: &Config, asset: Asset) -> impl Iterator<Item = &DeepBookPool> {
+        config
+            .deepbook
+            .pools
+            .iter()
+            .filter(move |pool| pool.base == asset || pool.quote == asset)
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for DeepBookAdapter {
+    fn protocol(&self) -> Protocol {
+        Protocol::DeepBook
+    }
+
+    fn enabled(&self, config: &Config) -> bool {
+        !config.deepbook.pools.is_empty()
+    }
+
+    fn supported_assets(&self, config: &Config) -> Vec<Asset> {
+        Asset::ALL
+            .into_iter()
+            .filter(|asset| Self::pools(config, *asset).next().is_some())
+            .collect()
+    }
+
+    fn router_id(&self, config: &Config) -> Option<u64> {
+        config.deepbook.protocol_id
+    }
+
+    /// DeepBook has no quote API; liquidity is read from chain
+    fn api<'a>(&self, _config: &'a Config) -> Option<&'a ProtocolApiConfig> {
+        None
+    }
+
+    async fn fetch_onchain_quote(
+        &self,
+        ctx: &FetchContext<'_>,
+        asset: Asset,
+    ) -> Result<MarketQuote> {
+        let mut deepest: Option<u64> = None;
+        for pool in Self::pools(ctx.config, asset) {
+            let balance = match read_vault_balance(ctx.sui, pool, asset).await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    warn!("Failed to read DeepBook pool {}: {}", pool.pool_id, e);
+                    continue;
+                }
+            };
+            debug!("DeepBook pool {} holds {} {}", pool.pool_id, balance, asset);
+            deepest = deepest.max(Some(balance));
+        }
+
+        let available_liquidity =
+            deepest.ok_or_else(|| eyre::eyre!("No readable DeepBook pool holds {}", asset))?;
+        Ok(MarketQuote {
+            fee_bps: 0,
+            available_liquidity,
+        })
+    }
+
+    fn gas_overhead(&self, _config: &Config) -> u64 {
+        1_000_000
+    }
+}
+
+/// Balance of `asset` in the vault of a DeepBook `Pool`
+///
+/// The pool keeps its state in a `Versioned` wrapper, so this reads the pool for the
+/// wrapper id and version, then the `PoolInner` dynamic field stored under it.
+async fn read_vault_balance(sui: &SuiClient, pool: &DeepBookPool, asset: Asset) -> Result<u64> {
+    let (inner_id, version) = parse_versioned(&read_move_fields(sui, pool.pool_id).await?)?;
+    let name = DynamicFieldName {
+        type_: TypeTag::U64,
+        value: Value::from(version.to_string()),
+    };
+    let (base, quote) = parse_vault_balances(&read_dynamic_field(sui, inner_id, name).await?)?;
+    Ok(if pool.base == asset { base } else { quote })
+}
+
+/// Id and version of the `Versioned` wrapper in a DeepBook `Pool`'s fields
+///
+/// # Errors
+///
+/// Returns an error if `inner` is missing or malformed
+pub fn parse_versioned(pool: &Value) -> Result<(ObjectID, u64)> {
+    let inner = &pool["inner"];
+    let id = inner["id"]["id"]
+        .as_str()
+        .ok_or_else(|| eyre::eyre!("DeepBook pool has no 'inner.id'"))?
+        .parse()
+        .map_err(|e| eyre::eyre!("DeepBook pool 'inner.id' is not an object id: {}", e))?;
+    let version = move_u64(&inner["version"])
+        .ok_or_else(|| eyre::eyre!("DeepBook pool has no 'inner.version'"))?;
+    Ok((id, version))
+}
+
+/// Base and quote balances of the vault in a `Field<u64, PoolInner>`'s fields
+///
+/// # Errors
+///
+/// Returns an error if either balance is missing or not a u64
+pub fn parse_vault_balances(field: &Value) -> Result<(u64, u64)> {
+    let vault = &field["value"]["vault"];
+    let balance = |name: &str| {
+        move_u64(&vault[name])
+            .ok_or_else(|| eyre::eyre!("DeepBook vault has no '{}': {}", name, vault))
+    };
+    Ok((balance("base_balance")?, balance("quote_balance")?))
+}
+
+/// A Move `u64` or `Balance`, which the RPC renders as a string, number or `{"value": ..}`
+fn move_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        Value::Object(fields) => fields.get("value").and_then(move_u64),
+        _ => None,
+    }
+}
+
+/// Flash loans from Suilend reserves, routed through a registered router adapter
+///
+/// Quotes come from the reserve for the asset in the shared `LendingMarket` object:
+/// its `available_amount` and its `borrow_fee_bps`, which Suilend charges on every borrow.
+#[derive(Debug, Clone, Copy)]
+pub struct SuilendAdapter;
+
+#[async_trait]
+impl ProtocolAdapter for SuilendAdapter {
+    fn protocol(&self) -> Protocol {
+        Protocol::Suilend
+    }
+
+    fn enabled(&self, config: &Config) -> bool {
+        config.suilend.lending_market_id.is_some()
+    }
+
+    fn router_id(&self, config: &Config) -> Option<u64> {
+        config.suilend.protocol_id
+    }
+
+    /// Suilend has no quote API; reserves are read from chain
+    fn api<'a>(&self, _config: &'a Config) -> Option<&'a ProtocolApiConfig> {
+        None
+    }
+
+    async fn fetch_onchain_quote(
+        &self,
+        ctx: &FetchContext<'_>,
+        asset: Asset,
+    ) -> Result<MarketQuote> {
+        let market_id = ctx
+            .config
+            .suilend
+            .lending_market_id
+            .ok_or_else(|| eyre::eyre!("suilend.lending_market_id is not configured"))?;
+        let market = read_move_fields(ctx.sui, market_id).await?;
+        Self::reserve_quote(ctx.config, &market, asset)
+    }
+
+    fn gas_overhead(&self, _config: &Config) -> u64 {
+        1_600_000
+    }
+}
+
+impl SuilendAdapter {
+    /// Quote of the reserve lending `asset`'s configured coin type in `market`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if [`parse_suilend_reserve`] finds no usable reserve for the type
+    pub fn reserve_quote(config: &Config, market: &Value, asset: Asset) -> Result<MarketQuote> {
+        parse_suilend_reserve(market, config.asset_types.get(asset))
+    }
+}
+
+/// Fee and available amount of the reserve lending `coin_type` in a `LendingMarket`'s fields
+///
+/// # Errors
+///
+/// Returns an error if no reserve lends `coin_type` or its fields are malformed
+pub fn parse_suilend_reserve(market: &Value, coin_type: &str) -> Result<MarketQuote> {
+    let reserves = market["reserves"]
+        .as_array()
+        .ok_or_else(|| eyre::eyre!("Suilend lending market has no 'reserves' vector"))?;
+    let reserve = reserves
+        .iter()
+        .find(|reserve| {
+            reserve["coin_type"]["name"]
+                .as_str()
+                .is_some_and(|name| same_type(name, coin_type))
+        })
+        .ok_or_else(|| eyre::eyre!("No Suilend reserve for {}", coin_type))?;
+
+    let available_liquidity = move_u64(&reserve["available_amount"]).ok_or_else(|| {
+        eyre::eyre!(
+            "Suilend reserve for {} has no 'available_amount'",
+            coin_type
+        )
+    })?;
+    // `config` is a `Cell<ReserveConfig>`, rendered as `{"element": {...}}`
+    let fee_bps = move_u64(&reserve["config"]["element"]["borrow_fee_bps"])
+        .ok_or_else(|| eyre::eyre!("Suilend reserve for {} has no 'borrow_fee_bps'", coin_type))?;
+    if fee_bps > 10_000 {
+        eyre::bail!(
+            "Suilend fee of {} bps for {} exceeds 10_000",
+            fee_bps,
+            coin_type
+        );
+    }
+
+    Ok(MarketQuote {
+        fee_bps,
+        available_liquidity,
+    })
+}
+
+/// Compare Move type tags, ignoring `0x` prefixes and leading zeros in addresses
+///
+/// `TypeName`s render addresses in full without `0x` (`000...002::sui::SUI`), while
+/// configured types use the short form (`0x2::sui::SUI`).
+fn same_type(a: &str, b: &str) -> bool {
+    fn normalize(tag: &str) -> String {
+        let is_separator = |c: char| !c.is_ascii_alphanumeric() && c != '_';
+        tag.split_inclusive(is_separator)
+            .map(|segment| {
+                let token = segment.trim_end_matches(is_separator);
+                let hex = token.strip_prefix("0x").unwrap_or(token);
+                if hex.is_empty() || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return segment.to_string();
+                }
+                let address = hex.trim_start_matches('0');
+                let address = if address.is_empty() { "0" } else { address };
+                format!("{address}{}", &segment[token.len()..])
+            })
+            .collect::<String>()
+            .to_ascii_lowercase()
+    }
+    normalize(a) == normalize(b)
+}
+
+/// Adapter package found in the on-chain registry, described by `[adapters.<id>]`
+///
+/// The router dispatches its loans to the package by registry id.
+#[derive(Debug, Clone)]
+pub struct RegisteredAdapter {
+    protocol_id: u64,
+}
+
+impl RegisteredAdapter {
+    pub fn new(entry: &RegistryEntry) -> Self {
+        Self {
+            protocol_id: entry.protocol_id,
+        }
+    }
+
+    fn settings<'a>(&self, config: &'a Config) -> Option<&'a AdapterConfig> {
+        config.adapter(self.protocol_id)
+    }
+}
+
+#[async_trait]
+impl ProtocolAdapter for RegisteredAdapter {
+    fn protocol(&self) -> Protocol {
+        Protocol::Registered(self.protocol_id)
+    }
+
+    fn gas_overhead(&self, config: &Config) -> u64 {
+        self.settings(config)
+            .and_then(|adapter| adapter.gas_overhead)
+            .unwrap_or(DEFAULT_ADAPTER_GAS_OVERHEAD)
+    }
+}
+
+/// Runtime set of protocol adapters, shared by the collector and executor
+///
+/// Clones share the same set, so adapters registered after startup (such as those
+/// discovered in the on-chain registry) are visible everywhere.
+#[derive(Clone)]
+pub struct AdapterRegistry(Arc<RwLock<Adapters>>);
+
+#[derive(Default)]
+struct Adapters {
+    /// Registered in code; never replaced by discovery
+    registered: Vec<Arc<dyn ProtocolAdapter>>,
+    /// Built from the on-chain registry and `[adapters.<id>]`, replaced every round
+    discovered: Vec<Arc<dyn ProtocolAdapter>>,
+    /// Gas of each protocol's latest dry run, in MIST
+    dry_run_gas: HashMap<Protocol, u64>,
+}
+
+impl fmt::Debug for AdapterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AdapterRegistry")
+            .field(&self.protocols())
+            .finish()
+    }
+}
+
+impl Default for AdapterRegistry {
+    /// Registry holding the built-in Navi, Bucket, Scallop, DeepBook and Suilend adapters
+    fn default() -> Self {
+        let registry = Self(Arc::default());
+        registry.register(Arc::new(NaviAdapter));
+        registry.register(Arc::new(BucketAdapter));
+        registry.register(Arc::new(ScallopAdapter));
+        registry.register(Arc::new(DeepBookAdapter));
+        registry.register(Arc::new(SuilendAdapter));
+        registry
+    }
+}
+
+impl AdapterRegistry {
+    /// Add `adapter`, replacing any adapter already registered for its protocol
+    pub fn register(&self, adapter: Arc<dyn ProtocolAdapter>) {
+        let mut adapters = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        adapters
+            .registered
+            .retain(|existing| existing.protocol() != adapter.protocol());
+        adapters.registered.push(adapter);
+    }
+
+    /// Replace the adapters discovered in the on-chain registry
+    ///
+    /// Adapters added with [`Self::register`] take precedence over discovered ones
+    /// for the same protocol.
+    pub fn sync_discovered(&self, discovered: Vec<Arc<dyn ProtocolAdapter>>) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .discovered = discovered;
+    }
+
+    pub fn get(&self, protocol: Protocol) -> Option<Arc<dyn ProtocolAdapter>> {
+        let adapters = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        adapters
+            .registered
+            .iter()
+            .chain(&adapters.discovered)
+            .find(|adapter| adapter.protocol() == protocol)
+            .cloned()
+    }
+
+    /// One adapter per protocol, ordered by protocol id
+    pub fn all(&self) -> Vec<Arc<dyn ProtocolAdapter>> {
+        let adapters = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        let mut all: Vec<_> = adapters.registered.clone();
+        for adapter in &adapters.discovered {
+            if all
+                .iter()
+                .all(|existing| existing.protocol() != adapter.protocol())
+            {
+                all.push(Arc::clone(adapter));
+            }
+        }
+        all.sort_by_key(|adapter| adapter.protocol());
+        all
+    }
+
+    pub fn protocols(&self) -> Vec<Protocol> {
+        self.all()
+            .iter()
+            .map(|adapter| adapter.protocol())
+            .collect()
+    }
+
+    /// Remember the gas a dry run of a `protocol` loan used
+    pub fn record_gas(&self, protocol: Protocol, gas: u64) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .dry_run_gas
+            .insert(protocol, gas);
+    }
+
+    /// Gas a `protocol` loan is expected to use, in MIST: its latest dry run, else the base
+    /// loan cost plus the adapter's overhead
+    pub fn gas_estimate(&self, protocol: Protocol, config: &Config) -> u64 {
+        if let Some(gas) = self
+            .0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .dry_run_gas
+            .get(&protocol)
+        {
+            return *gas;
+        }
+        self.get(protocol).map_or(BASE_LOAN_GAS, |adapter| {
+            BASE_LOAN_GAS.saturating_add(adapter.gas_overhead(config))
+        })
+    }
+
+    /// Whether `protocol` is registered and can lend `asset`
+    pub fn supports(&self, protocol: Protocol, asset: Asset, config: &Config) -> bool {
+        self.get(protocol)
+            .is_some_and(|adapter| adapter.supported_assets(config).contains(&asset))
+    }
+}
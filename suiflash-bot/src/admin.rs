@@ -0,0 +1,375 @@
+use eyre::Result;
+use serde::Serialize;
+use serde_json::Value;
+use sui_json_rpc_types::{
+    SuiObjectData, SuiObjectDataOptions, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockResponseOptions,
+};
+use sui_sdk::SuiClient;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    crypto::SuiKeyPair,
+    identifier::Identifier,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    quorum_driver_types::ExecuteTransactionRequestType,
+    transaction::{ObjectArg, Transaction, TransactionData},
+};
+use tracing::{debug, info};
+
+use crate::{
+    collectors::parse_registry_entries,
+    config::{Bps, Config},
+    rpc::{connect_sui_client, read_move_fields, sdk_error, shared_object_arg},
+    signer::decode_keypair,
+};
+
+/// Gas budget for admin calls; they touch at most two small objects
+const ADMIN_GAS_BUDGET: u64 = 50_000_000;
+
+/// An `AdminCap`-gated call in the `state` or `registry` Move modules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminCall {
+    SetPaused(bool),
+    SetServiceFee(Bps),
+    AddAllowedAsset(u64),
+    SetProtocolConfig {
+        protocol_id: u64,
+        config_address: SuiAddress,
+    },
+    /// Creates and shares a new `ProtocolRegistry`; needs no `AdminCap`
+    InitRegistry,
+    AppendAdapter {
+        adapter_package: SuiAddress,
+    },
+    UpdateAdapter {
+        protocol_id: u64,
+        adapter_package: SuiAddress,
+    },
+}
+
+impl AdminCall {
+    /// Move module and function implementing this call
+    pub const fn target(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::SetPaused(_) => ("state", "set_paused"),
+            Self::SetServiceFee(_) => ("state", "set_service_fee"),
+            Self::AddAllowedAsset(_) => ("state", "add_allowed_asset"),
+            Self::SetProtocolConfig { .. } => ("state", "set_protocol_config"),
+            Self::InitRegistry => ("registry", "init_and_share_registry"),
+            Self::AppendAdapter { .. } => ("registry", "append_adapter"),
+            Self::UpdateAdapter { .. } => ("registry", "update_adapter"),
+        }
+    }
+}
+
+/// Result of simulating an admin call
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRunOutcome {
+    pub success: bool,
+    pub error: Option<String>,
+    pub gas_used: u64,
+}
+
+/// Result of submitting an admin call
+#[derive(Debug, Clone, Serialize)]
+pub struct SubmitOutcome {
+    pub digest: String,
+    /// Objects created by the transaction, e.g. the new registry from `init-registry`
+    pub created_objects: Vec<ObjectID>,
+}
+
+/// Fields of the shared `state::Config` object
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterState {
+    pub config_object_id: ObjectID,
+    pub treasury: String,
+    pub service_fee_bps: u64,
+    pub paused: bool,
+    pub allowed_assets: Vec<u64>,
+    pub protocol_configs: Vec<String>,
+    /// Adapter packages by protocol id, if `registry_object_id` is configured
+    pub registry: Option<Vec<String>>,
+}
+
+/// Builds, simulates and submits admin transactions signed by the configured key
+pub struct AdminClient {
+    client: SuiClient,
+    config: Config,
+    keypair: SuiKeyPair,
+    sender: SuiAddress,
+}
+
+impl AdminClient {
+    /// Connect to the RPC and decode the signing key
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no RPC is reachable or the private key is invalid
+    pub async fn new(config: Config) -> Result<Self> {
+        let keypair = decode_keypair(&config.private_key)?;
+        let sender = SuiAddress::from(&keypair.public());
+        let client = connect_sui_client(&config).await?;
+        Ok(Self {
+            client,
+            config,
+            keypair,
+            sender,
+        })
+    }
+
+    pub const fn sender(&self) -> SuiAddress {
+        self.sender
+    }
+
+    /// Build the transaction for `call`, paying gas from the sender's first large enough coin
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required object is not configured or cannot be read
+    pub async fn build(&self, call: &AdminCall) -> Result<TransactionData> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let mut arguments = Vec::new();
+
+        if *call != AdminCall::InitRegistry {
+            let cap_id = self
+                .config
+                .admin_cap_id
+                .ok_or_else(|| eyre::eyre!("admin_cap_id is not configured"))?;
+            let cap = self.object(cap_id).await?;
+            arguments.push(
+                builder
+                    .obj(ObjectArg::ImmOrOwnedObject(cap.object_ref()))
+                    .map_err(sdk_error)?,
+            );
+        }
+
+        match call {
+            AdminCall::SetPaused(_)
+            | AdminCall::SetServiceFee(_)
+            | AdminCall::AddAllowedAsset(_)
+            | AdminCall::SetProtocolConfig { .. } => {
+                let state = self
+                    .shared_object(self.config.sui_flash_config_object_id)
+                    .await?;
+                arguments.push(builder.obj(state).map_err(sdk_error)?);
+            }
+            AdminCall::AppendAdapter { .. } | AdminCall::UpdateAdapter { .. } => {
+                let registry = self
+                    .config
+                    .registry_object_id
+                    .ok_or_else(|| eyre::eyre!("registry_object_id is not configured"))?;
+                let registry = self.shared_object(registry).await?;
+                arguments.push(builder.obj(registry).map_err(sdk_error)?);
+            }
+            AdminCall::InitRegistry => {}
+        }
+
+        match *call {
+            AdminCall::SetPaused(paused) => {
+                arguments.push(builder.pure(paused).map_err(sdk_error)?);
+            }
+            AdminCall::SetServiceFee(fee_bps) => {
+                arguments.push(builder.pure(fee_bps.get()).map_err(sdk_error)?);
+            }
+            AdminCall::AddAllowedAsset(tag) => {
+                arguments.push(builder.pure(tag).map_err(sdk_error)?);
+            }
+            AdminCall::SetProtocolConfig {
+                protocol_id,
+                config_address,
+            } => {
+                arguments.push(builder.pure(protocol_id).map_err(sdk_error)?);
+                arguments.push(builder.pure(config_address).map_err(sdk_error)?);
+            }
+            AdminCall::InitRegistry => {}
+            AdminCall::AppendAdapter { adapter_package } => {
+                arguments.push(builder.pure(adapter_package).map_err(sdk_error)?);
+            }
+            AdminCall::UpdateAdapter {
+                protocol_id,
+                adapter_package,
+            } => {
+                arguments.push(builder.pure(protocol_id).map_err(sdk_error)?);
+                arguments.push(builder.pure(adapter_package).map_err(sdk_error)?);
+            }
+        }
+
+        let (module, function) = call.target();
+        builder.programmable_move_call(
+            self.config.sui_flash_package_id,
+            Identifier::new(module).map_err(sdk_error)?,
+            Identifier::new(function).map_err(sdk_error)?,
+            Vec::new(),
+            arguments,
+        );
+
+        let gas_price = self.client.read_api().get_reference_gas_price().await?;
+        let gas_coin = self
+            .client
+            .coin_read_api()
+            .get_coins(self.sender, None, None, None)
+            .await?
+            .data
+            .into_iter()
+            .find(|coin| coin.balance >= ADMIN_GAS_BUDGET)
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "{} has no SUI coin covering the {} MIST gas budget",
+                    self.sender,
+                    ADMIN_GAS_BUDGET
+                )
+            })?;
+
+        debug!("Built {}::{} for {}", module, function, self.sender);
+        Ok(TransactionData::new_programmable(
+            self.sender,
+            vec![gas_coin.object_ref()],
+            builder.finish(),
+            ADMIN_GAS_BUDGET,
+            gas_price,
+        ))
+    }
+
+    /// Simulate `call` without submitting it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be built or the RPC call fails
+    pub async fn dry_run(&self, call: &AdminCall) -> Result<DryRunOutcome> {
+        let tx_data = self.build(call).await?;
+        let response = self
+            .client
+            .read_api()
+            .dry_run_transaction_block(tx_data)
+            .await?;
+        let status = response.effects.status();
+        Ok(DryRunOutcome {
+            success: status.is_ok(),
+            error: (!status.is_ok()).then(|| format!("{status:?}")),
+            gas_used: response.effects.gas_cost_summary().gas_used(),
+        })
+    }
+
+    /// Sign and submit `call`, waiting for local execution
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if submission fails or the transaction aborts on chain
+    pub async fn submit(&self, call: &AdminCall) -> Result<SubmitOutcome> {
+        let tx_data = self.build(call).await?;
+        let transaction = Transaction::from_data_and_signer(tx_data, vec![&self.keypair]);
+        let response = self
+            .client
+            .quorum_driver_api()
+            .execute_transaction_block(
+                transaction,
+                SuiTransactionBlockResponseOptions::new().with_effects(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+
+        let digest = response.digest.to_string();
+        let effects = response
+            .effects
+            .ok_or_else(|| eyre::eyre!("Transaction {} returned no effects", digest))?;
+        if !effects.status().is_ok() {
+            eyre::bail!("Transaction {} failed: {:?}", digest, effects.status());
+        }
+
+        info!("Admin call {:?} executed in {}", call, digest);
+        Ok(SubmitOutcome {
+            digest,
+            created_objects: effects.created().iter().map(|o| o.object_id()).collect(),
+        })
+    }
+
+    /// Read the router `Config` object and, when configured, the protocol registry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an object is missing or its fields do not match the Move layout
+    pub async fn state(&self) -> Result<RouterState> {
+        let config_object_id = self.config.sui_flash_config_object_id;
+        let fields = read_move_fields(&self.client, config_object_id).await?;
+
+        let registry = match self.config.registry_object_id {
+            Some(id) => Some(
+                parse_registry_entries(&read_move_fields(&self.client, id).await?)?
+                    .into_iter()
+                    .map(|entry| entry.adapter_package)
+                    .collect(),
+            ),
+            None => None,
+        };
+
+        Ok(RouterState {
+            config_object_id,
+            treasury: string_field(&fields, "treasury")?,
+            service_fee_bps: u64_field(&fields["service_fee_bps"], "service_fee_bps")?,
+            paused: router_paused(&fields)?,
+            allowed_assets: fields["allowed_assets"]
+                .as_array()
+                .ok_or_else(|| eyre::eyre!("Config field 'allowed_assets' is not a vector"))?
+                .iter()
+                .map(|tag| u64_field(tag, "allowed_assets"))
+                .collect::<Result<_>>()?,
+            protocol_configs: address_vec(&fields, "protocol_configs")?,
+            registry,
+        })
+    }
+
+    async fn object(&self, id: ObjectID) -> Result<SuiObjectData> {
+        self.client
+            .read_api()
+            .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+            .await?
+            .data
+            .ok_or_else(|| eyre::eyre!("Object {} not found", id))
+    }
+
+    async fn shared_object(&self, id: ObjectID) -> Result<ObjectArg> {
+        shared_object_arg(&self.client, id, true).await
+    }
+}
+
+/// `paused` flag of the router `state::Config` object's fields
+///
+/// # Errors
+///
+/// Returns an error if the field is missing or not a bool
+pub fn router_paused(fields: &Value) -> Result<bool> {
+    fields["paused"]
+        .as_bool()
+        .ok_or_else(|| eyre::eyre!("Config field 'paused' is not a bool"))
+}
+
+fn string_field(fields: &Value, name: &str) -> Result<String> {
+    fields[name]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| eyre::eyre!("Field '{}' is not a string", name))
+}
+
+/// Move `u64` values are rendered as JSON strings by the RPC
+fn u64_field(value: &Value, name: &str) -> Result<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| eyre::eyre!("Field '{}' is not a u64: {}", name, value))
+}
+
+fn address_vec(fields: &Value, name: &str) -> Result<Vec<String>> {
+    fields[name]
+        .as_array()
+        .ok_or_else(|| eyre::eyre!("Field '{}' is not a vector", name))?
+        .iter()
+        .map(|address| {
+            address
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| eyre::eyre!("Field '{}' holds a non-address value", name))
+        })
+        .collect()
+}
@@ -1,6 +1,30 @@
 /// Simplified API tests for SuiFlash Bot
 /// Tests basic functionality without requiring full network connectivity
-use crate::config::{Config, FlashLoanRequest, Protocol, RouteMode};
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+use sui_types::base_types::ObjectID;
+
+use suiflash_client::v1::{
+    BatchItem, BatchRequest, BatchResponse, GasEstimate, HistoryPage, Protocols, Quote, Status,
+};
+
+use crate::{
+    config::{
+        Asset, AssetTypes, AuthConfig, Bps, Config, DeepBookConfig, ExecutionStoreConfig,
+        FlashLoanRequest, FlashLoanResponse, GasPolicyConfig, GasPoolConfig, Network,
+        NetworkProfiles, Protocol, ProtocolApis, ProtocolData, RateLimitConfig, ReadinessConfig,
+        RegistryEntry, RouteMode, SharedConfig, ShutdownConfig, StatusResponse, StrategyKind,
+        SuilendConfig, WebhookConfig,
+    },
+    executions::{Execution, ExecutionStatus},
+    executors::FlashLoanEvent,
+    rate_limit::RateLimiter,
+    readiness::{self, ReadinessReport},
+    shutdown::Shutdown,
+    v1,
+    webhooks::{WebhookEvent, WebhookPayload},
+};
 
 #[tokio::test]
 async fn test_flash_loan_request_serialization() {
@@ -56,33 +80,61 @@ async fn test_protocol_serialization() {
         let deserialized: Protocol = serde_json::from_str(&json).expect("Should deserialize");
 
         // Verify protocol values are consistent
-        assert_eq!(protocol as u8, deserialized as u8);
+        assert_eq!(protocol.id(), deserialized.id());
     }
 }
 
+/// Spec served at `/openapi.json`, checked in so contract changes show up in review
+const OPENAPI_SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
 #[tokio::test]
 async fn test_config_validation() {
     let config = Config {
+        network: Network::Testnet,
         sui_rpc_url: "https://fullnode.testnet.sui.io:443".to_string(),
-        private_key: "test_key".to_string(),
-        sui_flash_package_id: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
-        sui_flash_config_object_id: "0xabcdef1234567890abcdef1234567890abcdef12".to_string(),
+        sui_rpc_fallback_urls: Vec::new(),
+        private_key: "test_key".into(),
+        sui_flash_package_id: ObjectID::from_hex_literal(
+            "0x1234567890abcdef1234567890abcdef12345678",
+        )
+        .unwrap(),
+        sui_flash_config_object_id: ObjectID::from_hex_literal(
+            "0xabcdef1234567890abcdef1234567890abcdef12",
+        )
+        .unwrap(),
+        admin_cap_id: None,
+        registry_object_id: None,
         server_port: 3000,
         refresh_interval_ms: 10000,
-        strategy: "cheapest".to_string(),
-        contract_package_id: "0x1".to_string(),
-        navi_package_id: "0x2".to_string(),
-        bucket_package_id: "0x3".to_string(),
-        scallop_package_id: "0x4".to_string(),
-        service_fee_bps: 40,
+        strategy: StrategyKind::Cheapest,
+        contract_package_id: ObjectID::from_hex_literal("0x1").unwrap(),
+        navi_package_id: ObjectID::from_hex_literal("0x2").unwrap(),
+        bucket_package_id: ObjectID::from_hex_literal("0x3").unwrap(),
+        scallop_package_id: ObjectID::from_hex_literal("0x4").unwrap(),
+        service_fee_bps: Bps::try_from(40).unwrap(),
+        protocol_apis: ProtocolApis::default(),
+        asset_types: AssetTypes::default(),
+        adapters: BTreeMap::new(),
+        deepbook: DeepBookConfig::default(),
+        suilend: SuilendConfig::default(),
+        gas_pool: GasPoolConfig::default(),
+        gas_policy: GasPolicyConfig::default(),
+        api_keys: Vec::new(),
+        auth: AuthConfig { disabled: true },
+        rate_limits: RateLimitConfig::default(),
+        executions: ExecutionStoreConfig::default(),
+        webhooks: WebhookConfig::default(),
+        shutdown: ShutdownConfig::default(),
+        readiness: ReadinessConfig::default(),
+        networks: NetworkProfiles::default(),
     };
 
     // Test that config has reasonable values
     assert!(config.server_port > 0);
     assert!(config.refresh_interval_ms > 0);
-    assert!(config.service_fee_bps < 1000); // Less than 10%
+    assert!(config.service_fee_bps.get() < 1000); // Less than 10%
     assert!(config.sui_rpc_url.starts_with("http"));
-    assert!(config.sui_flash_package_id.starts_with("0x"));
+    assert!(config.sui_flash_package_id.to_string().starts_with("0x"));
 }
 
 #[tokio::test]
@@ -114,3 +166,191 @@ async fn test_request_validation() {
         assert!(recipient.len() >= 42); // Minimum address length
     }
 }
+
+#[tokio::test]
+async fn test_openapi_spec_matches_handlers() {
+    let example = concat!(env!("CARGO_MANIFEST_DIR"), "/config.example.toml");
+    let config = SharedConfig::new(Config::from_toml(example).unwrap());
+    let limiter = RateLimiter::new(config.clone());
+    let spec = crate::api_routes(&config, &limiter, &Shutdown::default()).into_openapi();
+    let generated = serde_json::to_value(&spec).unwrap();
+
+    let paths: Vec<_> = generated["paths"].as_object().unwrap().keys().collect();
+    assert_eq!(
+        paths,
+        [
+            "/admin/reload",
+            "/health",
+            "/metrics",
+            "/ready",
+            "/v1/executions/{id}",
+            "/v1/flashloan",
+            "/v1/flashloan/batch",
+            "/v1/history",
+            "/v1/protocols",
+            "/v1/quote",
+            "/v1/status",
+            "/v1/stream/protocols",
+            "/v1/stream/protocols/ws",
+            "/v1/webhooks",
+            "/v1/webhooks/{id}",
+            "/v1/webhooks/{id}/deliveries"
+        ]
+    );
+
+    // Each schema documents exactly the fields its type serializes
+    let documented = |name: &str| -> BTreeSet<String> {
+        generated["components"]["schemas"][name]["properties"]
+            .as_object()
+            .unwrap_or_else(|| panic!("{name} has no schema"))
+            .keys()
+            .cloned()
+            .collect()
+    };
+    let serialized =
+        |value: Value| -> BTreeSet<String> { value.as_object().unwrap().keys().cloned().collect() };
+    let data = ProtocolData {
+        protocol: Protocol::Navi,
+        asset: Asset::SUI,
+        fee_bps: 8,
+        available_liquidity: 1,
+        last_updated: 1,
+    };
+    let request = FlashLoanRequest {
+        asset: "SUI".to_string(),
+        amount: 1,
+        route_mode: RouteMode::Explicit,
+        explicit_protocol: Some(Protocol::Navi),
+        user_operation: "arbitrage".to_string(),
+        callback_recipient: Some("0x1".to_string()),
+        callback_payload: Some("dGVzdA==".to_string()),
+    };
+    let response = FlashLoanResponse {
+        transaction_digest: "digest".to_string(),
+        protocol_used: Protocol::Navi,
+        protocol_fee: 1,
+        service_fee: 1,
+        total_fee: 2,
+    };
+    let receipt = v1::receipt("id".to_string(), &request, response);
+    let protocols = Protocols {
+        protocols: vec![data.clone().into()],
+        unknown_protocols: vec![
+            RegistryEntry {
+                protocol_id: 4,
+                adapter_package: "0x4".to_string(),
+            }
+            .into(),
+        ],
+    };
+    let status = Status::from(StatusResponse {
+        strategy: StrategyKind::Cheapest,
+        service_fee_bps: 40,
+        protocol_count: 1,
+        last_updated_any: Some(1),
+    });
+    let quote = Quote {
+        protocol: receipt.protocol,
+        asset: "SUI".to_string(),
+        amount: 1,
+        protocol_fee: 1,
+        service_fee: 1,
+        total_fee: 2,
+        total_repayment: 3,
+        gas: GasEstimate {
+            reference_gas_price: 750,
+            gas_price: 750,
+            computation_cost: 1,
+            storage_cost: 1,
+            gas_budget: 3,
+        },
+    };
+    let batch = BatchRequest {
+        loans: vec![request.clone().into()],
+    };
+    let batch_response = BatchResponse {
+        succeeded: 1,
+        failed: 0,
+        results: vec![BatchItem {
+            index: 0,
+            status: 200,
+            execution_id: Some("id".to_string()),
+            receipt: Some(receipt.clone()),
+            error: None,
+            replayed: false,
+        }],
+    };
+    let execution = suiflash_client::v1::Execution {
+        receipt: Some(receipt.clone()),
+        error: Some("error".to_string()),
+        ..suiflash_client::v1::Execution::from(Execution {
+            id: "id".to_string(),
+            client: None,
+            request: request.clone(),
+            status: ExecutionStatus::Confirmed,
+            response: None,
+            error: None,
+            http_status: None,
+            created_at: 1,
+            updated_at: 1,
+        })
+    };
+    let history = HistoryPage {
+        executions: vec![execution.clone()],
+        next: Some(v1::history_cursor(&execution)),
+    };
+    let webhook = WebhookPayload {
+        protocol: Some(Protocol::Navi),
+        digest: Some("digest".to_string()),
+        flash_loan_event: Some(FlashLoanEvent {
+            protocol: 1,
+            amount: 1,
+            protocol_fee: 1,
+            service_fee: 1,
+            total_repayment: 3,
+        }),
+        error: Some("error".to_string()),
+        ..WebhookPayload::new(WebhookEvent::Confirmed, "id")
+    };
+    let readiness = ReadinessReport::new(vec![readiness::balance_check(1, 0)]);
+    for (name, value) in [
+        ("ProtocolData", serde_json::to_value(&data)),
+        (
+            "ProtocolInfo",
+            serde_json::to_value(&protocols.protocols[0]),
+        ),
+        (
+            "FlashLoanRequest",
+            serde_json::to_value(suiflash_client::v1::FlashLoanRequest::from(request)),
+        ),
+        ("FlashLoanReceipt", serde_json::to_value(&receipt)),
+        ("Quote", serde_json::to_value(&quote)),
+        ("Protocols", serde_json::to_value(&protocols)),
+        ("Status", serde_json::to_value(&status)),
+        ("BatchRequest", serde_json::to_value(&batch)),
+        ("BatchResponse", serde_json::to_value(&batch_response)),
+        (
+            "BatchItem",
+            serde_json::to_value(&batch_response.results[0]),
+        ),
+        ("Execution", serde_json::to_value(&execution)),
+        ("HistoryPage", serde_json::to_value(&history)),
+        ("WebhookPayload", serde_json::to_value(&webhook)),
+        ("ReadinessReport", serde_json::to_value(&readiness)),
+        ("ReadinessCheck", serde_json::to_value(&readiness.checks[0])),
+    ] {
+        assert_eq!(serialized(value.unwrap()), documented(name), "{name}");
+    }
+
+    // Regenerate the checked-in copy with `UPDATE_OPENAPI=1 cargo test test_openapi_spec`
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        let pretty = serde_json::to_string_pretty(&spec).unwrap();
+        std::fs::write(OPENAPI_SNAPSHOT, pretty + "\n").unwrap();
+    }
+    let committed: Value =
+        serde_json::from_str(&std::fs::read_to_string(OPENAPI_SNAPSHOT).unwrap()).unwrap();
+    assert!(
+        generated == committed,
+        "openapi.json is out of date; regenerate it with UPDATE_OPENAPI=1 cargo test test_openapi_spec"
+    );
+}
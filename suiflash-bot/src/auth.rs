@@ -0,0 +1,204 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use eyre::Result;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::{
+    config::{ApiKeyConfig, ApiScope, Asset, FlashLoanRequest, Protocol, SharedConfig},
+    executions::{ExecutionStore, to_sql},
+};
+
+/// Header accepted alongside `Authorization: Bearer <key>`
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The client a request was authenticated as, added to the request extensions
+#[derive(Debug, Clone)]
+pub struct ApiClient(pub ApiKeyConfig);
+
+impl ApiClient {
+    /// Check `request` against the client's asset and amount limits and explicit protocol
+    ///
+    /// # Errors
+    ///
+    /// Returns the first limit the request breaks
+    pub fn check_request(&self, request: &FlashLoanRequest) -> Result<()> {
+        let key = &self.0;
+        if !key.allowed_assets.is_empty() {
+            let asset = request.asset.parse::<Asset>()?;
+            if !key.allowed_assets.contains(&asset) {
+                eyre::bail!("{} may not borrow {}", key.name, asset);
+            }
+        }
+        if let Some(max) = key.max_loan_amount
+            && request.amount > max
+        {
+            eyre::bail!(
+                "{} may borrow at most {}, requested {}",
+                key.name,
+                max,
+                request.amount
+            );
+        }
+        if let Some(protocol) = request.explicit_protocol
+            && !self.allows_protocol(protocol)
+        {
+            eyre::bail!("{} may not route to {:?}", key.name, protocol);
+        }
+        Ok(())
+    }
+
+    pub fn allows_protocol(&self, protocol: Protocol) -> bool {
+        self.0.allowed_protocols.is_empty() || self.0.allowed_protocols.contains(&protocol)
+    }
+}
+
+/// Hex BLAKE3 hash stored in `api_keys[].key_hash`
+pub fn hash_api_key(key: &str) -> String {
+    blake3::hash(key.as_bytes()).to_hex().to_string()
+}
+
+/// Find the configured client whose hash matches `key`
+///
+/// Hashes are compared in constant time.
+pub fn authenticate<'a>(keys: &'a [ApiKeyConfig], key: &str) -> Option<&'a ApiKeyConfig> {
+    let presented = blake3::hash(key.as_bytes());
+    keys.iter().find(|candidate| {
+        blake3::Hash::from_hex(&candidate.key_hash).is_ok_and(|hash| hash == presented)
+    })
+}
+
+/// Key sent as `Authorization: Bearer <key>` or in the `X-API-Key` header
+pub fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            headers
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(str::trim)
+}
+
+/// Require a key with the `loans` scope
+pub async fn require_loans_scope(
+    State(config): State<SharedConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    authorize(&config, ApiScope::Loans, request, next).await
+}
+
+/// Require a key with the `admin` scope
+pub async fn require_admin_scope(
+    State(config): State<SharedConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    authorize(&config, ApiScope::Admin, request, next).await
+}
+
+async fn authorize(
+    config: &SharedConfig,
+    scope: ApiScope,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let config = config.current();
+    // Only an explicit auth.disabled turns checks off; serve warns about it at startup
+    if config.auth.disabled {
+        return next.run(request).await;
+    }
+
+    // With no keys configured nothing authenticates, so every request is refused
+    let Some(key) =
+        presented_key(request.headers()).and_then(|key| authenticate(&config.api_keys, key))
+    else {
+        return (StatusCode::UNAUTHORIZED, "Missing or unknown API key").into_response();
+    };
+    if !key.scopes.contains(&scope) {
+        warn!(
+            "{} lacks the {:?} scope for {}",
+            key.name,
+            scope,
+            request.uri()
+        );
+        return (StatusCode::FORBIDDEN, "API key lacks the required scope").into_response();
+    }
+
+    request.extensions_mut().insert(ApiClient(key.clone()));
+    next.run(request).await
+}
+
+/// Days since the Unix epoch, in UTC
+pub fn current_utc_day() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400)
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS loan_counts (
+    client TEXT NOT NULL,
+    day INTEGER NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (client, day)
+);
+";
+
+/// Loans taken per client and UTC day, kept in the execution store so restarts keep them
+#[derive(Debug, Clone)]
+pub struct DailyLoanCounter {
+    pool: SqlitePool,
+}
+
+impl DailyLoanCounter {
+    /// Create the counter table in `store` if missing
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table cannot be created
+    pub async fn new(store: &ExecutionStore) -> Result<Self> {
+        let pool = store.pool().clone();
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// Count a loan for `client` on `day`, refusing it once `limit` is reached
+    ///
+    /// Counts of earlier days are dropped on the way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub async fn try_acquire(&self, client: &str, limit: u32, day: u64) -> Result<bool> {
+        let day = to_sql(day);
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM loan_counts WHERE client = ? AND day < ?")
+            .bind(client)
+            .bind(day)
+            .execute(&mut *tx)
+            .await?;
+        // The conditional upsert counts the loan only while the client is under its limit
+        let counted = sqlx::query(
+            "INSERT INTO loan_counts (client, day, count) SELECT ?, ?, 1 WHERE ? > 0
+             ON CONFLICT (client, day) DO UPDATE SET count = count + 1 WHERE count < ?",
+        )
+        .bind(client)
+        .bind(day)
+        .bind(limit)
+        .bind(limit)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            == 1;
+        tx.commit().await?;
+        Ok(counted)
+    }
+}
@@ -0,0 +1,426 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use eyre::Result;
+use serde::Serialize;
+use tracing::warn;
+
+use sui_types::base_types::SuiAddress;
+
+use crate::{
+    admin::{AdminCall, AdminClient, DryRunOutcome, RouterState, SubmitOutcome},
+    auth,
+    collectors::ProtocolDataCollector,
+    config::{
+        Asset, Bps, Config, FlashLoanRequest, FlashLoanResponse, Network, Protocol,
+        ProtocolsResponse, RouteMode, SharedConfig,
+    },
+    executors::FlashLoanExecutor,
+    gas_policy::GasQuote,
+    signer,
+    strategies::{ExecutionPlan, FlashLoanStrategy},
+};
+
+/// Template written by `config init`
+const CONFIG_TEMPLATE: &str = include_str!("../config.example.toml");
+
+#[derive(Debug, Parser)]
+#[command(name = "suiflash", version, about = "SuiFlash flash loan aggregator")]
+pub struct Cli {
+    /// Network profile to use; overrides SUIFLASH_NETWORK and config.toml
+    #[arg(long, global = true)]
+    pub network: Option<Network>,
+
+    /// Defaults to `serve` when omitted
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the HTTP server and background collection
+    Serve,
+    /// Price a flash loan without executing it
+    Quote(LoanArgs),
+    /// Execute a flash loan
+    Execute {
+        #[command(flatten)]
+        loan: LoanArgs,
+        /// Operation passed through to the execution plan
+        #[arg(long, default_value = "")]
+        user_operation: String,
+        /// Address of the contract handling the callback
+        #[arg(long)]
+        callback_recipient: Option<String>,
+        /// Payload forwarded to the callback
+        #[arg(long)]
+        callback_payload: Option<String>,
+    },
+    /// Fetch and print current protocol data
+    Protocols,
+    /// Print the active strategy and data freshness
+    Status,
+    /// Inspect or create configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Print the signer address derived from the configured private key
+    Address,
+    /// Print the `api_keys[].key_hash` value for a key
+    HashApiKey {
+        /// Read from stdin when omitted, keeping the key out of shell history
+        key: Option<String>,
+    },
+    /// Call AdminCap-gated router functions, dry-running before submission
+    Admin {
+        /// Only simulate the call
+        #[arg(long)]
+        dry_run: bool,
+        #[command(subcommand)]
+        action: AdminCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommand {
+    /// Print the on-chain router config and protocol registry
+    State,
+    /// Pause flash loans (`state::set_paused(true)`)
+    Pause,
+    /// Resume flash loans (`state::set_paused(false)`)
+    Unpause,
+    /// Set the on-chain service fee
+    SetServiceFee {
+        #[arg(long)]
+        bps: Bps,
+    },
+    /// Allow an asset tag
+    AddAllowedAsset {
+        #[arg(long)]
+        tag: u64,
+    },
+    /// Point a protocol id at its external config object
+    SetProtocolConfig {
+        #[arg(long)]
+        protocol_id: u64,
+        #[arg(long)]
+        address: SuiAddress,
+    },
+    /// Create and share a new protocol registry
+    InitRegistry,
+    /// Register an adapter package under the next protocol id
+    AppendAdapter {
+        #[arg(long)]
+        package: SuiAddress,
+    },
+    /// Replace the adapter package of an existing protocol id
+    UpdateAdapter {
+        #[arg(long)]
+        protocol_id: u64,
+        #[arg(long)]
+        package: SuiAddress,
+    },
+}
+
+impl AdminCommand {
+    /// The Move call to make, or `None` for read-only `state`
+    fn into_call(self) -> Option<AdminCall> {
+        Some(match self {
+            Self::State => return None,
+            Self::Pause => AdminCall::SetPaused(true),
+            Self::Unpause => AdminCall::SetPaused(false),
+            Self::SetServiceFee { bps } => AdminCall::SetServiceFee(bps),
+            Self::AddAllowedAsset { tag } => AdminCall::AddAllowedAsset(tag),
+            Self::SetProtocolConfig {
+                protocol_id,
+                address,
+            } => AdminCall::SetProtocolConfig {
+                protocol_id,
+                config_address: address,
+            },
+            Self::InitRegistry => AdminCall::InitRegistry,
+            Self::AppendAdapter { package } => AdminCall::AppendAdapter {
+                adapter_package: package,
+            },
+            Self::UpdateAdapter {
+                protocol_id,
+                package,
+            } => AdminCall::UpdateAdapter {
+                protocol_id,
+                adapter_package: package,
+            },
+        })
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct LoanArgs {
+    /// Asset to borrow (SUI, USDC or USDT)
+    #[arg(long)]
+    pub asset: Asset,
+    /// Amount in base units
+    #[arg(long)]
+    pub amount: u64,
+    /// Use this protocol instead of the configured strategy
+    #[arg(long)]
+    pub protocol: Option<Protocol>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Load the configuration and report every problem
+    Validate,
+    /// Print the effective configuration as TOML, secrets omitted
+    Show,
+    /// Write a commented configuration template
+    Init {
+        #[arg(long, default_value = "config.toml")]
+        path: PathBuf,
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Cost breakdown printed by `quote`
+#[derive(Debug, Serialize)]
+pub struct QuoteOutput {
+    pub protocol: Protocol,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub service_fee: u64,
+    pub total_fee: u64,
+    pub total_repayment: u64,
+    /// Gas price and budget under the configured gas policy
+    pub gas: GasQuote,
+}
+
+/// Result of an `admin` subcommand
+#[derive(Debug, Serialize)]
+pub struct AdminOutput {
+    pub sender: SuiAddress,
+    pub call: Option<String>,
+    pub dry_run: Option<DryRunOutcome>,
+    pub submitted: Option<SubmitOutcome>,
+    pub state: RouterState,
+}
+
+/// Run a subcommand, printing results to stdout
+///
+/// # Errors
+///
+/// Returns an error if configuration, data collection or the command itself fails
+pub async fn run(command: Command, network: Option<Network>) -> Result<()> {
+    match command {
+        Command::Serve => crate::serve(network).await,
+        Command::Config { action } => run_config(action, network),
+        Command::Address => {
+            let config = load_valid_config(network)?;
+            println!("{}", signer::signer_address(&config.private_key)?);
+            Ok(())
+        }
+        Command::HashApiKey { key } => {
+            let key = match key {
+                Some(key) => key,
+                None => {
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line)?;
+                    line
+                }
+            };
+            let key = key.trim();
+            if key.is_empty() {
+                eyre::bail!("API key is empty");
+            }
+            println!("{}", auth::hash_api_key(key));
+            Ok(())
+        }
+        Command::Admin { dry_run, action } => {
+            let admin = AdminClient::new(load_valid_config(network)?).await?;
+            print_json(&run_admin(&admin, action, dry_run).await?)
+        }
+        Command::Protocols => {
+            let strategy = collect_once(load_valid_config(network)?).await?.0;
+            let data = strategy.collector().get_all_protocol_data().await;
+            print_json(&ProtocolsResponse {
+                protocols: data.into_values().collect(),
+                unknown_protocols: strategy.collector().unknown_protocols().await,
+            })
+        }
+        Command::Status => {
+            let strategy = collect_once(load_valid_config(network)?).await?.0;
+            print_json(&strategy.status().await)
+        }
+        Command::Quote(loan) => {
+            let (strategy, config) = collect_once(load_valid_config(network)?).await?;
+            let executor = FlashLoanExecutor::new(config.clone())
+                .await?
+                .with_adapters(strategy.collector().adapters().clone());
+            let request = loan.into_request(String::new(), None, None);
+            let plan = plan(&strategy, &request).await?;
+            let gas = executor.quote_gas(&plan).await?;
+            let protocol_fee = plan.total_cost - plan.amount;
+            let service_fee = config.current().service_fee_bps.fee_on(plan.amount);
+            print_json(&QuoteOutput {
+                protocol: plan.protocol,
+                amount: plan.amount,
+                protocol_fee,
+                service_fee,
+                total_fee: protocol_fee + service_fee,
+                total_repayment: plan.total_cost + service_fee,
+                gas,
+            })
+        }
+        Command::Execute {
+            loan,
+            user_operation,
+            callback_recipient,
+            callback_payload,
+        } => {
+            let (strategy, config) = collect_once(load_valid_config(network)?).await?;
+            let executor = FlashLoanExecutor::new(config.clone())
+                .await?
+                .with_adapters(strategy.collector().adapters().clone());
+            let request = loan.into_request(user_operation, callback_recipient, callback_payload);
+            let plan = plan(&strategy, &request).await?;
+            // A one-shot run has no maintenance task, so fill the gas pool once up front
+            if let Some(pool) = executor.gas_pool() {
+                pool.refresh().await?;
+            }
+            let transaction_digest = executor.execute_flash_loan(&plan).await?;
+
+            let protocol_fee = plan.total_cost - plan.amount;
+            let service_fee = config.current().service_fee_bps.fee_on(plan.amount);
+            print_json(&FlashLoanResponse {
+                transaction_digest,
+                protocol_used: plan.protocol,
+                protocol_fee,
+                service_fee,
+                total_fee: protocol_fee + service_fee,
+            })
+        }
+    }
+}
+
+async fn run_admin(
+    admin: &AdminClient,
+    action: AdminCommand,
+    dry_run: bool,
+) -> Result<AdminOutput> {
+    let Some(call) = action.into_call() else {
+        return Ok(AdminOutput {
+            sender: admin.sender(),
+            call: None,
+            dry_run: None,
+            submitted: None,
+            state: admin.state().await?,
+        });
+    };
+
+    let (module, function) = call.target();
+    let simulation = admin.dry_run(&call).await?;
+    if let Some(error) = &simulation.error {
+        eyre::bail!("Dry run of {}::{} failed: {}", module, function, error);
+    }
+    let submitted = if dry_run {
+        None
+    } else {
+        Some(admin.submit(&call).await?)
+    };
+
+    Ok(AdminOutput {
+        sender: admin.sender(),
+        call: Some(format!("{module}::{function}")),
+        dry_run: Some(simulation),
+        submitted,
+        state: admin.state().await?,
+    })
+}
+
+fn run_config(action: ConfigCommand, network: Option<Network>) -> Result<()> {
+    match action {
+        ConfigCommand::Validate => {
+            let config = load_config(network)?;
+            let report = config.validate();
+            print!("{report}");
+            report.into_result()?;
+            println!("Configuration is valid for {}", config.network);
+            Ok(())
+        }
+        ConfigCommand::Show => {
+            print!("{}", load_config(network)?.to_toml_string(false)?);
+            Ok(())
+        }
+        ConfigCommand::Init { path, force } => {
+            if path.exists() && !force {
+                eyre::bail!(
+                    "{} already exists; pass --force to overwrite",
+                    path.display()
+                );
+            }
+            std::fs::write(&path, CONFIG_TEMPLATE)?;
+            println!("Wrote {}", path.display());
+            Ok(())
+        }
+    }
+}
+
+fn load_config(network: Option<Network>) -> Result<Config> {
+    Config::load_for_network(network)
+        .map_err(|e| eyre::eyre!("Failed to load configuration: {}", e))
+}
+
+/// Load configuration, logging warnings and failing on errors as `serve` does
+fn load_valid_config(network: Option<Network>) -> Result<Config> {
+    let config = load_config(network)?;
+    let report = config.validate();
+    for warning in &report.warnings {
+        warn!("Configuration warning: {}", warning);
+    }
+    report.into_result()?;
+    Ok(config)
+}
+
+/// Build a strategy and run a single collection round so it has data to route on
+async fn collect_once(config: Config) -> Result<(FlashLoanStrategy, SharedConfig)> {
+    let config = SharedConfig::new(config);
+    let collector = ProtocolDataCollector::new(config.clone()).await;
+    collector.collect_all_data().await?;
+    Ok((FlashLoanStrategy::new(config.clone(), collector), config))
+}
+
+async fn plan(strategy: &FlashLoanStrategy, request: &FlashLoanRequest) -> Result<ExecutionPlan> {
+    match request.explicit_protocol {
+        Some(protocol) => strategy.override_protocol(request, protocol).await,
+        None => strategy.generate_execution_plan(request).await,
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+impl LoanArgs {
+    fn into_request(
+        self,
+        user_operation: String,
+        callback_recipient: Option<String>,
+        callback_payload: Option<String>,
+    ) -> FlashLoanRequest {
+        FlashLoanRequest {
+            asset: self.asset.to_string(),
+            amount: self.amount,
+            route_mode: if self.protocol.is_some() {
+                RouteMode::Explicit
+            } else {
+                RouteMode::BestCost
+            },
+            explicit_protocol: self.protocol,
+            user_operation,
+            callback_recipient,
+            callback_payload,
+        }
+    }
+}
@@ -4,37 +4,61 @@ use artemis::types::{Collector, CollectorStream};
 use async_trait::async_trait;
 use eyre::Result;
 use reqwest::Client;
+use serde::Deserialize;
 use serde_json::Value;
-use sui_sdk::{SuiClient, SuiClientBuilder};
-use sui_types::base_types::ObjectID;
+use sui_sdk::SuiClient;
 use tokio::{
-    sync::RwLock,
-    time::{Duration, interval},
+    sync::{RwLock, broadcast},
+    time::{Duration, Instant, sleep},
 };
+use tokio_stream::{StreamExt, wrappers::BroadcastStream};
 use tracing::{debug, error, info, warn};
 
-use crate::config::{Config, Protocol, ProtocolData};
+use crate::{
+    adapters::{AdapterRegistry, FetchContext, ProtocolAdapter, RegisteredAdapter},
+    config::{
+        Asset, MarketQuote, Protocol, ProtocolApiConfig, ProtocolData, RegistryEntry, SharedConfig,
+    },
+    metrics::{METRICS, protocol_label},
+    rpc::{connect_sui_client, read_move_fields},
+};
+
+/// Changes buffered per subscriber before it lags and has to resync
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
+
+/// A change to the collected data, broadcast after each collection round
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtocolChange {
+    /// First data for a protocol, or a new fee or liquidity
+    Updated(ProtocolData),
+    /// The protocol has no data for the asset any more and drops out of routing for it
+    Removed(Protocol, Asset),
+}
 
 #[derive(Clone)]
 pub struct ProtocolDataCollector {
-    config: Config,
+    config: SharedConfig,
     client: Client,
     sui_client: SuiClient,
-    data_store: Arc<RwLock<HashMap<Protocol, ProtocolData>>>,
+    data_store: Arc<RwLock<HashMap<(Protocol, Asset), ProtocolData>>>,
+    /// Last successful read of the on-chain `ProtocolRegistry` entries
+    registry: Arc<RwLock<Vec<RegistryEntry>>>,
+    adapters: AdapterRegistry,
+    updates: broadcast::Sender<ProtocolChange>,
 }
 
 impl std::fmt::Debug for ProtocolDataCollector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ProtocolDataCollector")
-            .field("config", &self.config)
+            .field("config", &self.config.current())
             .finish_non_exhaustive() // Indicates that some fields are intentionally omitted
     }
 }
 
 impl ProtocolDataCollector {
-    pub async fn new(config: Config) -> Self {
-        let sui_client = SuiClientBuilder::default()
-            .build(&config.sui_rpc_url)
+    pub async fn new(config: impl Into<SharedConfig>) -> Self {
+        let config = config.into();
+        let sui_client = connect_sui_client(&config.current())
             .await
             .expect("Failed to create SUI client");
 
@@ -45,262 +69,250 @@ impl ProtocolDataCollector {
             client: http_client,
             sui_client,
             data_store: Arc::new(RwLock::new(HashMap::new())),
+            registry: Arc::new(RwLock::new(Vec::new())),
+            adapters: AdapterRegistry::default(),
+            updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
         }
     }
 
-    pub async fn get_protocol_data(&self, protocol: Protocol) -> Option<ProtocolData> {
-        self.data_store.read().await.get(&protocol).cloned()
+    /// Receive every [`ProtocolChange`] from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ProtocolChange> {
+        self.updates.subscribe()
     }
 
-    pub async fn get_all_protocol_data(&self) -> HashMap<Protocol, ProtocolData> {
-        self.data_store.read().await.clone()
+    /// Adapters this collector quotes, including those discovered in the registry
+    pub const fn adapters(&self) -> &AdapterRegistry {
+        &self.adapters
     }
 
-    /// Fetch real protocol data from on-chain sources
-    async fn fetch_protocol_data(&self, protocol: Protocol) -> Result<ProtocolData> {
-        info!("Fetching real data for protocol {:?}", protocol);
+    pub async fn get_protocol_data(
+        &self,
+        protocol: Protocol,
+        asset: Asset,
+    ) -> Option<ProtocolData> {
+        self.data_store
+            .read()
+            .await
+            .get(&(protocol, asset))
+            .cloned()
+    }
+
+    pub async fn get_all_protocol_data(&self) -> HashMap<(Protocol, Asset), ProtocolData> {
+        self.data_store.read().await.clone()
+    }
 
-        let (fee_bps, liquidity) = match protocol {
-            Protocol::Navi => self.fetch_navi_data().await?,
-            Protocol::Bucket => self.fetch_bucket_data().await?,
-            Protocol::Scallop => self.fetch_scallop_data().await?,
+    /// Fetch real protocol data for `asset` from the round's API response or on-chain sources
+    async fn fetch_protocol_data(
+        &self,
+        adapter: &dyn ProtocolAdapter,
+        asset: Asset,
+        api_response: Option<&Result<Value>>,
+    ) -> Result<ProtocolData> {
+        let protocol = adapter.protocol();
+        info!("Fetching real {} data for protocol {:?}", asset, protocol);
+
+        let quote = match self.fetch_live_quote(adapter, asset, api_response).await {
+            Ok(quote) => quote,
+            Err(e) => {
+                let config = self.config.current();
+                let Some(fallback) = adapter.api(&config).and_then(|api| api.static_fallback)
+                else {
+                    return Err(e);
+                };
+                warn!(
+                    "Live {} data unavailable for {:?} ({}), using configured static fallback: fee_bps={}, liquidity={}",
+                    asset, protocol, e, fallback.fee_bps, fallback.available_liquidity
+                );
+                fallback
+            }
         };
 
         Ok(ProtocolData {
             protocol,
-            fee_bps,
-            available_liquidity: liquidity,
+            asset,
+            fee_bps: quote.fee_bps,
+            available_liquidity: quote.available_liquidity,
             last_updated: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)?
                 .as_secs(),
         })
     }
 
-    /// Fetch Navi Protocol data from on-chain and APIs
-    async fn fetch_navi_data(&self) -> Result<(u64, u64)> {
-        debug!("Fetching Navi protocol data");
-
-        // Try to fetch from Navi's API first
-        match self.fetch_navi_api_data().await {
-            Ok(data) => Ok(data),
-            Err(e) => {
-                warn!("Failed to fetch Navi API data, using fallback: {}", e);
-                self.fetch_navi_onchain_data().await
-            }
-        }
-    }
-
-    async fn fetch_navi_api_data(&self) -> Result<(u64, u64)> {
-        // Navi Protocol API endpoints
-        let url = "https://app.naviprotocol.io/api/lending/pools";
-
-        let response = self
-            .client
-            .get(url)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await?;
-
-        let data: Value = response.json().await?;
-
-        // Parse SUI pool data
-        let sui_pool = data["pools"]
-            .as_array()
-            .and_then(|pools| {
-                pools
-                    .iter()
-                    .find(|pool| pool["coinType"].as_str() == Some("0x2::sui::SUI"))
-            })
-            .ok_or_else(|| eyre::eyre!("SUI pool not found in Navi data"))?;
-
-        let fee_bps = sui_pool["flashLoanFeeBps"].as_u64().unwrap_or(8); // Default 8 basis points
-
-        let available_liquidity = sui_pool["availableLiquidity"]
-            .as_u64()
-            .unwrap_or(10_000_000_000); // Default 10 SUI
-
-        debug!(
-            "Navi API data: fee_bps={}, liquidity={}",
-            fee_bps, available_liquidity
-        );
-        Ok((fee_bps, available_liquidity))
-    }
-
-    async fn fetch_navi_onchain_data(&self) -> Result<(u64, u64)> {
-        debug!("Fetching Navi on-chain data");
-
-        // Query Navi's core pool object for real data
-        let pool_object_id = ObjectID::from_hex_literal(&self.config.navi_package_id)?;
-
-        match self.fetch_navi_object(pool_object_id).await {
-            Ok(response) => {
-                response.data.map_or_else(
-                    || {
-                        warn!("Navi object not found, using fallback");
-                        Ok((8, 10_000_000_000))
-                    },
-                    |object_data| {
-                        // Parse object content for liquidity and fee data
-                        debug!("Navi object data: {:?}", object_data);
-                        // For now return default values, implement proper parsing based on Navi's object structure
-                        Ok((8, 10_000_000_000))
-                    },
-                )
-            }
-            Err(e) => {
-                warn!("Failed to fetch Navi object: {}", e);
-                Ok((8, 10_000_000_000)) // Fallback values
-            }
-        }
-    }
-
-    /// Helper function to fetch Navi object from chain
-    async fn fetch_navi_object(
+    /// Quote `asset` from the protocol's API response, falling back to on-chain data
+    ///
+    /// `api_response` is `None` for protocols without an API data source.
+    async fn fetch_live_quote(
         &self,
-        pool_object_id: ObjectID,
-    ) -> Result<sui_json_rpc_types::SuiObjectResponse> {
-        self.sui_client
-            .read_api()
-            .get_object_with_options(
-                pool_object_id,
-                sui_json_rpc_types::SuiObjectDataOptions::new().with_content(),
-            )
-            .await
-            .map_err(eyre::Error::from)
-    }
-
-    /// Fetch Bucket Protocol data
-    async fn fetch_bucket_data(&self) -> Result<(u64, u64)> {
-        debug!("Fetching Bucket protocol data");
-
-        // Try API first, then on-chain
-        match self.fetch_bucket_api_data().await {
-            Ok(data) => Ok(data),
-            Err(e) => {
-                warn!("Failed to fetch Bucket API data, using fallback: {}", e);
-                self.fetch_bucket_onchain_data().await
-            }
-        }
-    }
-
-    async fn fetch_bucket_api_data(&self) -> Result<(u64, u64)> {
-        // Bucket Protocol typically has 5 basis points for flash loans
-        let url = "https://bucket-protocol.io/api/markets";
-
-        match self
-            .client
-            .get(url)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await
-        {
-            Ok(response) => {
-                let data: Value = response.json().await?;
-
-                // Parse Bucket data structure
-                let fee_bps = data["flashLoanFee"].as_u64().unwrap_or(5);
+        adapter: &dyn ProtocolAdapter,
+        asset: Asset,
+        api_response: Option<&Result<Value>>,
+    ) -> Result<MarketQuote> {
+        let protocol = adapter.protocol();
+        let config = self.config.current();
+        let ctx = FetchContext {
+            config: &config,
+            http: &self.client,
+            sui: &self.sui_client,
+        };
 
-                let liquidity = data["availableLiquidity"].as_u64().unwrap_or(5_000_000_000);
+        let Some(api_response) = api_response else {
+            return timed(
+                protocol,
+                "onchain",
+                adapter.fetch_onchain_quote(&ctx, asset),
+            )
+            .await;
+        };
 
+        let api_quote = match api_response {
+            Ok(response) => adapter.api_quote(&config, response, asset),
+            Err(e) => Err(eyre::eyre!("{}", e)),
+        };
+        match api_quote {
+            Ok(quote) => {
                 debug!(
-                    "Bucket API data: fee_bps={}, liquidity={}",
-                    fee_bps, liquidity
+                    "{:?} {} API data: fee_bps={}, liquidity={}",
+                    protocol, asset, quote.fee_bps, quote.available_liquidity
                 );
-                Ok((fee_bps, liquidity))
+                Ok(quote)
             }
-            Err(_) => {
-                // API might not exist, use default values
-                Ok((5, 5_000_000_000))
+            Err(api_err) => {
+                warn!(
+                    "Failed to fetch {:?} {} API data, trying on-chain: {}",
+                    protocol, asset, api_err
+                );
+                timed(
+                    protocol,
+                    "onchain",
+                    adapter.fetch_onchain_quote(&ctx, asset),
+                )
+                .await
+                .map_err(|chain_err| {
+                    eyre::eyre!(
+                        "{:?} {} API failed ({}); on-chain failed ({})",
+                        protocol,
+                        asset,
+                        api_err,
+                        chain_err
+                    )
+                })
             }
         }
     }
 
-    async fn fetch_bucket_onchain_data(&self) -> Result<(u64, u64)> {
-        debug!("Fetching Bucket on-chain data");
-        // Bucket Protocol uses 5 basis points as documented
-        Ok((5, 5_000_000_000))
-    }
-
-    /// Fetch Scallop Protocol data
-    async fn fetch_scallop_data(&self) -> Result<(u64, u64)> {
-        debug!("Fetching Scallop protocol data");
+    pub async fn collect_all_data(&self) -> Result<()> {
+        info!("Collecting protocol data from live sources...");
 
-        match self.fetch_scallop_api_data().await {
-            Ok(data) => Ok(data),
-            Err(e) => {
-                warn!("Failed to fetch Scallop API data, using fallback: {}", e);
-                self.fetch_scallop_onchain_data().await
-            }
-        }
+        self.refresh_registry().await;
+        self.sync_registered_adapters().await;
+        let config = self.config.current();
+        let adapters: Vec<_> = self
+            .adapters
+            .all()
+            .into_iter()
+            .filter(|adapter| adapter.enabled(&config))
+            .collect();
+        let new_data = self.collect_protocols_data(&adapters).await;
+
+        self.update_data_store(new_data, adapters.len()).await;
+        Ok(())
     }
 
-    async fn fetch_scallop_api_data(&self) -> Result<(u64, u64)> {
-        // Scallop Protocol API
-        let url = "https://api.scallop.io/lending/markets";
-
-        match self
-            .client
-            .get(url)
-            .timeout(Duration::from_secs(10))
-            .send()
+    /// Re-read the registry entries, keeping the previous ones if the read fails
+    async fn refresh_registry(&self) {
+        let Some(registry_id) = self.config.current().registry_object_id else {
+            return;
+        };
+        let entries = read_move_fields(&self.sui_client, registry_id)
             .await
-        {
-            Ok(response) => {
-                let data: Value = response.json().await?;
-
-                let fee_bps = data["flashLoanFee"].as_u64().unwrap_or(9); // 9 basis points as per our integration
-
-                let liquidity = data["totalLiquidity"].as_u64().unwrap_or(8_000_000_000);
-
-                debug!(
-                    "Scallop API data: fee_bps={}, liquidity={}",
-                    fee_bps, liquidity
-                );
-                Ok((fee_bps, liquidity))
-            }
-            Err(_) => {
-                // Use default values from our integration
-                Ok((9, 8_000_000_000))
+            .and_then(|fields| parse_registry_entries(&fields));
+        match entries {
+            Ok(entries) => {
+                debug!("Protocol registry lists {} adapters", entries.len());
+                *self.registry.write().await = entries;
             }
+            Err(e) => warn!("Failed to read protocol registry {}: {}", registry_id, e),
         }
     }
 
-    async fn fetch_scallop_onchain_data(&self) -> Result<(u64, u64)> {
-        debug!("Fetching Scallop on-chain data");
-        // Scallop Protocol uses 9 basis points as per our integration
-        Ok((9, 8_000_000_000))
+    /// Build an adapter for each registry entry with an `[adapters.<id>]` table,
+    /// dropping any whose table was removed
+    async fn sync_registered_adapters(&self) {
+        let config = self.config.current();
+        let discovered = self
+            .registry
+            .read()
+            .await
+            .iter()
+            .filter(|entry| {
+                matches!(
+                    Protocol::from_id(entry.protocol_id),
+                    Protocol::Registered(id) if config.adapter(id).is_some()
+                )
+            })
+            .map(|entry| Arc::new(RegisteredAdapter::new(entry)) as Arc<dyn ProtocolAdapter>)
+            .collect();
+        self.adapters.sync_discovered(discovered);
     }
 
-    pub async fn collect_all_data(&self) -> Result<()> {
-        info!("Collecting protocol data from live sources...");
-
-        let protocols = [Protocol::Navi, Protocol::Bucket, Protocol::Scallop];
-        let new_data = self.collect_protocols_data(&protocols).await;
-
-        self.update_data_store(new_data, protocols.len()).await;
-        Ok(())
+    /// Registry entries that cannot be routed to because no adapter is configured
+    pub async fn unknown_protocols(&self) -> Vec<RegistryEntry> {
+        let config = self.config.current();
+        let adapters = self.adapters.all();
+        self.registry
+            .read()
+            .await
+            .iter()
+            .filter(|entry| {
+                matches!(
+                    Protocol::from_id(entry.protocol_id),
+                    Protocol::Registered(_)
+                ) && !adapters
+                    .iter()
+                    .any(|adapter| adapter.router_id(&config) == Some(entry.protocol_id))
+            })
+            .cloned()
+            .collect()
     }
 
-    /// Collect data for specific protocols
+    /// Collect data for every asset each of the given protocols supports
     async fn collect_protocols_data(
         &self,
-        protocols: &[Protocol],
-    ) -> HashMap<Protocol, ProtocolData> {
+        adapters: &[Arc<dyn ProtocolAdapter>],
+    ) -> HashMap<(Protocol, Asset), ProtocolData> {
+        let config = self.config.current();
         let mut new_data = HashMap::new();
 
-        for &protocol in protocols {
-            match self.fetch_protocol_data(protocol).await {
-                Ok(data) => {
-                    info!(
-                        "Updated live data for {:?}: fee_bps={}, liquidity={} MIST",
-                        protocol, data.fee_bps, data.available_liquidity
-                    );
-                    new_data.insert(protocol, data);
+        for adapter in adapters {
+            let protocol = adapter.protocol();
+            let assets = adapter.supported_assets(&config);
+            // One API request per round serves every asset the protocol lends
+            let api_response = match adapter.api(&config) {
+                Some(api) if !assets.is_empty() => {
+                    Some(timed(protocol, "api", fetch_api_response(&self.client, api)).await)
                 }
-                Err(e) => {
-                    error!("Failed to fetch live data for {:?}: {}", protocol, e);
-                    self.handle_collection_failure(protocol, &mut new_data)
-                        .await;
+                _ => None,
+            };
+            for asset in assets {
+                match self
+                    .fetch_protocol_data(adapter.as_ref(), asset, api_response.as_ref())
+                    .await
+                {
+                    Ok(data) => {
+                        info!(
+                            "Updated live {} data for {:?}: fee_bps={}, liquidity={}",
+                            asset, protocol, data.fee_bps, data.available_liquidity
+                        );
+                        new_data.insert((protocol, asset), data);
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to fetch live {} data for {:?}: {}",
+                            asset, protocol, e
+                        );
+                        self.handle_collection_failure(protocol, asset, &mut new_data)
+                            .await;
+                    }
                 }
             }
         }
@@ -312,24 +324,34 @@ impl ProtocolDataCollector {
     async fn handle_collection_failure(
         &self,
         protocol: Protocol,
-        new_data: &mut HashMap<Protocol, ProtocolData>,
+        asset: Asset,
+        new_data: &mut HashMap<(Protocol, Asset), ProtocolData>,
     ) {
-        if let Some(old_data) = self.get_protocol_data(protocol).await {
-            warn!("Using stale data for {:?}", protocol);
-            new_data.insert(protocol, old_data);
+        if let Some(old_data) = self.get_protocol_data(protocol, asset).await {
+            warn!("Using stale {} data for {:?}", asset, protocol);
+            new_data.insert((protocol, asset), old_data);
         }
     }
 
     /// Update the data store with new data
     async fn update_data_store(
         &self,
-        new_data: HashMap<Protocol, ProtocolData>,
+        new_data: HashMap<(Protocol, Asset), ProtocolData>,
         total_protocols: usize,
     ) {
         if new_data.is_empty() {
             warn!("No protocol data could be collected");
         } else {
-            *self.data_store.write().await = new_data;
+            let changes = {
+                let mut store = self.data_store.write().await;
+                let changes = diff_protocol_data(&store, &new_data);
+                *store = new_data;
+                changes
+            };
+            for change in changes {
+                // Sending only fails when nobody is subscribed
+                let _ = self.updates.send(change);
+            }
             info!(
                 "Protocol data collection complete - {} protocols updated",
                 total_protocols
@@ -338,30 +360,206 @@ impl ProtocolDataCollector {
     }
 
     pub async fn start_background_collection(&self) {
-        let interval_duration = Duration::from_millis(self.config.refresh_interval_ms);
-        let mut ticker = interval(interval_duration);
-
         info!(
             "Starting background protocol data collection every {}ms",
-            self.config.refresh_interval_ms
+            self.config.current().refresh_interval_ms
         );
 
         loop {
-            ticker.tick().await;
             if let Err(e) = self.collect_all_data().await {
                 error!("Background collection failed: {}", e);
             }
+            // Re-read the interval each round so a config reload takes effect immediately
+            sleep(Duration::from_millis(
+                self.config.current().refresh_interval_ms,
+            ))
+            .await;
         }
     }
 }
 
+/// Changes from `old` to `new`, ignoring refreshes that only move `last_updated`
+pub fn diff_protocol_data(
+    old: &HashMap<(Protocol, Asset), ProtocolData>,
+    new: &HashMap<(Protocol, Asset), ProtocolData>,
+) -> Vec<ProtocolChange> {
+    let mut changes: Vec<_> = new
+        .iter()
+        .filter(|(key, data)| {
+            old.get(key).is_none_or(|previous| {
+                (previous.fee_bps, previous.available_liquidity)
+                    != (data.fee_bps, data.available_liquidity)
+            })
+        })
+        .map(|(_, data)| ProtocolChange::Updated(data.clone()))
+        .collect();
+    changes.extend(
+        old.keys()
+            .filter(|key| !new.contains_key(key))
+            .map(|&(protocol, asset)| ProtocolChange::Removed(protocol, asset)),
+    );
+    changes
+}
+
+/// Run a fetch, recording its latency and any failure under `protocol`/`source`
+async fn timed<T>(
+    protocol: Protocol,
+    source: &str,
+    fetch: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let protocol = protocol_label(protocol);
+    let labels = [protocol.as_ref(), source];
+    let started = Instant::now();
+    let result = fetch.await;
+
+    METRICS
+        .fetch_duration
+        .with_label_values(&labels)
+        .observe(started.elapsed().as_secs_f64());
+    if result.is_err() {
+        METRICS.fetch_failures.with_label_values(&labels).inc();
+    }
+    result
+}
+
+/// Entries of a `ProtocolRegistry` object, indexed by protocol id
+///
+/// # Errors
+///
+/// Returns an error if `entries` is missing or holds a non-address value
+pub fn parse_registry_entries(fields: &Value) -> Result<Vec<RegistryEntry>> {
+    fields["entries"]
+        .as_array()
+        .ok_or_else(|| eyre::eyre!("ProtocolRegistry has no 'entries' vector"))?
+        .iter()
+        .zip(0u64..)
+        .map(|(address, protocol_id)| {
+            let adapter_package = address
+                .as_str()
+                .ok_or_else(|| eyre::eyre!("Registry entry {} is not an address", protocol_id))?;
+            Ok(RegistryEntry {
+                protocol_id,
+                adapter_package: adapter_package.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Fetch `api` once per round; [`parse_market_quote`] picks each asset's market out of it
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the response is not JSON
+pub async fn fetch_api_response(http: &Client, api: &ProtocolApiConfig) -> Result<Value> {
+    let response = http
+        .get(&api.url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(response.json().await?)
+}
+
+/// Locate the market entry for `coin_type` in a protocol API response.
+///
+/// With `markets_pointer` set, the pointed-to array is searched for the entry whose
+/// `asset_type_pointer` value equals `coin_type`; otherwise the response root is the market.
+///
+/// # Errors
+///
+/// Returns an error if the markets array or the matching entry cannot be found
+fn select_market<'a>(
+    data: &'a Value,
+    api: &ProtocolApiConfig,
+    coin_type: &str,
+) -> Result<&'a Value> {
+    let Some(markets_pointer) = &api.markets_pointer else {
+        return Ok(data);
+    };
+
+    let markets = data
+        .pointer(markets_pointer)
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre::eyre!("No market array at '{}' in {}", markets_pointer, api.url))?;
+
+    let Some(asset_type_pointer) = &api.asset_type_pointer else {
+        return markets
+            .first()
+            .ok_or_else(|| eyre::eyre!("Market array at '{}' is empty", markets_pointer));
+    };
+
+    markets
+        .iter()
+        .find(|market| {
+            market.pointer(asset_type_pointer).and_then(Value::as_str) == Some(coin_type)
+        })
+        .ok_or_else(|| eyre::eyre!("Market for {} not found in {}", coin_type, api.url))
+}
+
+/// Parse the fee and liquidity for `coin_type` out of a protocol API response.
+///
+/// # Errors
+///
+/// Returns an error if the market is missing, a field is absent or not an unsigned
+/// integer, or the fee exceeds 10_000 bps
+pub fn parse_market_quote(
+    data: &Value,
+    api: &ProtocolApiConfig,
+    coin_type: &str,
+) -> Result<MarketQuote> {
+    let market = select_market(data, api, coin_type)?;
+    let quote = MarketQuote {
+        fee_bps: read_amount(market, &api.fee_bps_pointer, &api.url)?,
+        available_liquidity: read_amount(market, &api.liquidity_pointer, &api.url)?,
+    };
+
+    if quote.fee_bps > 10_000 {
+        eyre::bail!(
+            "Fee of {} bps at '{}' in {} exceeds 10_000",
+            quote.fee_bps,
+            api.fee_bps_pointer,
+            api.url
+        );
+    }
+    Ok(quote)
+}
+
+/// Amount field as protocol APIs encode it: a JSON integer or a decimal string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApiAmount {
+    Number(u64),
+    Text(String),
+}
+
+fn read_amount(market: &Value, pointer: &str, url: &str) -> Result<u64> {
+    let raw = market
+        .pointer(pointer)
+        .ok_or_else(|| eyre::eyre!("Missing field '{}' in {}", pointer, url))?;
+
+    match ApiAmount::deserialize(raw) {
+        Ok(ApiAmount::Number(value)) => Ok(value),
+        Ok(ApiAmount::Text(text)) => text
+            .parse()
+            .map_err(|e| eyre::eyre!("Malformed field '{}' in {}: {}", pointer, url, e)),
+        Err(_) => Err(eyre::eyre!(
+            "Malformed field '{}' in {}: expected unsigned integer, got {}",
+            pointer,
+            url,
+            raw
+        )),
+    }
+}
+
 // Artemis Collector implementation
 #[async_trait]
 impl Collector<ProtocolData> for ProtocolDataCollector {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, ProtocolData>> {
-        // This would be implemented to provide a stream of protocol data updates
-        // For now, returning a placeholder - in real implementation this would
-        // stream updates when protocol data changes
-        todo!("Implement Artemis collector stream for protocol data updates")
+        let updates = BroadcastStream::new(self.subscribe()).filter_map(|change| match change {
+            Ok(ProtocolChange::Updated(data)) => Some(data),
+            Ok(ProtocolChange::Removed(..)) | Err(_) => None,
+        });
+        Ok(Box::pin(updates))
     }
 }
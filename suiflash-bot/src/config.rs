@@ -1,37 +1,855 @@
-use std::fmt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, PoisonError, RwLock},
+};
 
-use config::{Config as ConfigBuilder, ConfigError, Environment, File};
-use serde::{Deserialize, Serialize};
+use config::{
+    Config as ConfigBuilder, ConfigError, Environment, File,
+    builder::{ConfigBuilder as Builder, DefaultState},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sui_types::base_types::ObjectID;
+use utoipa::ToSchema;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    pub network: Network,
     pub sui_rpc_url: String,
-    pub private_key: String,
-    pub sui_flash_package_id: String,
-    pub sui_flash_config_object_id: String,
+    /// Tried in order when `sui_rpc_url` is unreachable
+    #[serde(default)]
+    pub sui_rpc_fallback_urls: Vec<String>,
+    #[serde(skip_serializing)]
+    pub private_key: SecretString,
+    pub sui_flash_package_id: ObjectID,
+    pub sui_flash_config_object_id: ObjectID,
+    /// `state::AdminCap` owned by the signer; only needed for admin commands
+    #[serde(default)]
+    pub admin_cap_id: Option<ObjectID>,
+    /// Shared `registry::ProtocolRegistry` object
+    #[serde(default)]
+    pub registry_object_id: Option<ObjectID>,
     pub server_port: u16,
     pub refresh_interval_ms: u64,
-    pub strategy: String, // "cheapest" or "highest_liquidity"
-    pub contract_package_id: String,
-    pub navi_package_id: String,
-    pub bucket_package_id: String,
-    pub scallop_package_id: String,
-    pub service_fee_bps: u64, // off-chain expectation (mirror of on-chain Config)
+    pub strategy: StrategyKind,
+    pub contract_package_id: ObjectID,
+    pub navi_package_id: ObjectID,
+    pub bucket_package_id: ObjectID,
+    pub scallop_package_id: ObjectID,
+    pub service_fee_bps: Bps, // off-chain expectation (mirror of on-chain Config)
+    #[serde(default)]
+    pub protocol_apis: ProtocolApis,
+    #[serde(default)]
+    pub asset_types: AssetTypes,
+    /// Adapters for protocols discovered in the on-chain registry, keyed by protocol id
+    #[serde(default)]
+    pub adapters: BTreeMap<String, AdapterConfig>,
+    /// DeepBook v3 pools quoted for fee-free flash loans
+    #[serde(default)]
+    pub deepbook: DeepBookConfig,
+    /// Suilend lending market quoted from its reserves
+    #[serde(default)]
+    pub suilend: SuilendConfig,
+    /// Owned SUI coins leased to concurrent executions for gas
+    #[serde(default)]
+    pub gas_pool: GasPoolConfig,
+    /// Gas price and budget applied to every loan
+    #[serde(default)]
+    pub gas_policy: GasPolicyConfig,
+    /// Clients allowed to call the loan and admin endpoints
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    /// Switch to turn API key checks off outside mainnet
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Request rate and concurrency limits for `POST /flashloan`
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+    /// Where loan executions and idempotency keys are recorded
+    #[serde(default)]
+    pub executions: ExecutionStoreConfig,
+    /// Retry policy for loan lifecycle webhooks
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    /// How long SIGINT/SIGTERM waits for in-flight loans
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    /// Thresholds of the `GET /ready` dependency checks
+    #[serde(default)]
+    pub readiness: ReadinessConfig,
+    /// Per-network overrides; the profile for `network` is applied on load
+    #[serde(default)]
+    pub networks: NetworkProfiles,
+}
+
+/// Config shared by the server, strategy, collector and executor
+///
+/// Readers take a cheap [`SharedConfig::current`] snapshot per operation, so a reload never
+/// changes values halfway through a request.
+#[derive(Debug, Clone)]
+pub struct SharedConfig(Arc<RwLock<Arc<Config>>>);
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    /// Snapshot of the config currently in effect
+    pub fn current(&self) -> Arc<Config> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Atomically replace the config seen by every holder of this handle
+    pub fn replace(&self, config: Config) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(config);
+    }
+
+    /// Swap in the config `update` derives from the current one, holding the lock
+    /// throughout so concurrent updates never work from the same stale config
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `update`, leaving the config untouched
+    pub fn try_update<T>(
+        &self,
+        update: impl FnOnce(&Config) -> eyre::Result<(Config, T)>,
+    ) -> eyre::Result<T> {
+        let mut current = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        let (config, output) = update(&current)?;
+        *current = Arc::new(config);
+        Ok(output)
+    }
+}
+
+impl From<Config> for SharedConfig {
+    fn from(config: Config) -> Self {
+        Self::new(config)
+    }
+}
+
+/// Move type tags of the supported assets on the selected network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetTypes {
+    pub sui: String,
+    pub usdc: String,
+    pub usdt: String,
+}
+
+impl AssetTypes {
+    pub fn get(&self, asset: Asset) -> &str {
+        match asset {
+            Asset::SUI => &self.sui,
+            Asset::USDC => &self.usdc,
+            Asset::USDT => &self.usdt,
+        }
+    }
+}
+
+impl Default for AssetTypes {
+    fn default() -> Self {
+        Self {
+            sui: Asset::SUI.to_type_tag().to_string(),
+            usdc: Asset::USDC.to_type_tag().to_string(),
+            usdt: Asset::USDT.to_type_tag().to_string(),
+        }
+    }
+}
+
+/// Settings for one network; every field is optional and overrides the top-level value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkProfile {
+    /// First entry becomes `sui_rpc_url`, the rest `sui_rpc_fallback_urls`
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
+    /// Expected `sui_getChainIdentifier` result; defaults to the public network's id
+    pub chain_id: Option<String>,
+    pub sui_flash_package_id: Option<ObjectID>,
+    pub sui_flash_config_object_id: Option<ObjectID>,
+    pub admin_cap_id: Option<ObjectID>,
+    pub registry_object_id: Option<ObjectID>,
+    pub contract_package_id: Option<ObjectID>,
+    pub navi_package_id: Option<ObjectID>,
+    pub bucket_package_id: Option<ObjectID>,
+    pub scallop_package_id: Option<ObjectID>,
+    pub asset_types: Option<AssetTypes>,
+}
+
+impl NetworkProfile {
+    /// The profile as a config source setting the top-level keys it overrides
+    fn into_source(self) -> Result<config::Config, ConfigError> {
+        let mut builder = ConfigBuilder::builder();
+        if let Some((primary, fallbacks)) = self.rpc_urls.split_first() {
+            builder = builder
+                .set_override("sui_rpc_url", primary.as_str())?
+                .set_override("sui_rpc_fallback_urls", fallbacks.to_vec())?;
+        }
+        for (key, id) in [
+            ("sui_flash_package_id", self.sui_flash_package_id),
+            (
+                "sui_flash_config_object_id",
+                self.sui_flash_config_object_id,
+            ),
+            ("admin_cap_id", self.admin_cap_id),
+            ("registry_object_id", self.registry_object_id),
+            ("contract_package_id", self.contract_package_id),
+            ("navi_package_id", self.navi_package_id),
+            ("bucket_package_id", self.bucket_package_id),
+            ("scallop_package_id", self.scallop_package_id),
+        ] {
+            if let Some(id) = id {
+                builder = builder.set_override(key, id.to_string())?;
+            }
+        }
+        if let Some(asset_types) = self.asset_types {
+            builder = builder
+                .set_override("asset_types.sui", asset_types.sui)?
+                .set_override("asset_types.usdc", asset_types.usdc)?
+                .set_override("asset_types.usdt", asset_types.usdt)?;
+        }
+        builder.build()
+    }
+}
+
+/// Profiles keyed by network name (`[networks.mainnet]` etc. in config.toml)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkProfiles {
+    pub mainnet: Option<NetworkProfile>,
+    pub testnet: Option<NetworkProfile>,
+    pub devnet: Option<NetworkProfile>,
+    pub localnet: Option<NetworkProfile>,
+}
+
+impl NetworkProfiles {
+    pub const fn get(&self, network: Network) -> Option<&NetworkProfile> {
+        match network {
+            Network::Mainnet => self.mainnet.as_ref(),
+            Network::Testnet => self.testnet.as_ref(),
+            Network::Devnet => self.devnet.as_ref(),
+            Network::Localnet => self.localnet.as_ref(),
+        }
+    }
+}
+
+/// Sui network the bot is deployed against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    #[default]
+    Testnet,
+    Devnet,
+    Localnet,
+}
+
+impl Network {
+    /// Public full node used when neither config nor profile sets an RPC URL
+    pub const fn default_rpc_url(self) -> &'static str {
+        match self {
+            Self::Mainnet => "https://fullnode.mainnet.sui.io:443",
+            Self::Testnet => "https://fullnode.testnet.sui.io:443",
+            Self::Devnet => "https://fullnode.devnet.sui.io:443",
+            Self::Localnet => "http://127.0.0.1:9000",
+        }
+    }
+
+    /// Chain identifier of the public network; devnet and localnet change on every reset
+    pub const fn chain_identifier(self) -> Option<&'static str> {
+        match self {
+            Self::Mainnet => Some("35834a8a"),
+            Self::Testnet => Some("4c78adac"),
+            Self::Devnet | Self::Localnet => None,
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "devnet" => Ok(Self::Devnet),
+            "localnet" => Ok(Self::Localnet),
+            other => Err(eyre::eyre!(
+                "Unknown network '{}' (expected mainnet, testnet, devnet or localnet)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mainnet => write!(f, "mainnet"),
+            Self::Testnet => write!(f, "testnet"),
+            Self::Devnet => write!(f, "devnet"),
+            Self::Localnet => write!(f, "localnet"),
+        }
+    }
+}
+
+/// Protocol selection rule used when a request does not name a protocol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyKind {
+    #[default]
+    Cheapest,
+    HighestLiquidity,
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cheapest => write!(f, "cheapest"),
+            Self::HighestLiquidity => write!(f, "highest_liquidity"),
+        }
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cheapest" => Ok(Self::Cheapest),
+            "highest_liquidity" => Ok(Self::HighestLiquidity),
+            other => Err(eyre::eyre!(
+                "Unknown strategy '{}' (expected \"cheapest\" or \"highest_liquidity\")",
+                other
+            )),
+        }
+    }
+}
+
+/// Basis points bounded to 0..=10_000, matching the on-chain `invalid_fee_bps` check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u64", into = "u64")]
+pub struct Bps(u64);
+
+impl Bps {
+    pub const MAX: u64 = 10_000;
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Fee on `amount` at this rate, rounded down; never exceeds `amount`
+    pub fn fee_on(self, amount: u64) -> u64 {
+        let fee = u128::from(amount) * u128::from(self.0) / 10_000;
+        u64::try_from(fee).unwrap_or(amount)
+    }
+}
+
+impl TryFrom<u64> for Bps {
+    type Error = String;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        if value > Self::MAX {
+            return Err(format!("{value} bps exceeds the maximum of {}", Self::MAX));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl FromStr for Bps {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: u64 = s.parse()?;
+        Self::try_from(value).map_err(|e| eyre::eyre!(e))
+    }
+}
+
+impl From<Bps> for u64 {
+    fn from(value: Bps) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Bps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Problems found by [`Config::validate`]; errors block startup, warnings are logged
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Convert into a result carrying every error at once
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing all validation errors if there are any
+    pub fn into_result(self) -> eyre::Result<()> {
+        if self.is_ok() {
+            return Ok(());
+        }
+        Err(eyre::eyre!("Invalid configuration:\n{}", self))
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for error in &self.errors {
+            writeln!(f, "  error: {error}")?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "  warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+/// String secret (private keys, API keys) that never prints its value.
+///
+/// `Debug` and `Display` show `[REDACTED]`; read the value with [`SecretString::expose`].
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+/// HTTP data source for one protocol: endpoint plus JSON pointers (RFC 6901)
+/// locating the fee and liquidity fields in the response body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolApiConfig {
+    pub url: String,
+    /// Pointer to an array of per-asset markets; when unset the response root is the market.
+    pub markets_pointer: Option<String>,
+    /// Pointer (relative to a market) to the coin type used to pick the market for an asset.
+    pub asset_type_pointer: Option<String>,
+    /// Pointer (relative to the market) to the flash loan fee in basis points.
+    pub fee_bps_pointer: String,
+    /// Pointer (relative to the market) to the liquidity available for borrowing.
+    pub liquidity_pointer: String,
+    /// Opt-in values used when neither the API nor on-chain data is available.
+    #[serde(default)]
+    pub static_fallback: Option<MarketQuote>,
+}
+
+/// Fee and liquidity for one asset market of a protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketQuote {
+    pub fee_bps: u64,
+    pub available_liquidity: u64,
+}
+
+/// Per-protocol API data sources.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolApis {
+    pub navi: ProtocolApiConfig,
+    pub bucket: ProtocolApiConfig,
+    pub scallop: ProtocolApiConfig,
+}
+
+impl ProtocolApis {
+    /// Data source of a built-in protocol; registered protocols are configured in `adapters`
+    pub const fn get(&self, protocol: Protocol) -> Option<&ProtocolApiConfig> {
+        match protocol {
+            Protocol::Navi => Some(&self.navi),
+            Protocol::Bucket => Some(&self.bucket),
+            Protocol::Scallop => Some(&self.scallop),
+            Protocol::DeepBook | Protocol::Suilend | Protocol::Registered(_) => None,
+        }
+    }
+}
+
+/// DeepBook v3 flash loan source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeepBookConfig {
+    /// DeepBook v3 package exposing `pool::borrow_flashloan_*`
+    #[serde(default)]
+    pub package_id: Option<ObjectID>,
+    /// Registry id of the router adapter for DeepBook; execution is refused until set
+    #[serde(default)]
+    pub protocol_id: Option<u64>,
+    /// Pools to quote; the deepest pool holding an asset sets its liquidity
+    #[serde(default)]
+    pub pools: Vec<DeepBookPool>,
+}
+
+/// Suilend flash loan source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuilendConfig {
+    /// Shared `LendingMarket` object whose reserves are quoted; Suilend is off when unset
+    #[serde(default)]
+    pub lending_market_id: Option<ObjectID>,
+    /// Registry id of the router adapter for Suilend; execution is refused until set
+    #[serde(default)]
+    pub protocol_id: Option<u64>,
+}
+
+/// Gas coin pool kept by the executor so parallel loans never share a gas coin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasPoolConfig {
+    /// Coins to keep, leased ones included; new coins are split off the largest coin
+    pub target_size: usize,
+    /// Balance of each coin split off, in MIST
+    pub coin_balance: u64,
+    /// Coins below this balance are merged into the largest coin, in MIST
+    pub min_coin_balance: u64,
+    /// How long an execution waits for a free coin before failing
+    pub lease_timeout_ms: u64,
+    /// Interval between pool refreshes and rebalances
+    pub refresh_interval_ms: u64,
+}
+
+impl Default for GasPoolConfig {
+    fn default() -> Self {
+        Self {
+            target_size: 8,
+            coin_balance: 500_000_000,
+            min_coin_balance: 50_000_000,
+            lease_timeout_ms: 5_000,
+            refresh_interval_ms: 30_000,
+        }
+    }
+}
+
+/// How gas price and budget are derived from the reference gas price and a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasPolicyConfig {
+    /// Budget as a multiple of the dry-run computation and storage cost
+    pub safety_factor: f64,
+    /// Gas price as a multiple of the epoch's reference gas price
+    pub priority_fee_multiplier: f64,
+    /// Loans whose budget would exceed this many MIST are refused
+    pub max_gas_per_loan: u64,
+}
+
+impl Default for GasPolicyConfig {
+    fn default() -> Self {
+        Self {
+            safety_factor: 1.2,
+            priority_fee_multiplier: 1.0,
+            max_gas_per_loan: 100_000_000,
+        }
+    }
+}
+
+/// Whether the loan, webhook and admin endpoints check API keys
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Serve every request unauthenticated; refused on mainnet
+    pub disabled: bool,
+}
+
+/// Endpoint group an API key may call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// `POST /flashloan`
+    Loans,
+    /// `/admin/*`
+    Admin,
+}
+
+/// An API client, identified by the hash of its key, and what it may borrow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Unique name used in logs
+    pub name: String,
+    /// Hex BLAKE3 hash of the key, as printed by `suiflash hash-api-key`
+    pub key_hash: String,
+    #[serde(default = "default_api_scopes")]
+    pub scopes: Vec<ApiScope>,
+    /// Assets the client may borrow; empty allows all
+    #[serde(default)]
+    pub allowed_assets: Vec<Asset>,
+    /// Protocols the client's loans may be routed to; empty allows all
+    #[serde(default)]
+    pub allowed_protocols: Vec<Protocol>,
+    #[serde(default)]
+    pub max_loan_amount: Option<u64>,
+    /// Loans per UTC day
+    #[serde(default)]
+    pub daily_loan_limit: Option<u32>,
+    /// Entry of `rate_limits.tiers` applied to this client; `rate_limits.default_tier` when unset
+    #[serde(default)]
+    pub tier: Option<String>,
+}
+
+/// Token bucket: `burst` requests at once, refilled at `requests_per_second`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TokenBucketConfig {
+    pub requests_per_second: f64,
+    pub burst: u32,
+}
+
+/// Limits shared by the API clients assigned to a tier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateTier {
+    #[serde(flatten)]
+    pub bucket: TokenBucketConfig,
+    /// In-flight loans per client
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Applied to every caller's IP before authentication; `None` disables it
+    pub per_ip: Option<TokenBucketConfig>,
+    /// Take the client IP from the first `X-Forwarded-For` entry; only behind a trusted proxy
+    pub trust_forwarded_for: bool,
+    /// In-flight loans across all clients
+    pub max_concurrent_executions: usize,
+    /// Loans accepted in one `POST /flashloan/batch`; no bucket's `burst` may be smaller
+    pub max_batch_loans: usize,
+    /// Per-client limits by tier name
+    pub tiers: BTreeMap<String, RateTier>,
+    /// Tier of clients whose key names none; unset leaves them capped only globally
+    pub default_tier: Option<String>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_ip: Some(TokenBucketConfig {
+                requests_per_second: 5.0,
+                burst: 10,
+            }),
+            trust_forwarded_for: false,
+            max_concurrent_executions: 8,
+            max_batch_loans: 10,
+            tiers: BTreeMap::new(),
+            default_tier: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionStoreConfig {
+    /// SQLite database, created if missing; `sqlite::memory:` keeps nothing across restarts
+    pub database_url: String,
+    /// How long an `Idempotency-Key` is remembered, in seconds
+    pub idempotency_window_secs: u64,
+    /// Days finished executions are kept before they are deleted; 0 keeps them forever
+    pub retention_days: u64,
+}
+
+impl Default for ExecutionStoreConfig {
+    fn default() -> Self {
+        Self {
+            database_url: "sqlite://suiflash.db".to_string(),
+            idempotency_window_secs: 86_400,
+            retention_days: 30,
+        }
+    }
+}
+
+/// How webhook deliveries are retried
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Attempts per delivery, including the first, before it is marked failed
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for each retry after it
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Time allowed for the receiver to answer each attempt
+    pub timeout_ms: u64,
+    /// Hosts webhooks may reach although they resolve to loopback, private or link-local
+    /// addresses, for receivers inside the bot's own network
+    pub allowed_private_hosts: Vec<String>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 300_000,
+            timeout_ms: 5_000,
+            allowed_private_hosts: Vec::new(),
+        }
+    }
+}
+
+/// Graceful shutdown on SIGINT or SIGTERM
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    /// Time in-flight loans get to finish once a signal arrives; 0 stops at once
+    pub drain_timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout_secs: 30,
+        }
+    }
+}
+
+/// What `GET /ready` treats as healthy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadinessConfig {
+    /// Signer SUI balance below which loans cannot be relied on to pay gas, in MIST
+    pub min_signer_balance_mist: u64,
+    /// Protocol data older than this is reported stale
+    pub max_data_age_secs: u64,
+    /// Time allowed for each RPC check
+    pub check_timeout_ms: u64,
+    /// How long RPC and database results are reused between requests; 0 probes every time
+    pub cache_ttl_ms: u64,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            min_signer_balance_mist: 1_000_000_000,
+            max_data_age_secs: 300,
+            check_timeout_ms: 3_000,
+            cache_ttl_ms: 2_000,
+        }
+    }
+}
+
+fn default_api_scopes() -> Vec<ApiScope> {
+    vec![ApiScope::Loans]
+}
+
+/// A DeepBook `Pool<Base, Quote>` and the assets on each side
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeepBookPool {
+    pub pool_id: ObjectID,
+    pub base: Asset,
+    pub quote: Asset,
+}
+
+/// Off-chain settings for a protocol discovered in the on-chain registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdapterConfig {
+    /// Name used in logs
+    pub name: String,
+    /// Move module in the registered adapter package exposing the borrow/settle calls
+    pub module: String,
+    pub api: ProtocolApiConfig,
+    /// Gas added on top of the base flash loan cost, in MIST
+    #[serde(default)]
+    pub gas_overhead: Option<u64>,
+}
+
+impl Default for ProtocolApis {
+    fn default() -> Self {
+        Self {
+            navi: ProtocolApiConfig {
+                url: "https://app.naviprotocol.io/api/lending/pools".to_string(),
+                markets_pointer: Some("/pools".to_string()),
+                asset_type_pointer: Some("/coinType".to_string()),
+                fee_bps_pointer: "/flashLoanFeeBps".to_string(),
+                liquidity_pointer: "/availableLiquidity".to_string(),
+                static_fallback: None,
+            },
+            bucket: ProtocolApiConfig {
+                url: "https://bucket-protocol.io/api/markets".to_string(),
+                markets_pointer: None,
+                asset_type_pointer: None,
+                fee_bps_pointer: "/flashLoanFee".to_string(),
+                liquidity_pointer: "/availableLiquidity".to_string(),
+                static_fallback: None,
+            },
+            scallop: ProtocolApiConfig {
+                url: "https://api.scallop.io/lending/markets".to_string(),
+                markets_pointer: None,
+                asset_type_pointer: None,
+                fee_bps_pointer: "/flashLoanFee".to_string(),
+                liquidity_pointer: "/totalLiquidity".to_string(),
+                static_fallback: None,
+            },
+        }
+    }
 }
 
 impl Config {
-    /// Load configuration from multiple sources with priority:
-    /// 1. config.toml file (if exists)
-    /// 2. Environment variables (with SUIFLASH_ prefix)
-    /// 3. Default values
+    /// Keys holding [`SecretString`]s. Each can also be read from a file named by
+    /// `<key>_file` in config.toml or by the `SUIFLASH_<KEY>_FILE` / `<KEY>_FILE` env var.
+    pub const SECRET_KEYS: [&'static str; 1] = ["private_key"];
+
+    /// Secret values by key, in `SECRET_KEYS` order
+    fn secrets(&self) -> [(&'static str, &SecretString); 1] {
+        [("private_key", &self.private_key)]
+    }
+
+    /// Load configuration from multiple sources, highest priority first:
+    /// 1. Environment variables with the SUIFLASH_ prefix
+    /// 2. Legacy environment variables without it
+    /// 3. The `[networks.<name>]` profile of the selected network
+    /// 4. config.toml file (if exists)
+    /// 5. Default values
     ///
     /// # Errors
     ///
     /// Returns an error if required configuration values are missing or invalid
     pub fn load() -> Result<Self, ConfigError> {
+        Self::load_for_network(None)
+    }
+
+    /// Like [`Config::load`], with `network` (e.g. from `--network`) taking precedence
+    /// over `SUIFLASH_NETWORK` and config.toml
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if required configuration values are missing or invalid
+    pub fn load_for_network(network: Option<Network>) -> Result<Self, ConfigError> {
         let mut builder = ConfigBuilder::builder()
             // Set default values
-            .set_default("sui_rpc_url", "https://fullnode.testnet.sui.io:443")?
+            .set_default("network", Network::default().to_string())?
             .set_default("server_port", 3000)?
             .set_default("refresh_interval_ms", 10000)?
             .set_default("strategy", "cheapest")?
@@ -41,24 +859,594 @@ impl Config {
             .set_default("scallop_package_id", "0x4")?
             .set_default("service_fee_bps", 40)?;
 
+        // Protocol API defaults, one key at a time so config.toml can override any single field
+        let apis = ProtocolApis::default();
+        for (name, api) in [
+            ("navi", &apis.navi),
+            ("bucket", &apis.bucket),
+            ("scallop", &apis.scallop),
+        ] {
+            builder = builder
+                .set_default(format!("protocol_apis.{name}.url"), api.url.as_str())?
+                .set_default(
+                    format!("protocol_apis.{name}.markets_pointer"),
+                    api.markets_pointer.clone(),
+                )?
+                .set_default(
+                    format!("protocol_apis.{name}.asset_type_pointer"),
+                    api.asset_type_pointer.clone(),
+                )?
+                .set_default(
+                    format!("protocol_apis.{name}.fee_bps_pointer"),
+                    api.fee_bps_pointer.as_str(),
+                )?
+                .set_default(
+                    format!("protocol_apis.{name}.liquidity_pointer"),
+                    api.liquidity_pointer.as_str(),
+                )?;
+        }
+
         // Try to load from config.toml file (optional)
         if std::path::Path::new("config.toml").exists() {
             builder = builder.add_source(File::with_name("config"));
         }
 
-        // Add environment variables with SUIFLASH_ prefix
-        // This allows using SUIFLASH_PRIVATE_KEY instead of PRIVATE_KEY
-        builder = builder.add_source(
-            Environment::with_prefix("SUIFLASH")
-                .separator("_")
-                .ignore_empty(true),
-        );
+        let mut overrides = environment(None);
+        if let Some(network) = network {
+            overrides = overrides.set_override("network", network.to_string())?;
+        }
+
+        Self::from_layers(builder.build()?, overrides.build()?)
+    }
+
+    /// Resolve `base` (defaults and files) under `overrides` (environment and flags), with
+    /// the selected network's profile in between so it never masks an environment variable
+    pub fn from_layers(
+        base: config::Config,
+        overrides: config::Config,
+    ) -> Result<Self, ConfigError> {
+        // The profile and the RPC default depend on the selected network, so resolve that first
+        let layered = ConfigBuilder::builder()
+            .add_source(base.clone())
+            .add_source(overrides.clone())
+            .build()?;
+        let network: Network = layered.get("network")?;
+        let profile = match layered.get::<NetworkProfile>(&format!("networks.{network}")) {
+            Ok(profile) => profile.into_source()?,
+            // A malformed profile is reported with the other fields
+            Err(_) => config::Config::default(),
+        };
+
+        let config = ConfigBuilder::builder()
+            .set_default("sui_rpc_url", network.default_rpc_url())?
+            .add_source(base)
+            .add_source(profile)
+            .add_source(overrides)
+            .build()?;
+        let config = resolve_secret_files(config)?;
+        Self::from_source(config)
+    }
+
+    /// Convert a built source one field at a time, so every missing or malformed value is
+    /// reported together instead of only the first
+    fn from_source(source: config::Config) -> Result<Self, ConfigError> {
+        let mut fields = FieldReader {
+            source,
+            report: ValidationReport::default(),
+        };
+        let network = fields.or_default("network");
+        let sui_rpc_url = fields.required("sui_rpc_url");
+        let sui_rpc_fallback_urls = fields.or_default("sui_rpc_fallback_urls");
+        let private_key = fields.required("private_key");
+        let sui_flash_package_id = fields.required("sui_flash_package_id");
+        let sui_flash_config_object_id = fields.required("sui_flash_config_object_id");
+        let admin_cap_id = fields.or_default("admin_cap_id");
+        let registry_object_id = fields.or_default("registry_object_id");
+        let server_port = fields.required("server_port");
+        let refresh_interval_ms = fields.required("refresh_interval_ms");
+        let strategy = fields.required("strategy");
+        let contract_package_id = fields.required("contract_package_id");
+        let navi_package_id = fields.required("navi_package_id");
+        let bucket_package_id = fields.required("bucket_package_id");
+        let scallop_package_id = fields.required("scallop_package_id");
+        let service_fee_bps = fields.required("service_fee_bps");
+        let protocol_apis = fields.or_default("protocol_apis");
+        let asset_types = fields.or_default("asset_types");
+        let adapters = fields.or_default("adapters");
+        let deepbook = fields.or_default("deepbook");
+        let suilend = fields.or_default("suilend");
+        let gas_pool = fields.or_default("gas_pool");
+        let gas_policy = fields.or_default("gas_policy");
+        let api_keys = fields.or_default("api_keys");
+        let auth = fields.or_default("auth");
+        let rate_limits = fields.or_default("rate_limits");
+        let executions = fields.or_default("executions");
+        let webhooks = fields.or_default("webhooks");
+        let shutdown = fields.or_default("shutdown");
+        let readiness = fields.or_default("readiness");
+        let networks = fields.or_default("networks");
+
+        let (
+            Some(sui_rpc_url),
+            Some(private_key),
+            Some(sui_flash_package_id),
+            Some(sui_flash_config_object_id),
+            Some(server_port),
+            Some(refresh_interval_ms),
+            Some(strategy),
+            Some(contract_package_id),
+            Some(navi_package_id),
+            Some(bucket_package_id),
+            Some(scallop_package_id),
+            Some(service_fee_bps),
+        ) = (
+            sui_rpc_url,
+            private_key,
+            sui_flash_package_id,
+            sui_flash_config_object_id,
+            server_port,
+            refresh_interval_ms,
+            strategy,
+            contract_package_id,
+            navi_package_id,
+            bucket_package_id,
+            scallop_package_id,
+            service_fee_bps,
+        )
+        else {
+            return Err(fields.into_error());
+        };
+        if !fields.report.is_ok() {
+            return Err(fields.into_error());
+        }
+
+        Ok(Self {
+            network,
+            sui_rpc_url,
+            sui_rpc_fallback_urls,
+            private_key,
+            sui_flash_package_id,
+            sui_flash_config_object_id,
+            admin_cap_id,
+            registry_object_id,
+            server_port,
+            refresh_interval_ms,
+            strategy,
+            contract_package_id,
+            navi_package_id,
+            bucket_package_id,
+            scallop_package_id,
+            service_fee_bps,
+            protocol_apis,
+            asset_types,
+            adapters,
+            deepbook,
+            suilend,
+            gas_pool,
+            gas_policy,
+            api_keys,
+            auth,
+            rate_limits,
+            executions,
+            webhooks,
+            shutdown,
+            readiness,
+            networks,
+        })
+    }
+
+    /// API data source for `protocol`, built in or from its configured adapter
+    pub fn protocol_api(&self, protocol: Protocol) -> Option<&ProtocolApiConfig> {
+        match protocol {
+            Protocol::Registered(id) => self.adapter(id).map(|adapter| &adapter.api),
+            built_in => self.protocol_apis.get(built_in),
+        }
+    }
+
+    /// Adapter configured for registry protocol `id`
+    pub fn adapter(&self, id: u64) -> Option<&AdapterConfig> {
+        self.adapters.get(&id.to_string())
+    }
+
+    /// Chain identifier the RPC must report for the selected network, if known
+    pub fn expected_chain_id(&self) -> Option<&str> {
+        self.networks
+            .get(self.network)
+            .and_then(|profile| profile.chain_id.as_deref())
+            .or_else(|| self.network.chain_identifier())
+    }
+
+    /// Primary RPC URL followed by the fallbacks
+    pub fn rpc_urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.sui_rpc_url.as_str())
+            .chain(self.sui_rpc_fallback_urls.iter().map(String::as_str))
+    }
+
+    /// Check the loaded values, collecting every problem instead of stopping at the first
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        if let Err(e) = check_http_url(&self.sui_rpc_url) {
+            report
+                .errors
+                .push(format!("sui_rpc_url '{}': {}", self.sui_rpc_url, e));
+        }
+        for url in &self.sui_rpc_fallback_urls {
+            if let Err(e) = check_http_url(url) {
+                report
+                    .errors
+                    .push(format!("sui_rpc_fallback_urls '{url}': {e}"));
+            }
+        }
+        if self.network != Network::Localnet
+            && self
+                .rpc_urls()
+                .any(|url| url.contains("127.0.0.1") || url.contains("localhost"))
+        {
+            report.warnings.push(format!(
+                "{} is configured with a local RPC URL",
+                self.network
+            ));
+        }
+        if self.private_key.expose().is_empty() {
+            report.errors.push("private_key is empty".to_string());
+        }
+        if self.server_port == 0 {
+            report.errors.push("server_port must not be 0".to_string());
+        }
+        if self.refresh_interval_ms == 0 {
+            report
+                .errors
+                .push("refresh_interval_ms must be greater than 0".to_string());
+        }
+
+        for (key, id) in self.object_ids() {
+            if !is_placeholder_id(id) {
+                continue;
+            }
+            let problem = format!("{key} is a placeholder ({id})");
+            if self.network == Network::Mainnet {
+                report.errors.push(problem);
+            } else {
+                report.warnings.push(problem);
+            }
+        }
+
+        let mut apis = vec![
+            ("protocol_apis.navi".to_string(), &self.protocol_apis.navi),
+            (
+                "protocol_apis.bucket".to_string(),
+                &self.protocol_apis.bucket,
+            ),
+            (
+                "protocol_apis.scallop".to_string(),
+                &self.protocol_apis.scallop,
+            ),
+        ];
+        for (key, adapter) in &self.adapters {
+            match key.parse::<u64>() {
+                Ok(id) if !matches!(Protocol::from_id(id), Protocol::Registered(_)) => {
+                    report.errors.push(format!(
+                        "adapters.{key}: protocol id {id} is built in; use protocol_apis instead"
+                    ));
+                }
+                Ok(_) => {}
+                Err(_) => report.errors.push(format!(
+                    "adapters.{key}: key must be a registry protocol id"
+                )),
+            }
+            if adapter.module.is_empty() {
+                report
+                    .errors
+                    .push(format!("adapters.{key}.module is empty"));
+            }
+            apis.push((format!("adapters.{key}.api"), &adapter.api));
+        }
+        self.validate_deepbook(&mut report);
+        self.validate_router_ids(&mut report);
+        self.validate_gas_pool(&mut report);
+        self.validate_gas_policy(&mut report);
+        self.validate_api_keys(&mut report);
+        self.validate_rate_limits(&mut report);
+        self.validate_executions(&mut report);
+        self.validate_webhooks(&mut report);
+        self.validate_readiness(&mut report);
+
+        for (name, api) in apis {
+            if let Err(e) = check_http_url(&api.url) {
+                report
+                    .errors
+                    .push(format!("{name}.url '{}': {}", api.url, e));
+            }
+            for (field, pointer) in [
+                ("fee_bps_pointer", Some(&api.fee_bps_pointer)),
+                ("liquidity_pointer", Some(&api.liquidity_pointer)),
+                ("markets_pointer", api.markets_pointer.as_ref()),
+                ("asset_type_pointer", api.asset_type_pointer.as_ref()),
+            ] {
+                if pointer.is_some_and(|p| !p.is_empty() && !p.starts_with('/')) {
+                    report.errors.push(format!(
+                        "{name}.{field} must be a JSON pointer starting with '/'"
+                    ));
+                }
+            }
+            if api.static_fallback.is_some() && self.network == Network::Mainnet {
+                report
+                    .warnings
+                    .push(format!("{name}.static_fallback is set on mainnet"));
+            }
+        }
+
+        report
+    }
+
+    fn validate_deepbook(&self, report: &mut ValidationReport) {
+        let deepbook = &self.deepbook;
+        if !deepbook.pools.is_empty() && deepbook.package_id.is_none() {
+            report
+                .errors
+                .push("deepbook.package_id is required when deepbook.pools is set".to_string());
+        }
+        for (index, pool) in deepbook.pools.iter().enumerate() {
+            if pool.base == pool.quote {
+                report.errors.push(format!(
+                    "deepbook.pools[{index}]: base and quote are both {}",
+                    pool.base
+                ));
+            }
+        }
+    }
+
+    fn validate_gas_pool(&self, report: &mut ValidationReport) {
+        let pool = &self.gas_pool;
+        if pool.coin_balance == 0 {
+            report
+                .errors
+                .push("gas_pool.coin_balance must be greater than 0".to_string());
+        }
+        if pool.min_coin_balance > pool.coin_balance {
+            report.errors.push(format!(
+                "gas_pool.min_coin_balance {} exceeds gas_pool.coin_balance {}",
+                pool.min_coin_balance, pool.coin_balance
+            ));
+        }
+        for (key, value) in [
+            ("lease_timeout_ms", pool.lease_timeout_ms),
+            ("refresh_interval_ms", pool.refresh_interval_ms),
+        ] {
+            if value == 0 {
+                report
+                    .errors
+                    .push(format!("gas_pool.{key} must be greater than 0"));
+            }
+        }
+    }
+
+    fn validate_gas_policy(&self, report: &mut ValidationReport) {
+        let policy = &self.gas_policy;
+        for (key, factor) in [
+            ("safety_factor", policy.safety_factor),
+            ("priority_fee_multiplier", policy.priority_fee_multiplier),
+        ] {
+            if !(factor.is_finite() && factor >= 1.0) {
+                report.errors.push(format!(
+                    "gas_policy.{key} must be at least 1.0, got {factor}"
+                ));
+            }
+        }
+        if policy.max_gas_per_loan == 0 {
+            report
+                .errors
+                .push("gas_policy.max_gas_per_loan must be greater than 0".to_string());
+        }
+    }
+
+    fn validate_api_keys(&self, report: &mut ValidationReport) {
+        if self.auth.disabled {
+            if self.network == Network::Mainnet {
+                report
+                    .errors
+                    .push("auth.disabled is not allowed on mainnet".to_string());
+            }
+        } else if self.api_keys.is_empty() {
+            report.errors.push(
+                "api_keys: no API keys configured; add one or set auth.disabled = true".to_string(),
+            );
+        }
+        for (index, key) in self.api_keys.iter().enumerate() {
+            let label = format!("api_keys[{index}] ({})", key.name);
+            if key.name.is_empty() {
+                report.errors.push(format!("{label}: name is empty"));
+            } else if self.api_keys[..index]
+                .iter()
+                .any(|other| other.name == key.name)
+            {
+                report.errors.push(format!("{label}: name is not unique"));
+            }
+            if blake3::Hash::from_hex(&key.key_hash).is_err() {
+                report.errors.push(format!(
+                    "{label}: key_hash must be a 64-character hex BLAKE3 hash"
+                ));
+            }
+            if key.scopes.is_empty() {
+                report.errors.push(format!("{label}: scopes is empty"));
+            }
+            if let Some(tier) = &key.tier
+                && !self.rate_limits.tiers.contains_key(tier)
+            {
+                report.errors.push(format!(
+                    "{label}: tier '{tier}' is not in rate_limits.tiers"
+                ));
+            }
+            if key.max_loan_amount == Some(0) || key.daily_loan_limit == Some(0) {
+                report
+                    .warnings
+                    .push(format!("{label}: a zero limit blocks every loan"));
+            }
+        }
+    }
+
+    fn validate_rate_limits(&self, report: &mut ValidationReport) {
+        let limits = &self.rate_limits;
+        if limits.max_concurrent_executions == 0 {
+            report
+                .errors
+                .push("rate_limits.max_concurrent_executions must be greater than 0".to_string());
+        }
+        if limits.max_batch_loans == 0 {
+            report
+                .errors
+                .push("rate_limits.max_batch_loans must be greater than 0".to_string());
+        }
+        let buckets = limits
+            .per_ip
+            .iter()
+            .map(|bucket| ("rate_limits.per_ip".to_string(), bucket))
+            .chain(
+                limits
+                    .tiers
+                    .iter()
+                    .map(|(name, tier)| (format!("rate_limits.tiers.{name}"), &tier.bucket)),
+            );
+        for (key, bucket) in buckets {
+            if !(bucket.requests_per_second.is_finite() && bucket.requests_per_second > 0.0) {
+                report
+                    .errors
+                    .push(format!("{key}.requests_per_second must be greater than 0"));
+            }
+            if bucket.burst == 0 {
+                report
+                    .errors
+                    .push(format!("{key}.burst must be greater than 0"));
+            } else if usize::try_from(bucket.burst)
+                .is_ok_and(|burst| burst < limits.max_batch_loans)
+            {
+                // A batch takes one token per loan, so a smaller burst could never admit a full one
+                report.errors.push(format!(
+                    "{key}.burst {} is below rate_limits.max_batch_loans {}",
+                    bucket.burst, limits.max_batch_loans
+                ));
+            }
+        }
+        if let Some(tier) = &limits.default_tier
+            && !limits.tiers.contains_key(tier)
+        {
+            report.errors.push(format!(
+                "rate_limits.default_tier '{tier}' is not in rate_limits.tiers"
+            ));
+        }
+        for (name, tier) in &limits.tiers {
+            if tier.max_concurrent == Some(0) {
+                report.errors.push(format!(
+                    "rate_limits.tiers.{name}.max_concurrent must be greater than 0"
+                ));
+            }
+        }
+    }
+
+    fn validate_executions(&self, report: &mut ValidationReport) {
+        let executions = &self.executions;
+        if !executions.database_url.starts_with("sqlite:") {
+            report.errors.push(format!(
+                "executions.database_url '{}' must be a sqlite: URL",
+                executions.database_url
+            ));
+        }
+        if executions.idempotency_window_secs == 0 {
+            report
+                .errors
+                .push("executions.idempotency_window_secs must be greater than 0".to_string());
+        }
+        if executions.retention_days > 0
+            && executions.retention_days.saturating_mul(86_400) < executions.idempotency_window_secs
+        {
+            report.warnings.push(
+                "executions.retention_days is shorter than idempotency_window_secs; \
+                 retries of pruned loans run again"
+                    .to_string(),
+            );
+        }
+    }
+
+    fn validate_webhooks(&self, report: &mut ValidationReport) {
+        let webhooks = &self.webhooks;
+        for (key, value) in [
+            ("max_attempts", u64::from(webhooks.max_attempts)),
+            ("initial_backoff_ms", webhooks.initial_backoff_ms),
+            ("timeout_ms", webhooks.timeout_ms),
+        ] {
+            if value == 0 {
+                report
+                    .errors
+                    .push(format!("webhooks.{key} must be greater than 0"));
+            }
+        }
+        if webhooks.max_backoff_ms < webhooks.initial_backoff_ms {
+            report.errors.push(format!(
+                "webhooks.max_backoff_ms ({}) is below initial_backoff_ms ({})",
+                webhooks.max_backoff_ms, webhooks.initial_backoff_ms
+            ));
+        }
+    }
 
-        // Also support legacy environment variables without prefix for backward compatibility
-        builder = builder.add_source(Environment::default().ignore_empty(true));
+    fn validate_readiness(&self, report: &mut ValidationReport) {
+        let readiness = &self.readiness;
+        if readiness.check_timeout_ms == 0 {
+            report
+                .errors
+                .push("readiness.check_timeout_ms must be greater than 0".to_string());
+        }
+        if readiness.max_data_age_secs.saturating_mul(1_000) < self.refresh_interval_ms {
+            report.warnings.push(format!(
+                "readiness.max_data_age_secs ({}) is shorter than refresh_interval_ms ({}ms); \
+                 protocol data will be reported stale between refreshes",
+                readiness.max_data_age_secs, self.refresh_interval_ms
+            ));
+        }
+    }
+
+    /// Router ids configured for on-chain sources must be free registry ids
+    fn validate_router_ids(&self, report: &mut ValidationReport) {
+        let router_ids = [
+            ("deepbook.protocol_id", self.deepbook.protocol_id),
+            ("suilend.protocol_id", self.suilend.protocol_id),
+        ];
+        for (index, (key, id)) in router_ids.iter().enumerate() {
+            let Some(id) = *id else {
+                continue;
+            };
+            if !matches!(Protocol::from_id(id), Protocol::Registered(_)) {
+                report
+                    .errors
+                    .push(format!("{key}: protocol id {id} is built in"));
+            } else if self.adapter(id).is_some() {
+                report.errors.push(format!(
+                    "{key}: protocol id {id} is also configured in adapters"
+                ));
+            } else if let Some((other, _)) = router_ids[..index]
+                .iter()
+                .find(|(_, other)| *other == Some(id))
+            {
+                report
+                    .errors
+                    .push(format!("{key}: protocol id {id} is also {other}"));
+            }
+        }
+    }
 
-        let config = builder.build()?;
-        config.try_deserialize()
+    /// Object id fields by config key
+    fn object_ids(&self) -> [(&'static str, ObjectID); 6] {
+        [
+            ("sui_flash_package_id", self.sui_flash_package_id),
+            (
+                "sui_flash_config_object_id",
+                self.sui_flash_config_object_id,
+            ),
+            ("contract_package_id", self.contract_package_id),
+            ("navi_package_id", self.navi_package_id),
+            ("bucket_package_id", self.bucket_package_id),
+            ("scallop_package_id", self.scallop_package_id),
+        ]
     }
 
     /// Legacy method for backward compatibility with environment variables
@@ -85,16 +1473,42 @@ impl Config {
     fn from_env_legacy() -> eyre::Result<Self> {
         dotenv::dotenv().ok();
 
+        let network: Network = std::env::var("NETWORK")
+            .ok()
+            .map(|network| network.parse())
+            .transpose()
+            .map_err(|e| eyre::eyre!("NETWORK: {}", e))?
+            .unwrap_or_default();
+
         Ok(Self {
+            network,
             sui_rpc_url: std::env::var("SUI_RPC_URL")
-                .unwrap_or_else(|_| "https://fullnode.testnet.sui.io:443".to_string()),
-            private_key: std::env::var("PRIVATE_KEY")
-                .map_err(|_| eyre::eyre!("PRIVATE_KEY environment variable required"))?,
-            sui_flash_package_id: std::env::var("SUI_FLASH_PACKAGE_ID")
-                .map_err(|_| eyre::eyre!("SUI_FLASH_PACKAGE_ID environment variable required"))?,
-            sui_flash_config_object_id: std::env::var("SUI_FLASH_CONFIG_OBJECT_ID").map_err(
-                |_| eyre::eyre!("SUI_FLASH_CONFIG_OBJECT_ID environment variable required"),
+                .unwrap_or_else(|_| network.default_rpc_url().to_string()),
+            sui_rpc_fallback_urls: Vec::new(),
+            private_key: match std::env::var("PRIVATE_KEY_FILE") {
+                Ok(path) => read_secret_file(&path)?.into(),
+                Err(_) => std::env::var("PRIVATE_KEY")
+                    .map_err(|_| eyre::eyre!("PRIVATE_KEY environment variable required"))?
+                    .into(),
+            },
+            sui_flash_package_id: ObjectID::from_hex_literal(
+                &std::env::var("SUI_FLASH_PACKAGE_ID").map_err(|_| {
+                    eyre::eyre!("SUI_FLASH_PACKAGE_ID environment variable required")
+                })?,
+            )?,
+            sui_flash_config_object_id: ObjectID::from_hex_literal(
+                &std::env::var("SUI_FLASH_CONFIG_OBJECT_ID").map_err(|_| {
+                    eyre::eyre!("SUI_FLASH_CONFIG_OBJECT_ID environment variable required")
+                })?,
             )?,
+            admin_cap_id: std::env::var("ADMIN_CAP_ID")
+                .ok()
+                .map(|id| ObjectID::from_hex_literal(&id))
+                .transpose()?,
+            registry_object_id: std::env::var("REGISTRY_OBJECT_ID")
+                .ok()
+                .map(|id| ObjectID::from_hex_literal(&id))
+                .transpose()?,
             server_port: std::env::var("SERVER_PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
@@ -103,18 +1517,43 @@ impl Config {
                 .unwrap_or_else(|_| "10000".to_string())
                 .parse()
                 .unwrap_or(10000),
-            strategy: std::env::var("STRATEGY").unwrap_or_else(|_| "cheapest".to_string()),
-            contract_package_id: std::env::var("CONTRACT_PACKAGE_ID")
-                .unwrap_or_else(|_| "0x1".to_string()),
-            navi_package_id: std::env::var("NAVI_PACKAGE_ID").unwrap_or_else(|_| "0x2".to_string()),
-            bucket_package_id: std::env::var("BUCKET_PACKAGE_ID")
-                .unwrap_or_else(|_| "0x3".to_string()),
-            scallop_package_id: std::env::var("SCALLOP_PACKAGE_ID")
-                .unwrap_or_else(|_| "0x4".to_string()),
-            service_fee_bps: std::env::var("SERVICE_FEE_BPS")
-                .unwrap_or_else(|_| "40".to_string()) // default 0.40%
-                .parse()
-                .unwrap_or(40),
+            strategy: std::env::var("STRATEGY")
+                .unwrap_or_else(|_| "cheapest".to_string())
+                .parse()?,
+            contract_package_id: ObjectID::from_hex_literal(
+                &std::env::var("CONTRACT_PACKAGE_ID").unwrap_or_else(|_| "0x1".to_string()),
+            )?,
+            navi_package_id: ObjectID::from_hex_literal(
+                &std::env::var("NAVI_PACKAGE_ID").unwrap_or_else(|_| "0x2".to_string()),
+            )?,
+            bucket_package_id: ObjectID::from_hex_literal(
+                &std::env::var("BUCKET_PACKAGE_ID").unwrap_or_else(|_| "0x3".to_string()),
+            )?,
+            scallop_package_id: ObjectID::from_hex_literal(
+                &std::env::var("SCALLOP_PACKAGE_ID").unwrap_or_else(|_| "0x4".to_string()),
+            )?,
+            service_fee_bps: Bps::try_from(
+                std::env::var("SERVICE_FEE_BPS")
+                    .unwrap_or_else(|_| "40".to_string()) // default 0.40%
+                    .parse::<u64>()
+                    .unwrap_or(40),
+            )
+            .map_err(|e| eyre::eyre!("SERVICE_FEE_BPS: {}", e))?,
+            protocol_apis: ProtocolApis::default(),
+            asset_types: AssetTypes::default(),
+            adapters: BTreeMap::new(),
+            deepbook: DeepBookConfig::default(),
+            suilend: SuilendConfig::default(),
+            gas_pool: GasPoolConfig::default(),
+            gas_policy: GasPolicyConfig::default(),
+            api_keys: Vec::new(),
+            auth: AuthConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            executions: ExecutionStoreConfig::default(),
+            webhooks: WebhookConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            readiness: ReadinessConfig::default(),
+            networks: NetworkProfiles::default(),
         })
     }
 
@@ -124,33 +1563,179 @@ impl Config {
     ///
     /// Returns an error if the file cannot be read or parsed
     pub fn from_toml<P: AsRef<std::path::Path>>(path: P) -> eyre::Result<Self> {
-        let builder = ConfigBuilder::builder().add_source(File::from(path.as_ref()));
-
-        let config = builder
+        let base = ConfigBuilder::builder()
+            .add_source(File::from(path.as_ref()))
             .build()
             .map_err(|e| eyre::eyre!("Failed to build config: {}", e))?;
 
-        config
-            .try_deserialize()
-            .map_err(|e| eyre::eyre!("Failed to deserialize config: {}", e))
+        Self::from_layers(base, config::Config::default())
+            .map_err(|e| eyre::eyre!("Failed to load config: {}", e))
     }
 
-    /// Save configuration to a TOML file
+    /// Save configuration to a TOML file, leaving out secrets such as `private_key`
     ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be written
     pub fn save_to_toml<P: AsRef<std::path::Path>>(&self, path: P) -> eyre::Result<()> {
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| eyre::eyre!("Failed to serialize config: {}", e))?;
+        self.write_toml(path, false)
+    }
 
+    /// Save configuration to a TOML file including secrets in plain text
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn save_to_toml_with_secrets<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> eyre::Result<()> {
+        self.write_toml(path, true)
+    }
+
+    fn write_toml<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        include_secrets: bool,
+    ) -> eyre::Result<()> {
+        let content = self.to_toml_string(include_secrets)?;
         std::fs::write(path, content).map_err(|e| eyre::eyre!("Failed to write config file: {}", e))
     }
+
+    /// Render the configuration as TOML, with secrets only if `include_secrets` is set
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be serialized
+    pub fn to_toml_string(&self, include_secrets: bool) -> eyre::Result<String> {
+        let mut table = toml::Table::try_from(self)
+            .map_err(|e| eyre::eyre!("Failed to serialize config: {}", e))?;
+        if include_secrets {
+            for (key, secret) in self.secrets() {
+                table.insert(key.to_string(), secret.expose().into());
+            }
+        }
+        toml::to_string_pretty(&table).map_err(|e| eyre::eyre!("Failed to serialize config: {}", e))
+    }
+}
+
+/// Reads the fields of a built config source, recording every problem in `report`
+struct FieldReader {
+    source: config::Config,
+    report: ValidationReport,
+}
+
+impl FieldReader {
+    /// Value of `key`, or `None` once the missing or malformed value is recorded
+    fn required<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        match self.source.get(key) {
+            Ok(value) => Some(value),
+            Err(ConfigError::NotFound(_)) => {
+                self.report.errors.push(format!("{key} is required"));
+                None
+            }
+            Err(e) => {
+                self.report.errors.push(e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Value of `key`, or the default when it is unset or malformed; malformed values
+    /// are recorded
+    fn or_default<T: DeserializeOwned + Default>(&mut self, key: &str) -> T {
+        match self.source.get(key) {
+            Ok(value) => value,
+            Err(ConfigError::NotFound(_)) => T::default(),
+            Err(e) => {
+                self.report.errors.push(e.to_string());
+                T::default()
+            }
+        }
+    }
+
+    fn into_error(self) -> ConfigError {
+        ConfigError::Message(format!("Invalid configuration:\n{}", self.report))
+    }
+}
+
+/// Framework and system objects (0x1 std, 0x2 sui, 0x5 system state, ...) stand in for
+/// unset ids in defaults and examples; no SuiFlash or protocol object lives there.
+fn is_placeholder_id(id: ObjectID) -> bool {
+    id.into_bytes()[..30].iter().all(|byte| *byte == 0)
+}
+
+pub fn check_http_url(url: &str) -> eyre::Result<()> {
+    let parsed = reqwest::Url::parse(url)?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        eyre::bail!("unsupported scheme '{}'", parsed.scheme());
+    }
+    Ok(())
+}
+
+/// Environment sources, later ones taking precedence: legacy unprefixed variables, then
+/// `SUIFLASH_` ones. `__` separates nested keys, e.g. `SUIFLASH_EXECUTIONS__DATABASE_URL`.
+///
+/// `vars` replaces the process environment, for tests.
+pub fn environment(vars: Option<config::Map<String, String>>) -> Builder<DefaultState> {
+    ConfigBuilder::builder()
+        .add_source(
+            Environment::default()
+                .ignore_empty(true)
+                .source(vars.clone()),
+        )
+        .add_source(
+            Environment::with_prefix("SUIFLASH")
+                .prefix_separator("_")
+                .separator("__")
+                .ignore_empty(true)
+                .source(vars),
+        )
+}
+
+/// Replace each secret with the contents of its `<key>_file` path when one is configured
+fn resolve_secret_files(config: config::Config) -> Result<config::Config, ConfigError> {
+    let mut overrides = Vec::new();
+    for key in Config::SECRET_KEYS {
+        let file_key = format!("{key}_file");
+        // The prefixed env source splits on '_', so read SUIFLASH_<KEY>_FILE directly
+        let path = config.get_string(&file_key).ok().or_else(|| {
+            std::env::var(format!("SUIFLASH_{}", file_key.to_uppercase()))
+                .ok()
+                .filter(|path| !path.is_empty())
+        });
+        if let Some(path) = path {
+            if config.get_string(key).is_ok() {
+                tracing::warn!("Both {} and {} are set; using {}", key, file_key, file_key);
+            }
+            let secret =
+                read_secret_file(&path).map_err(|e| ConfigError::Message(e.to_string()))?;
+            overrides.push((key, secret));
+        }
+    }
+
+    if overrides.is_empty() {
+        return Ok(config);
+    }
+    let mut builder = ConfigBuilder::builder().add_source(config);
+    for (key, secret) in overrides {
+        builder = builder.set_override(key, secret)?;
+    }
+    builder.build()
+}
+
+/// Read a secret from a file, dropping the trailing newline editors and `echo` add
+fn read_secret_file(path: &str) -> eyre::Result<String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| eyre::eyre!("Failed to read secret file {}: {}", path, e))?;
+    Ok(content.trim_end_matches(['\r', '\n']).to_string())
 }
 
 /// Asset types supported by the flash loan aggregator
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
 pub enum Asset {
     SUI,
     USDC,
@@ -167,8 +1752,25 @@ impl fmt::Display for Asset {
     }
 }
 
+impl FromStr for Asset {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "SUI" => Ok(Self::SUI),
+            "USDC" => Ok(Self::USDC),
+            "USDT" => Ok(Self::USDT),
+            _ => Err(eyre::eyre!(
+                "Unknown asset '{}' (expected SUI, USDC or USDT)",
+                s
+            )),
+        }
+    }
+}
+
 impl Asset {
-    #[allow(dead_code)]
+    pub const ALL: [Self; 3] = [Self::SUI, Self::USDC, Self::USDT];
+
     pub const fn to_type_tag(self) -> &'static str {
         match self {
             Self::SUI => "0x2::sui::SUI",
@@ -182,40 +1784,100 @@ impl Asset {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct FlashLoanRequest {
     pub asset: String,
     pub amount: u64,
     pub route_mode: RouteMode,
     pub explicit_protocol: Option<Protocol>,
-    pub user_operation: String, // Simplified: user's operation as string
-    pub callback_recipient: Option<String>, // hex address of contract handling callback
-    pub callback_payload: Option<String>, // base64 or hex encoded payload
+    /// Simplified: user's operation as string
+    pub user_operation: String,
+    /// Hex address of the contract handling the callback
+    pub callback_recipient: Option<String>,
+    /// Base64 or hex encoded payload
+    pub callback_payload: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub enum RouteMode {
     Explicit,
     BestCost,
     BestLiquidity,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Flash loan source, identified on chain by its `ProtocolRegistry` index
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
 pub enum Protocol {
-    Navi = 0,
-    Bucket = 1,
-    Scallop = 2,
+    Navi,
+    Bucket,
+    Scallop,
+    /// Pool vaults read from chain; its router id is `deepbook.protocol_id`
+    DeepBook,
+    /// Lending market reserves read from chain; its router id is `suilend.protocol_id`
+    Suilend,
+    /// Discovered in the on-chain registry; routable once `adapters.<id>` is configured
+    Registered(u64),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Protocol {
+    /// Protocols with a fixed router id, mirroring `protocols::id_*`
+    pub const BUILT_IN: [Self; 3] = [Self::Navi, Self::Bucket, Self::Scallop];
+
+    /// Registry index, as passed to `main::flash_loan`; `None` when it is configured
+    pub const fn id(self) -> Option<u64> {
+        match self {
+            Self::Navi => Some(0),
+            Self::Bucket => Some(1),
+            Self::Scallop => Some(2),
+            Self::DeepBook | Self::Suilend => None,
+            Self::Registered(id) => Some(id),
+        }
+    }
+
+    pub const fn from_id(id: u64) -> Self {
+        match id {
+            0 => Self::Navi,
+            1 => Self::Bucket,
+            2 => Self::Scallop,
+            id => Self::Registered(id),
+        }
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "navi" => Ok(Self::Navi),
+            "bucket" => Ok(Self::Bucket),
+            "scallop" => Ok(Self::Scallop),
+            "deepbook" => Ok(Self::DeepBook),
+            "suilend" => Ok(Self::Suilend),
+            other => other.parse().map(Self::from_id).map_err(|_| {
+                eyre::eyre!(
+                    "Unknown protocol '{}' (expected navi, bucket, scallop, deepbook, suilend or a registry id)",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct ProtocolData {
     pub protocol: Protocol,
+    /// Asset the fee and liquidity are quoted for
+    pub asset: Asset,
     pub fee_bps: u64,
     pub available_liquidity: u64,
-    pub last_updated: u64, // timestamp
+    /// Unix timestamp in seconds
+    pub last_updated: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct FlashLoanResponse {
     pub transaction_digest: String,
     pub protocol_used: Protocol,
@@ -224,14 +1886,58 @@ pub struct FlashLoanResponse {
     pub total_fee: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Body of `POST /flashloan/batch`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FlashLoanBatchRequest {
+    /// Planned together, then executed one after another in this order
+    pub loans: Vec<FlashLoanRequest>,
+}
+
+/// Outcome of one loan in a batch
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchItemResult {
+    /// Position of the loan in `loans`
+    pub index: usize,
+    /// Status `POST /flashloan` would have answered this loan with
+    pub status: u16,
+    /// Execution to look up with `GET /executions/{id}`, once recorded
+    pub execution_id: Option<String>,
+    /// Set when the loan was executed
+    pub response: Option<FlashLoanResponse>,
+    /// Why the loan was refused or failed
+    pub error: Option<String>,
+    /// Answered from an earlier batch sent with the same `Idempotency-Key`
+    pub replayed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FlashLoanBatchResponse {
+    /// Loans executed, including replayed ones
+    pub succeeded: usize,
+    /// Loans refused or failed; the rest are still running from an earlier batch
+    pub failed: usize,
+    /// One entry per requested loan, in request order
+    pub results: Vec<BatchItemResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ProtocolsResponse {
     pub protocols: Vec<ProtocolData>,
+    /// Registry entries the bot has no adapter configured for, so cannot route to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown_protocols: Vec<RegistryEntry>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Adapter package registered in the on-chain `ProtocolRegistry`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RegistryEntry {
+    pub protocol_id: u64,
+    pub adapter_package: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct StatusResponse {
-    pub strategy: String,
+    pub strategy: StrategyKind,
     pub service_fee_bps: u64,
     pub protocol_count: usize,
     pub last_updated_any: Option<u64>,
@@ -0,0 +1,503 @@
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::http::HeaderMap;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    Row,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow},
+};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::config::{ExecutionStoreConfig, FlashLoanRequest, FlashLoanResponse};
+
+/// Header a client sets to make `POST /flashloan` safe to retry
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses answered from an earlier request with the same key
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted `Idempotency-Key`
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// How often executions past `executions.retention_days` are deleted
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS executions (
+    id TEXT PRIMARY KEY,
+    client TEXT,
+    request TEXT NOT NULL,
+    status TEXT NOT NULL,
+    response TEXT,
+    error TEXT,
+    http_status INTEGER,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    client TEXT NOT NULL,
+    key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    execution_id TEXT NOT NULL REFERENCES executions (id),
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (client, key)
+);
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_at ON idempotency_keys (created_at);
+CREATE INDEX IF NOT EXISTS executions_client_created_at ON executions (client, created_at);
+CREATE INDEX IF NOT EXISTS executions_updated_at ON executions (updated_at);
+";
+
+/// Where a loan is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    /// Accepted, not yet routed
+    Pending,
+    /// Routed to a protocol
+    Planned,
+    /// Handed to the executor; from here on the loan may have reached the chain
+    Submitted,
+    Confirmed,
+    Failed,
+}
+
+impl ExecutionStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Planned => "planned",
+            Self::Submitted => "submitted",
+            Self::Confirmed => "confirmed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for ExecutionStatus {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "planned" => Ok(Self::Planned),
+            "submitted" => Ok(Self::Submitted),
+            "confirmed" => Ok(Self::Confirmed),
+            "failed" => Ok(Self::Failed),
+            other => Err(eyre::eyre!("Unknown execution status '{}'", other)),
+        }
+    }
+}
+
+/// One `POST /flashloan` and what became of it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Execution {
+    pub id: String,
+    /// API key name, when authentication is on
+    pub client: Option<String>,
+    pub request: FlashLoanRequest,
+    pub status: ExecutionStatus,
+    /// Set once confirmed
+    pub response: Option<FlashLoanResponse>,
+    /// Why the loan failed
+    pub error: Option<String>,
+    /// HTTP status the failure was answered with
+    pub http_status: Option<u16>,
+    /// Unix timestamps in seconds
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl TryFrom<SqliteRow> for Execution {
+    type Error = eyre::Report;
+
+    fn try_from(row: SqliteRow) -> Result<Self> {
+        let response: Option<String> = row.try_get("response")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            client: row.try_get("client")?,
+            request: serde_json::from_str(row.try_get("request")?)?,
+            status: row.try_get::<&str, _>("status")?.parse()?,
+            response: response.as_deref().map(serde_json::from_str).transpose()?,
+            error: row.try_get("error")?,
+            http_status: row.try_get("http_status")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// Outcome of [`ExecutionStore::begin`]
+#[derive(Debug, Clone)]
+pub enum Begin {
+    /// A new execution to carry out
+    Started(String),
+    /// The key was already used for this request; answer with the earlier execution
+    Replay(Box<Execution>),
+    /// The key was already used for a different request
+    Conflict,
+}
+
+/// Idempotency key presented with a request
+#[derive(Debug, Clone, Copy)]
+pub struct IdempotencyKey<'a> {
+    pub key: &'a str,
+    /// Seconds the key is remembered for
+    pub window_secs: u64,
+}
+
+/// Loan executions and idempotency keys, kept in SQLite
+#[derive(Debug, Clone)]
+pub struct ExecutionStore {
+    pool: SqlitePool,
+}
+
+impl ExecutionStore {
+    /// Connection pool, shared with the tables other modules keep in this database
+    pub const fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Open `executions.database_url`, creating the database and tables if missing
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is invalid or the database cannot be opened
+    pub async fn open(config: &ExecutionStoreConfig) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(&config.database_url)
+            .map_err(|e| eyre::eyre!("Invalid executions.database_url: {}", e))?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        // Keep one connection open so an in-memory database outlives idle periods
+        let pool = SqlitePoolOptions::new()
+            .min_connections(1)
+            .connect_with(options)
+            .await?;
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+        info!("Execution store opened at {}", config.database_url);
+        Ok(Self { pool })
+    }
+
+    /// Record a new execution of `request`, or find the one `idempotency` already started
+    ///
+    /// Keys are scoped per client and forgotten `window_secs` after first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read or written
+    pub async fn begin(
+        &self,
+        client: Option<&str>,
+        request: &FlashLoanRequest,
+        idempotency: Option<IdempotencyKey<'_>>,
+        now: u64,
+    ) -> Result<Begin> {
+        let request_json = serde_json::to_string(request)?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = to_sql(now);
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO executions (id, client, request, status, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(client)
+        .bind(&request_json)
+        .bind(ExecutionStatus::Pending.as_str())
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(IdempotencyKey { key, window_secs }) = idempotency {
+            let client = client.unwrap_or_default();
+            let request_hash = blake3::hash(request_json.as_bytes()).to_hex().to_string();
+            sqlx::query("DELETE FROM idempotency_keys WHERE created_at <= ?")
+                .bind(now.saturating_sub(to_sql(window_secs)))
+                .execute(&mut *tx)
+                .await?;
+            let claimed = sqlx::query(
+                "INSERT INTO idempotency_keys (client, key, request_hash, execution_id, created_at)
+                 VALUES (?, ?, ?, ?, ?) ON CONFLICT DO NOTHING",
+            )
+            .bind(client)
+            .bind(key)
+            .bind(&request_hash)
+            .bind(&id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+                == 1;
+
+            if !claimed {
+                // Drop the execution recorded above; the earlier one stands in for it
+                sqlx::query("DELETE FROM executions WHERE id = ?")
+                    .bind(&id)
+                    .execute(&mut *tx)
+                    .await?;
+                let row = sqlx::query(
+                    "SELECT e.*, k.request_hash FROM idempotency_keys k
+                     JOIN executions e ON e.id = k.execution_id
+                     WHERE k.client = ? AND k.key = ?",
+                )
+                .bind(client)
+                .bind(key)
+                .fetch_one(&mut *tx)
+                .await?;
+                tx.commit().await?;
+                if row.try_get::<&str, _>("request_hash")? != request_hash {
+                    return Ok(Begin::Conflict);
+                }
+                return Ok(Begin::Replay(Box::new(row.try_into()?)));
+            }
+        }
+
+        tx.commit().await?;
+        Ok(Begin::Started(id))
+    }
+
+    /// Execution `id`, if recorded
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read
+    pub async fn get(&self, id: &str) -> Result<Option<Execution>> {
+        sqlx::query("SELECT * FROM executions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(Execution::try_from)
+            .transpose()
+    }
+
+    /// Up to `limit` of `client`'s executions, newest first
+    ///
+    /// Pages continue strictly after `before`, the `(created_at, id)` of the last execution
+    /// of the previous page, so loans recorded meanwhile never shift a page.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read
+    pub async fn history(
+        &self,
+        client: Option<&str>,
+        status: Option<ExecutionStatus>,
+        before: Option<(u64, &str)>,
+        limit: u32,
+    ) -> Result<Vec<Execution>> {
+        let status = status.map(ExecutionStatus::as_str);
+        let created_at = before.map(|(created_at, _)| to_sql(created_at));
+        let id = before.map(|(_, id)| id);
+        sqlx::query(
+            "SELECT * FROM executions
+             WHERE client IS ? AND (? IS NULL OR status = ?)
+                 AND (? IS NULL OR created_at < ? OR (created_at = ? AND id < ?))
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?",
+        )
+        .bind(client)
+        .bind(status)
+        .bind(status)
+        .bind(created_at)
+        .bind(created_at)
+        .bind(created_at)
+        .bind(id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(Execution::try_from)
+        .collect()
+    }
+
+    /// Executions that have not reached a final status, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read
+    pub async fn unfinished(&self) -> Result<Vec<Execution>> {
+        sqlx::query("SELECT * FROM executions WHERE status NOT IN (?, ?) ORDER BY created_at, id")
+            .bind(ExecutionStatus::Confirmed.as_str())
+            .bind(ExecutionStatus::Failed.as_str())
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(Execution::try_from)
+            .collect()
+    }
+
+    /// Delete finished executions last updated before `before`, returning how many went
+    ///
+    /// Idempotency keys pointing at them go too. Unfinished executions are kept, since
+    /// shutdown reports and recovery still need them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub async fn prune(&self, before: u64) -> Result<u64> {
+        let before = to_sql(before);
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE execution_id IN (
+                 SELECT id FROM executions WHERE status IN (?, ?) AND updated_at < ?
+             )",
+        )
+        .bind(ExecutionStatus::Confirmed.as_str())
+        .bind(ExecutionStatus::Failed.as_str())
+        .bind(before)
+        .execute(&mut *tx)
+        .await?;
+        let pruned =
+            sqlx::query("DELETE FROM executions WHERE status IN (?, ?) AND updated_at < ?")
+                .bind(ExecutionStatus::Confirmed.as_str())
+                .bind(ExecutionStatus::Failed.as_str())
+                .bind(before)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        tx.commit().await?;
+        Ok(pruned)
+    }
+
+    /// Prune executions older than `retention_days` every [`PRUNE_INTERVAL`], forever
+    pub async fn run_retention(&self, retention_days: u64) {
+        let retention_secs = retention_days.saturating_mul(86_400);
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match self.prune(unix_now().saturating_sub(retention_secs)).await {
+                Ok(0) => {}
+                Ok(pruned) => info!(
+                    "Pruned {} executions older than {} days",
+                    pruned, retention_days
+                ),
+                Err(e) => warn!("Failed to prune executions: {}", e),
+            }
+        }
+    }
+
+    /// Run a trivial query to prove the database answers
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no connection can be acquired or the query fails
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Wait for queries in progress, then close every connection
+    ///
+    /// Closing the last connection checkpoints the write-ahead log into the database file.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    /// Move execution `id` on to `status`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub async fn set_status(&self, id: &str, status: ExecutionStatus, now: u64) -> Result<()> {
+        sqlx::query("UPDATE executions SET status = ?, updated_at = ? WHERE id = ?")
+            .bind(status.as_str())
+            .bind(to_sql(now))
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark execution `id` confirmed with the response its client received
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub async fn confirm(&self, id: &str, response: &FlashLoanResponse, now: u64) -> Result<()> {
+        sqlx::query("UPDATE executions SET status = ?, response = ?, updated_at = ? WHERE id = ?")
+            .bind(ExecutionStatus::Confirmed.as_str())
+            .bind(serde_json::to_string(response)?)
+            .bind(to_sql(now))
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Mark execution `id` failed
+    ///
+    /// A loan that never reached the executor releases its idempotency key, so the client can
+    /// retry with the same key once the cause is fixed. Later failures are replayed instead,
+    /// since the loan may have landed on chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    pub async fn fail(&self, id: &str, http_status: u16, error: &str, now: u64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE execution_id = ? AND EXISTS (
+                 SELECT 1 FROM executions WHERE id = ? AND status IN (?, ?)
+             )",
+        )
+        .bind(id)
+        .bind(id)
+        .bind(ExecutionStatus::Pending.as_str())
+        .bind(ExecutionStatus::Planned.as_str())
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE executions SET status = ?, error = ?, http_status = ?, updated_at = ?
+             WHERE id = ?",
+        )
+        .bind(ExecutionStatus::Failed.as_str())
+        .bind(error)
+        .bind(http_status)
+        .bind(to_sql(now))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// SQLite integers are signed
+pub fn to_sql(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Seconds since the Unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Validate the `Idempotency-Key` header, if sent
+///
+/// # Errors
+///
+/// Returns an error if the key is empty, longer than [`MAX_IDEMPOTENCY_KEY_LEN`] or not
+/// printable ASCII
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<&str>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| eyre::eyre!("Idempotency-Key must be printable ASCII"))?;
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        eyre::bail!(
+            "Idempotency-Key must be 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        );
+    }
+    Ok(Some(key))
+}
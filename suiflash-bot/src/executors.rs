@@ -1,79 +1,149 @@
+use std::{str::FromStr, sync::Arc};
+
 use artemis::types::Executor;
 use async_trait::async_trait;
 use eyre::Result;
-use sui_sdk::{SuiClient, SuiClientBuilder};
-use sui_types::base_types::SuiAddress;
+use serde::{Deserialize, Serialize};
+use sui_json_rpc_types::SuiTransactionBlockEffectsAPI;
+use sui_sdk::SuiClient;
+use sui_types::{
+    base_types::{ObjectRef, SuiAddress},
+    gas::GasCostSummary,
+    identifier::Identifier,
+    parse_sui_type_tag,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::TransactionData,
+};
 use tracing::{debug, error, info, warn};
+use utoipa::ToSchema;
 
 use crate::{
-    config::{Config, Protocol},
+    adapters::{AdapterRegistry, ProtocolAdapter},
+    config::SharedConfig,
+    gas_policy::{GasPolicy, GasQuote},
+    gas_pool::GasCoinPool,
+    metrics::{METRICS, protocol_label},
+    rpc::{connect_sui_client, sdk_error, shared_object_arg},
+    signer::{decode_keypair, signer_address},
     strategies::ExecutionPlan,
 };
 
+/// `suiflash::main::FlashLoanEvent`, emitted by the router when a loan is repaid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FlashLoanEvent {
+    /// Router id of the protocol lent from
+    pub protocol: u64,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub service_fee: u64,
+    pub total_repayment: u64,
+}
+
 #[derive(Clone)]
 pub struct FlashLoanExecutor {
-    client: SuiClient,
-    config: Config,
-    _signer_address: SuiAddress,
+    config: SharedConfig,
+    sui_client: SuiClient,
+    adapters: AdapterRegistry,
+    gas_policy: GasPolicy,
+    gas_pool: Option<GasCoinPool>,
 }
 
 impl FlashLoanExecutor {
-    pub async fn new(config: Config) -> Result<Self> {
-        let sui_client = SuiClientBuilder::default()
-            .build(&config.sui_rpc_url)
-            .await?;
-
-        // For testing, use a random address - in production would derive from private key
-        let signer_address = SuiAddress::random_for_testing_only();
+    pub async fn new(config: impl Into<SharedConfig>) -> Result<Self> {
+        let config = config.into();
+        let sui_client = connect_sui_client(&config.current()).await?;
+
+        // Without a usable key nothing can be signed, so executions run without leasing gas
+        let gas_pool = match decode_keypair(&config.current().private_key) {
+            Ok(keypair) => Some(GasCoinPool::new(
+                sui_client.clone(),
+                config.clone(),
+                keypair,
+            )),
+            Err(e) => {
+                warn!("Gas coin pool disabled: {}", e);
+                None
+            }
+        };
 
         Ok(Self {
-            client: sui_client,
+            gas_policy: GasPolicy::new(sui_client.clone(), config.clone()),
+            sui_client,
             config,
-            _signer_address: signer_address,
+            adapters: AdapterRegistry::default(),
+            gas_pool,
         })
     }
 
+    /// Resolve protocols through `adapters` instead of the built-in set, typically
+    /// the collector's so registry-discovered protocols can be executed
+    #[must_use]
+    pub fn with_adapters(mut self, adapters: AdapterRegistry) -> Self {
+        self.adapters = adapters;
+        self
+    }
+
+    /// Pool leasing the signer's gas coins, if the private key could be decoded
+    pub const fn gas_pool(&self) -> Option<&GasCoinPool> {
+        self.gas_pool.as_ref()
+    }
+
+    fn adapter(&self, plan: &ExecutionPlan) -> Result<Arc<dyn ProtocolAdapter>> {
+        self.adapters
+            .get(plan.protocol)
+            .ok_or_else(|| eyre::eyre!("No adapter registered for {:?}", plan.protocol))
+    }
+
     /// Execute a flash loan according to the execution plan
     pub async fn execute_flash_loan(&self, plan: &ExecutionPlan) -> Result<String> {
         info!(
-            "Executing flash loan: protocol={:?}, amount={}, cost={}",
-            plan.protocol, plan.amount, plan.total_cost
+            "Executing flash loan: protocol={:?}, asset={}, amount={}, cost={}",
+            plan.protocol, plan.asset, plan.amount, plan.total_cost
         );
 
-        // For now, simulate the transaction execution
-        // In production, this would:
-        // 1. Build real PTB with flash_loan call
-        // 2. Get gas coins and estimate gas
-        // 3. Sign transaction with private key
-        // 4. Submit to network and wait for confirmation
-
-        let tx_digest = self.simulate_transaction_execution(plan).await?;
+        // The PTB is dry-run against the node; submission is not wired up yet, so the
+        // digest returned is derived from the plan rather than signed and sent
+        let result = self.simulate_transaction_execution(plan).await;
+        METRICS.record_executor_outcome("submission", &result);
+        let tx_digest = result?;
 
         info!("Flash loan transaction submitted: {}", tx_digest);
         Ok(tx_digest)
     }
 
-    /// Simulate transaction execution for testing and development
+    /// Dry-run the plan's PTB to price its gas, then return a simulated digest
     async fn simulate_transaction_execution(&self, plan: &ExecutionPlan) -> Result<String> {
         debug!(
             "Simulating transaction execution for protocol {:?}",
             plan.protocol
         );
 
-        // Validate the execution plan
-        Self::validate_execution_plan(plan)?;
-
-        // Build transaction structure (for validation/testing)
-        let _ptb_structure = self.build_transaction_structure(plan).await?;
-
-        // Simulate gas estimation
-        let estimated_gas = self.estimate_gas_cost(plan).await?;
-        debug!("Estimated gas cost: {}", estimated_gas);
+        // The dry run pays from a leased coin, so one too small for the budget fails here
+        let lease = match &self.gas_pool {
+            Some(pool) => Some(pool.lease().await?),
+            None => None,
+        };
+        let gas_payment = lease.iter().map(|lease| lease.coin().object_ref).collect();
+        let quote = self.price_gas(plan, gas_payment).await;
+        // A dry run never spends its gas coin
+        if let Some(lease) = lease {
+            lease.release();
+        }
+        METRICS.record_executor_outcome("dry_run", &quote);
+        let quote = quote?;
+        METRICS
+            .gas_used
+            .with_label_values(&[protocol_label(plan.protocol).as_ref()])
+            .observe((quote.computation_cost + quote.storage_cost) as f64);
+        debug!(
+            "Gas price {} (reference {}), budget {}",
+            quote.gas_price, quote.reference_gas_price, quote.gas_budget
+        );
 
         // Generate simulated transaction digest
         let tx_content = format!(
-            "{}:{}:{}:{}",
-            plan.protocol as u64, plan.amount, plan.total_cost, plan.user_operation
+            "{:?}:{}:{}:{}",
+            plan.protocol, plan.amount, plan.total_cost, plan.user_operation
         );
 
         let hash = blake3::hash(tx_content.as_bytes());
@@ -83,6 +153,84 @@ impl FlashLoanExecutor {
         Ok(tx_digest)
     }
 
+    /// Dry-run the plan and price its gas under the configured policy
+    ///
+    /// The gas is also recorded in the adapter registry, so later routing weighs the
+    /// protocol by what its loans actually cost.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plan is invalid, the dry run fails or aborts, the reference gas
+    /// price is unavailable or the budget exceeds `gas_policy.max_gas_per_loan`
+    pub async fn quote_gas(&self, plan: &ExecutionPlan) -> Result<GasQuote> {
+        self.price_gas(plan, Vec::new()).await
+    }
+
+    /// [`Self::quote_gas`] with `gas_payment` paying for the dry run
+    async fn price_gas(
+        &self,
+        plan: &ExecutionPlan,
+        gas_payment: Vec<ObjectRef>,
+    ) -> Result<GasQuote> {
+        let costs = self.dry_run(plan, gas_payment).await?;
+        let quote = self.gas_policy.quote(&costs).await?;
+        self.adapters.record_gas(
+            plan.protocol,
+            quote.computation_cost.saturating_add(quote.storage_cost),
+        );
+        Ok(quote)
+    }
+
+    /// Validate the plan and dry-run its transaction on chain, returning the gas it used
+    async fn dry_run(
+        &self,
+        plan: &ExecutionPlan,
+        gas_payment: Vec<ObjectRef>,
+    ) -> Result<GasCostSummary> {
+        Self::validate_execution_plan(plan)?;
+        let tx_data = self.build_transaction(plan, gas_payment).await?;
+
+        let response = self
+            .sui_client
+            .read_api()
+            .dry_run_transaction_block(tx_data)
+            .await?;
+        let status = response.effects.status();
+        if !status.is_ok() {
+            eyre::bail!(
+                "Dry run of the {:?} loan failed: {:?}",
+                plan.protocol,
+                status
+            );
+        }
+        let costs = response.effects.gas_cost_summary().clone();
+        debug!("Dry run gas costs: {:?}", costs);
+        Ok(costs)
+    }
+
+    /// The router's `FlashLoanEvent` for a loan executed from `plan`
+    ///
+    /// Simulated transactions emit nothing on chain, so the event is the one the router
+    /// emits for the plan: its router id, fees and the repayment they add up to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the protocol has no adapter or router id
+    pub fn flash_loan_event(&self, plan: &ExecutionPlan) -> Result<FlashLoanEvent> {
+        let config = self.config.current();
+        let protocol = self.adapter(plan)?.router_id(&config).ok_or_else(|| {
+            eyre::eyre!("{:?} has no router adapter id configured", plan.protocol)
+        })?;
+        let service_fee = config.service_fee_bps.fee_on(plan.amount);
+        Ok(FlashLoanEvent {
+            protocol,
+            amount: plan.amount,
+            protocol_fee: plan.total_cost - plan.amount,
+            service_fee,
+            total_repayment: plan.total_cost + service_fee,
+        })
+    }
+
     /// Validate the execution plan before processing
     /// Validate execution plan parameters
     fn validate_execution_plan(plan: &ExecutionPlan) -> Result<()> {
@@ -104,46 +252,69 @@ impl FlashLoanExecutor {
         Ok(())
     }
 
-    /// Build transaction structure for validation
-    async fn build_transaction_structure(
+    /// Build the router's `main::flash_loan_coin` call for `plan`, signed by the configured key
+    ///
+    /// An empty `gas_payment` lets the dry run pay from a mock coin, as quotes do; execution
+    /// passes the coin it leased from the gas pool.
+    async fn build_transaction(
         &self,
         plan: &ExecutionPlan,
-    ) -> Result<TransactionStructure> {
+        gas_payment: Vec<ObjectRef>,
+    ) -> Result<TransactionData> {
         debug!(
-            "Building transaction structure for protocol {:?}",
-            plan.protocol
+            "Building transaction for {} from protocol {:?}",
+            plan.asset, plan.protocol
         );
 
-        let package_id = self.config.sui_flash_package_id.clone();
-        let config_object_id = self.config.sui_flash_config_object_id.clone();
-
-        // Prepare function call details
-        let module_name = "flash_router";
-        let function_name = "flash_loan";
-        let type_args = vec!["0x2::sui::SUI".to_string()]; // Assume SUI for now
-
-        // Prepare arguments
-        let args = vec![
-            format!("config:{}", config_object_id),
-            format!("protocol:{}", plan.protocol as u64),
-            format!("amount:{}", plan.amount),
-            format!(
-                "recipient:{}",
-                plan.callback_recipient.as_deref().unwrap_or("0x0")
-            ),
-            format!("payload:{}", plan.callback_payload.as_deref().unwrap_or("")),
-        ];
-
-        let tx_structure = TransactionStructure {
-            _package_id: package_id,
-            _module_name: module_name.to_string(),
-            _function_name: function_name.to_string(),
-            _type_args: type_args,
-            _args: args,
+        let config = self.config.current();
+        let adapter = self.adapter(plan)?;
+        let router_id = adapter.router_id(&config).ok_or_else(|| {
+            eyre::eyre!(
+                "{:?} cannot be executed until its router adapter id is configured",
+                plan.protocol
+            )
+        })?;
+        let coin_type = config.asset_types.get(plan.asset);
+
+        let recipient = match plan.callback_recipient.as_deref() {
+            Some(recipient) => SuiAddress::from_str(recipient)
+                .map_err(|e| eyre::eyre!("Invalid callback recipient '{}': {}", recipient, e))?,
+            None => SuiAddress::ZERO,
         };
+        let payload = plan
+            .callback_payload
+            .as_deref()
+            .unwrap_or_default()
+            .as_bytes()
+            .to_vec();
+        let router_config =
+            shared_object_arg(&self.sui_client, config.sui_flash_config_object_id, false).await?;
+
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let arguments = vec![
+            builder.obj(router_config).map_err(sdk_error)?,
+            builder.pure(router_id).map_err(sdk_error)?,
+            builder.pure(plan.amount).map_err(sdk_error)?,
+            builder.pure(recipient).map_err(sdk_error)?,
+            builder.pure(payload).map_err(sdk_error)?,
+        ];
+        builder.programmable_move_call(
+            config.sui_flash_package_id,
+            Identifier::new("main").map_err(sdk_error)?,
+            Identifier::new("flash_loan_coin").map_err(sdk_error)?,
+            vec![parse_sui_type_tag(coin_type).map_err(sdk_error)?],
+            arguments,
+        );
 
-        debug!("Transaction structure built: {:?}", tx_structure);
-        Ok(tx_structure)
+        let sender = signer_address(&config.private_key)?;
+        let gas_price = self.gas_policy.reference_gas_price().await?;
+        Ok(TransactionData::new_programmable(
+            sender,
+            gas_payment,
+            builder.finish(),
+            config.gas_policy.max_gas_per_loan,
+            gas_price,
+        ))
     }
 
     /// Verify that a flash loan execution was successful
@@ -172,6 +343,7 @@ impl FlashLoanExecutor {
         // Log detailed error information
         info!("Failed execution details:");
         info!("  Protocol: {:?}", plan.protocol);
+        info!("  Asset: {}", plan.asset);
         info!("  Amount: {}", plan.amount);
         info!("  Total Cost: {}", plan.total_cost);
         info!("  User Operation: {}", plan.user_operation);
@@ -188,71 +360,6 @@ impl FlashLoanExecutor {
 
         Ok(())
     }
-
-    /// Estimate gas cost for a flash loan execution
-    pub async fn estimate_gas_cost(&self, plan: &ExecutionPlan) -> Result<u64> {
-        debug!("Estimating gas cost for execution plan");
-
-        // Base costs for different operations
-        let base_transaction_cost = 1_000_000; // ~0.001 SUI
-        let flash_loan_base_cost = 2_000_000; // ~0.002 SUI
-        let protocol_overhead = match plan.protocol {
-            Protocol::Navi => 1_500_000,
-            Protocol::Bucket => 1_200_000,
-            Protocol::Scallop => 1_800_000,
-        };
-
-        // Additional cost for user callback
-        let callback_cost = if plan.callback_recipient.is_some() {
-            5_000_000 // ~0.005 SUI for user callback execution
-        } else {
-            0
-        };
-
-        // Scale with amount (larger amounts may require more gas for computation)
-        let amount_scaling = (plan.amount / 1_000_000_000).max(1); // Scale per SUI
-        let scaling_cost = amount_scaling * 100_000; // Small additional cost per SUI
-
-        let total_estimate = base_transaction_cost
-            + flash_loan_base_cost
-            + protocol_overhead
-            + callback_cost
-            + scaling_cost;
-
-        debug!("Gas cost breakdown:");
-        debug!("  Base: {}", base_transaction_cost);
-        debug!("  Flash loan: {}", flash_loan_base_cost);
-        debug!("  Protocol overhead: {}", protocol_overhead);
-        debug!("  Callback: {}", callback_cost);
-        debug!("  Scaling: {}", scaling_cost);
-        debug!("  Total estimate: {}", total_estimate);
-
-        Ok(total_estimate)
-    }
-
-    /// Get current network gas price
-    pub async fn get_gas_price(&self) -> Result<u64> {
-        match self.client.read_api().get_reference_gas_price().await {
-            Ok(price) => {
-                debug!("Current network gas price: {}", price);
-                Ok(price)
-            }
-            Err(e) => {
-                warn!("Failed to get network gas price, using default: {}", e);
-                Ok(1000) // Default gas price
-            }
-        }
-    }
-}
-
-/// Structure representing a transaction for validation and testing
-#[derive(Debug, Clone)]
-struct TransactionStructure {
-    _package_id: String,
-    _module_name: String,
-    _function_name: String,
-    _type_args: Vec<String>,
-    _args: Vec<String>,
 }
 
 // Artemis Executor implementation
@@ -0,0 +1,165 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eyre::Result;
+use serde::Serialize;
+use sui_sdk::SuiClient;
+use sui_types::gas::GasCostSummary;
+use tracing::{debug, info, warn};
+
+use crate::config::{GasPolicyConfig, SharedConfig};
+
+/// How soon to ask again when an epoch has run past its scheduled end
+const EPOCH_RECHECK_MS: u64 = 10_000;
+
+/// Gas price and budget for one loan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GasQuote {
+    pub reference_gas_price: u64,
+    /// Reference price with the priority fee applied
+    pub gas_price: u64,
+    /// Dry-run computation cost at `gas_price`, in MIST
+    pub computation_cost: u64,
+    pub storage_cost: u64,
+    /// Computation plus storage with the safety factor applied, in MIST
+    pub gas_budget: u64,
+}
+
+impl GasQuote {
+    /// Apply `policy` to the costs of a dry run priced at `reference_gas_price`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the budget exceeds `gas_policy.max_gas_per_loan`
+    pub fn new(
+        reference_gas_price: u64,
+        costs: &GasCostSummary,
+        policy: &GasPolicyConfig,
+    ) -> Result<Self> {
+        let gas_price = scale(reference_gas_price, policy.priority_fee_multiplier);
+        // Computation is charged per unit at the gas price, so bidding higher costs more
+        let computation_cost = scale(costs.computation_cost, policy.priority_fee_multiplier);
+        let gas_budget = scale(
+            computation_cost.saturating_add(costs.storage_cost),
+            policy.safety_factor,
+        );
+        if gas_budget > policy.max_gas_per_loan {
+            eyre::bail!(
+                "Gas budget of {} MIST exceeds gas_policy.max_gas_per_loan ({} MIST)",
+                gas_budget,
+                policy.max_gas_per_loan
+            );
+        }
+
+        Ok(Self {
+            reference_gas_price,
+            gas_price,
+            computation_cost,
+            storage_cost: costs.storage_cost,
+            gas_budget,
+        })
+    }
+}
+
+fn scale(value: u64, factor: f64) -> u64 {
+    (value as f64 * factor).ceil() as u64
+}
+
+/// Reference gas price of one epoch
+#[derive(Debug, Clone, Copy)]
+struct EpochGasPrice {
+    epoch: u64,
+    price: u64,
+    /// Unix time after which the price is read again
+    expires_at_ms: u64,
+}
+
+/// Prices loans from the network's reference gas price, read once per epoch
+#[derive(Clone)]
+pub struct GasPolicy {
+    client: SuiClient,
+    config: SharedConfig,
+    current: Arc<Mutex<Option<EpochGasPrice>>>,
+}
+
+impl GasPolicy {
+    pub fn new(client: SuiClient, config: SharedConfig) -> Self {
+        Self {
+            client,
+            config,
+            current: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Reference gas price of the current epoch
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system state cannot be read and no earlier price is known
+    pub async fn reference_gas_price(&self) -> Result<u64> {
+        let now = now_ms();
+        let cached = *self.current.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(cached) = cached.filter(|cached| now < cached.expires_at_ms) {
+            return Ok(cached.price);
+        }
+
+        let state = match self
+            .client
+            .governance_api()
+            .get_latest_sui_system_state()
+            .await
+        {
+            Ok(state) => state,
+            Err(e) => {
+                let Some(cached) = cached else {
+                    return Err(e.into());
+                };
+                warn!(
+                    "Failed to read system state, keeping epoch {} gas price {}: {}",
+                    cached.epoch, cached.price, e
+                );
+                return Ok(cached.price);
+            }
+        };
+
+        let epoch_end = state
+            .epoch_start_timestamp_ms
+            .saturating_add(state.epoch_duration_ms);
+        let fresh = EpochGasPrice {
+            epoch: state.epoch,
+            price: state.reference_gas_price,
+            expires_at_ms: epoch_end.max(now + EPOCH_RECHECK_MS),
+        };
+        if cached.is_none_or(|cached| cached.epoch != fresh.epoch) {
+            info!("Epoch {} reference gas price: {}", fresh.epoch, fresh.price);
+        }
+        *self.current.lock().unwrap_or_else(PoisonError::into_inner) = Some(fresh);
+        Ok(fresh.price)
+    }
+
+    /// Price a loan whose dry run cost `costs`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference gas price is unavailable or the budget exceeds the cap
+    pub async fn quote(&self, costs: &GasCostSummary) -> Result<GasQuote> {
+        let reference_gas_price = self.reference_gas_price().await?;
+        let quote = GasQuote::new(
+            reference_gas_price,
+            costs,
+            &self.config.current().gas_policy,
+        )?;
+        debug!("Gas quote: {:?}", quote);
+        Ok(quote)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+        })
+}
@@ -0,0 +1,392 @@
+use std::{
+    collections::HashSet,
+    iter,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use eyre::Result;
+use sui_json_rpc_types::{SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponseOptions};
+use sui_sdk::SuiClient;
+use sui_types::{
+    base_types::{ObjectID, ObjectRef, SuiAddress},
+    crypto::SuiKeyPair,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    quorum_driver_types::ExecuteTransactionRequestType,
+    transaction::{Transaction, TransactionData},
+};
+use tokio::{
+    sync::Notify,
+    time::{Instant, sleep, timeout_at},
+};
+use tracing::{debug, info, warn};
+
+use crate::config::{GasPoolConfig, SharedConfig};
+
+/// Gas budget for split and merge transactions
+const REBALANCE_GAS_BUDGET: u64 = 50_000_000;
+
+/// An owned SUI coin used to pay gas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasCoin {
+    pub object_ref: ObjectRef,
+    pub balance: u64,
+}
+
+impl GasCoin {
+    pub const fn id(&self) -> ObjectID {
+        self.object_ref.0
+    }
+}
+
+/// Coins owned by the signer, split into those free to lease and those in flight
+#[derive(Debug, Default)]
+pub struct GasCoins {
+    available: Vec<GasCoin>,
+    leased: HashSet<ObjectID>,
+}
+
+impl GasCoins {
+    pub fn available(&self) -> &[GasCoin] {
+        &self.available
+    }
+
+    pub fn leased(&self) -> usize {
+        self.leased.len()
+    }
+
+    /// Lease the largest available coin
+    pub fn take(&mut self) -> Option<GasCoin> {
+        let largest = self.available.iter().max_by_key(|coin| coin.balance)?.id();
+        self.claim(largest)
+    }
+
+    /// Lease a specific available coin
+    pub fn claim(&mut self, id: ObjectID) -> Option<GasCoin> {
+        let index = self.available.iter().position(|coin| coin.id() == id)?;
+        let coin = self.available.swap_remove(index);
+        self.leased.insert(id);
+        Some(coin)
+    }
+
+    /// End the lease on `id`, making `coin` available again
+    ///
+    /// Without `coin` the latest version is unknown, so the coin stays out of the pool until
+    /// the next [`GasCoins::sync`] reads it from chain.
+    pub fn restore(&mut self, id: ObjectID, coin: Option<GasCoin>) {
+        if !self.leased.remove(&id) {
+            return;
+        }
+        if let Some(coin) = coin {
+            self.available.push(coin);
+        }
+    }
+
+    /// Replace the available coins with `owned`, as read from chain
+    ///
+    /// Leased coins are left alone, and a coin already returned at a newer version than the
+    /// read saw keeps the newer one.
+    pub fn sync(&mut self, owned: Vec<GasCoin>) {
+        let available = owned
+            .into_iter()
+            .filter(|coin| !self.leased.contains(&coin.id()))
+            .map(|coin| {
+                self.available
+                    .iter()
+                    .find(|known| known.id() == coin.id() && known.object_ref.1 > coin.object_ref.1)
+                    .copied()
+                    .unwrap_or(coin)
+            })
+            .collect();
+        self.available = available;
+    }
+}
+
+/// Merge and split that bring the pool to its target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebalancePlan {
+    /// Pays gas, absorbs `merge` and funds `split`
+    pub primary: GasCoin,
+    pub merge: Vec<GasCoin>,
+    /// Balances of the coins split off `primary`
+    pub split: Vec<u64>,
+}
+
+impl RebalancePlan {
+    /// Coins consumed or modified by the rebalance transaction
+    pub fn coins(&self) -> impl Iterator<Item = &GasCoin> {
+        iter::once(&self.primary).chain(&self.merge)
+    }
+}
+
+/// Plan how to reach `settings.target_size` coins given the `available` ones and `leased` count
+///
+/// Coins below `min_coin_balance` are merged into the largest coin, which then funds as many
+/// new `coin_balance` coins as the pool is short of while keeping one `coin_balance` itself.
+/// Returns `None` when there is nothing to do.
+pub fn plan_rebalance(
+    available: &[GasCoin],
+    leased: usize,
+    settings: &GasPoolConfig,
+) -> Option<RebalancePlan> {
+    let primary = *available.iter().max_by_key(|coin| coin.balance)?;
+    let merge: Vec<GasCoin> = available
+        .iter()
+        .filter(|coin| coin.id() != primary.id() && coin.balance < settings.min_coin_balance)
+        .copied()
+        .collect();
+
+    let pooled = available.len() - merge.len() + leased;
+    let missing = settings.target_size.saturating_sub(pooled);
+    let funds = merge.iter().map(|coin| coin.balance).sum::<u64>() + primary.balance;
+    let affordable = funds
+        .saturating_sub(settings.coin_balance + REBALANCE_GAS_BUDGET)
+        .checked_div(settings.coin_balance)
+        .unwrap_or(0);
+    let split =
+        vec![settings.coin_balance; missing.min(usize::try_from(affordable).unwrap_or(usize::MAX))];
+
+    if merge.is_empty() && split.is_empty() {
+        return None;
+    }
+    Some(RebalancePlan {
+        primary,
+        merge,
+        split,
+    })
+}
+
+struct PoolState {
+    coins: Mutex<GasCoins>,
+    /// Signalled whenever coins become available
+    returned: Notify,
+}
+
+impl PoolState {
+    fn coins(&self) -> MutexGuard<'_, GasCoins> {
+        self.coins.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn restore(&self, id: ObjectID, coin: Option<GasCoin>) {
+        self.coins().restore(id, coin);
+        self.returned.notify_waiters();
+    }
+}
+
+/// A gas coin reserved for one in-flight transaction
+///
+/// Return it with [`GasLease::release`] if the transaction was never submitted. A lease
+/// dropped otherwise keeps its coin out of the pool until the next refresh, since its
+/// version may have changed.
+pub struct GasLease {
+    coin: GasCoin,
+    state: Arc<PoolState>,
+    returned: Option<GasCoin>,
+}
+
+impl GasLease {
+    pub const fn coin(&self) -> GasCoin {
+        self.coin
+    }
+
+    /// Return the coin unchanged
+    pub fn release(mut self) {
+        self.returned = Some(self.coin);
+    }
+}
+
+impl Drop for GasLease {
+    fn drop(&mut self) {
+        self.state.restore(self.coin.id(), self.returned.take());
+    }
+}
+
+/// Pool of the signer's SUI coins, leased one per in-flight transaction so parallel
+/// executions never race for the same gas object
+#[derive(Clone)]
+pub struct GasCoinPool {
+    client: SuiClient,
+    config: SharedConfig,
+    keypair: Arc<SuiKeyPair>,
+    owner: SuiAddress,
+    state: Arc<PoolState>,
+}
+
+impl GasCoinPool {
+    /// Create an empty pool for the coins of `keypair`; call [`GasCoinPool::maintain`] to fill it
+    pub fn new(client: SuiClient, config: SharedConfig, keypair: SuiKeyPair) -> Self {
+        let owner = SuiAddress::from(&keypair.public());
+        Self {
+            client,
+            config,
+            keypair: Arc::new(keypair),
+            owner,
+            state: Arc::new(PoolState {
+                coins: Mutex::new(GasCoins::default()),
+                returned: Notify::new(),
+            }),
+        }
+    }
+
+    pub const fn owner(&self) -> SuiAddress {
+        self.owner
+    }
+
+    /// Lease the largest free coin, waiting up to `gas_pool.lease_timeout_ms` for one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no coin is returned in time
+    pub async fn lease(&self) -> Result<GasLease> {
+        let timeout = self.config.current().gas_pool.lease_timeout_ms;
+        let deadline = Instant::now() + Duration::from_millis(timeout);
+        loop {
+            // Register before checking so a coin returned in between still wakes us
+            let returned = self.state.returned.notified();
+            tokio::pin!(returned);
+            returned.as_mut().enable();
+
+            if let Some(coin) = self.state.coins().take() {
+                debug!("Leased gas coin {} ({} MIST)", coin.id(), coin.balance);
+                return Ok(GasLease {
+                    coin,
+                    state: self.state.clone(),
+                    returned: None,
+                });
+            }
+            if timeout_at(deadline, returned).await.is_err() {
+                eyre::bail!(
+                    "No gas coin of {} became free within {}ms",
+                    self.owner,
+                    timeout
+                );
+            }
+        }
+    }
+
+    /// Re-read the owner's SUI coins from chain
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC call fails
+    pub async fn refresh(&self) -> Result<()> {
+        let mut owned = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self
+                .client
+                .coin_read_api()
+                .get_coins(self.owner, None, cursor, None)
+                .await?;
+            owned.extend(page.data.iter().map(|coin| GasCoin {
+                object_ref: coin.object_ref(),
+                balance: coin.balance,
+            }));
+            cursor = page.next_cursor;
+            if !page.has_next_page || cursor.is_none() {
+                break;
+            }
+        }
+
+        let mut coins = self.state.coins();
+        coins.sync(owned);
+        debug!(
+            "Gas pool refreshed: {} available, {} leased",
+            coins.available().len(),
+            coins.leased()
+        );
+        drop(coins);
+        self.state.returned.notify_waiters();
+        Ok(())
+    }
+
+    /// Refresh the pool, then split or merge coins towards the configured size and balance
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the coins cannot be read or the rebalance transaction fails
+    pub async fn rebalance(&self) -> Result<()> {
+        self.refresh().await?;
+        let settings = self.config.current().gas_pool.clone();
+        let plan = {
+            let mut coins = self.state.coins();
+            let Some(plan) = plan_rebalance(coins.available(), coins.leased(), &settings) else {
+                return Ok(());
+            };
+            for coin in plan.coins() {
+                coins.claim(coin.id());
+            }
+            plan
+        };
+
+        let result = self.submit_rebalance(&plan).await;
+        // Every coin in the plan was merged away or changed version; the refresh reads them back
+        for coin in plan.coins() {
+            self.state.restore(coin.id(), None);
+        }
+        result?;
+        self.refresh().await
+    }
+
+    async fn submit_rebalance(&self, plan: &RebalancePlan) -> Result<()> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        if plan.split.is_empty() {
+            // Paying with several coins merges them into the first; send it back to ourselves
+            builder.pay_all_sui(self.owner);
+        } else {
+            builder
+                .pay_sui(vec![self.owner; plan.split.len()], plan.split.clone())
+                .map_err(|e| eyre::eyre!("{}", e))?;
+        }
+
+        let gas_price = self.client.read_api().get_reference_gas_price().await?;
+        let tx_data = TransactionData::new_programmable(
+            self.owner,
+            plan.coins().map(|coin| coin.object_ref).collect(),
+            builder.finish(),
+            REBALANCE_GAS_BUDGET,
+            gas_price,
+        );
+        let transaction = Transaction::from_data_and_signer(tx_data, vec![self.keypair.as_ref()]);
+        let response = self
+            .client
+            .quorum_driver_api()
+            .execute_transaction_block(
+                transaction,
+                SuiTransactionBlockResponseOptions::new().with_effects(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?;
+
+        let digest = response.digest.to_string();
+        let effects = response
+            .effects
+            .ok_or_else(|| eyre::eyre!("Transaction {} returned no effects", digest))?;
+        if !effects.status().is_ok() {
+            eyre::bail!("Transaction {} failed: {:?}", digest, effects.status());
+        }
+
+        info!(
+            "Gas pool rebalanced in {}: merged {} coins, split {}",
+            digest,
+            plan.merge.len(),
+            plan.split.len()
+        );
+        Ok(())
+    }
+
+    /// Rebalance every `gas_pool.refresh_interval_ms` until the task is aborted
+    pub async fn maintain(&self) {
+        info!("Maintaining gas coin pool for {}", self.owner);
+        loop {
+            if let Err(e) = self.rebalance().await {
+                warn!("Gas pool maintenance failed: {}", e);
+            }
+            // Re-read the interval each round so a config reload takes effect immediately
+            sleep(Duration::from_millis(
+                self.config.current().gas_pool.refresh_interval_ms,
+            ))
+            .await;
+        }
+    }
+}
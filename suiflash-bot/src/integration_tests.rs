@@ -1,28 +1,73 @@
 /// Integration tests for SuiFlash Bot
 /// These tests verify the full system functionality including protocol routing,
 /// transaction execution simulation, and API endpoints.
+use std::collections::BTreeMap;
+
+use sui_types::base_types::ObjectID;
+
 use crate::{
+    adapters::AdapterRegistry,
     collectors::ProtocolDataCollector,
-    config::{Config, FlashLoanRequest, Protocol, RouteMode},
+    config::{
+        Asset, AssetTypes, AuthConfig, Bps, Config, DeepBookConfig, ExecutionStoreConfig,
+        FlashLoanRequest, GasPolicyConfig, GasPoolConfig, MarketQuote, Network, NetworkProfiles,
+        Protocol, ProtocolApis, RateLimitConfig, ReadinessConfig, RouteMode, ShutdownConfig,
+        StrategyKind, SuilendConfig, WebhookConfig,
+    },
     executors::FlashLoanExecutor,
     strategies::FlashLoanStrategy,
 };
 
 /// Helper function to create test configuration
+///
+/// Opts into static fallbacks so the pipeline still has data when protocol APIs are unreachable.
 fn create_integration_test_config() -> Config {
+    let mut protocol_apis = ProtocolApis::default();
+    protocol_apis.navi.static_fallback = Some(MarketQuote {
+        fee_bps: 8,
+        available_liquidity: 10_000_000_000,
+    });
+    protocol_apis.bucket.static_fallback = Some(MarketQuote {
+        fee_bps: 5,
+        available_liquidity: 5_000_000_000,
+    });
+    protocol_apis.scallop.static_fallback = Some(MarketQuote {
+        fee_bps: 9,
+        available_liquidity: 8_000_000_000,
+    });
+
     Config {
+        network: Network::Testnet,
         sui_rpc_url: "https://fullnode.testnet.sui.io:443".to_string(),
-        private_key: "test_private_key".to_string(),
-        sui_flash_package_id: "0x1234567890abcdef".to_string(),
-        sui_flash_config_object_id: "0xabcdef1234567890".to_string(),
+        sui_rpc_fallback_urls: Vec::new(),
+        private_key: "test_private_key".into(),
+        sui_flash_package_id: ObjectID::from_hex_literal("0x1234567890abcdef").unwrap(),
+        sui_flash_config_object_id: ObjectID::from_hex_literal("0xabcdef1234567890").unwrap(),
+        admin_cap_id: None,
+        registry_object_id: None,
         server_port: 3000,
         refresh_interval_ms: 10000,
-        strategy: "cheapest".to_string(),
-        contract_package_id: "0x1".to_string(),
-        navi_package_id: "0x2".to_string(),
-        bucket_package_id: "0x3".to_string(),
-        scallop_package_id: "0x4".to_string(),
-        service_fee_bps: 40,
+        strategy: StrategyKind::Cheapest,
+        contract_package_id: ObjectID::from_hex_literal("0x1").unwrap(),
+        navi_package_id: ObjectID::from_hex_literal("0x2").unwrap(),
+        bucket_package_id: ObjectID::from_hex_literal("0x3").unwrap(),
+        scallop_package_id: ObjectID::from_hex_literal("0x4").unwrap(),
+        service_fee_bps: Bps::try_from(40).unwrap(),
+        protocol_apis,
+        asset_types: AssetTypes::default(),
+        adapters: BTreeMap::new(),
+        deepbook: DeepBookConfig::default(),
+        suilend: SuilendConfig::default(),
+        gas_pool: GasPoolConfig::default(),
+        gas_policy: GasPolicyConfig::default(),
+        api_keys: Vec::new(),
+        auth: AuthConfig { disabled: true },
+        rate_limits: RateLimitConfig::default(),
+        executions: ExecutionStoreConfig::default(),
+        webhooks: WebhookConfig::default(),
+        shutdown: ShutdownConfig::default(),
+        readiness: ReadinessConfig::default(),
+        networks: NetworkProfiles::default(),
     }
 }
 
@@ -45,7 +90,7 @@ async fn test_full_protocol_data_pipeline() {
 
     // Check each protocol has valid data
     for protocol in [Protocol::Navi, Protocol::Bucket, Protocol::Scallop] {
-        if let Some(protocol_data) = data.get(&protocol) {
+        if let Some(protocol_data) = data.get(&(protocol, Asset::SUI)) {
             assert!(protocol_data.fee_bps > 0, "Fee should be positive");
             assert!(
                 protocol_data.available_liquidity > 0,
@@ -112,44 +157,37 @@ async fn test_strategy_selection_logic() {
 async fn test_executor_gas_estimation() {
     let config = create_integration_test_config();
 
-    // Test executor creation and gas estimation
+    // Until a dry run measures them, protocols are ranked by their adapter's overhead
+    let adapters = AdapterRegistry::default();
+    let bucket_gas = adapters.gas_estimate(Protocol::Bucket, &config);
+    assert!(bucket_gas > 0, "Gas estimate should be positive");
+    assert!(
+        bucket_gas < 50_000_000,
+        "Gas estimate should be reasonable (< 0.05 SUI)"
+    );
+    assert!(adapters.gas_estimate(Protocol::Scallop, &config) > bucket_gas);
+    adapters.record_gas(Protocol::Scallop, 1_000);
+    assert_eq!(adapters.gas_estimate(Protocol::Scallop, &config), 1_000);
+
+    // Test executor creation and the dry run's input checks
     match FlashLoanExecutor::new(config).await {
         Ok(executor) => {
             let test_plan = crate::strategies::ExecutionPlan {
                 protocol: Protocol::Bucket,
+                asset: Asset::SUI,
                 amount: 500_000_000,     // 0.5 SUI
                 total_cost: 500_250_000, // Including 5 bps fee
                 user_operation: "gas_test".to_string(),
-                callback_recipient: None,
+                callback_recipient: Some("not-an-address".to_string()),
                 callback_payload: None,
             };
 
-            let gas_estimate = executor
-                .estimate_gas_cost(&test_plan)
-                .await
-                .expect("Should estimate gas cost");
-
-            assert!(gas_estimate > 0, "Gas estimate should be positive");
-            assert!(
-                gas_estimate < 50_000_000,
-                "Gas estimate should be reasonable (< 0.05 SUI)"
-            );
-
-            // Test with callback recipient (should cost more)
-            let callback_plan = crate::strategies::ExecutionPlan {
-                callback_recipient: Some("0x1234567890abcdef".to_string()),
-                ..test_plan
-            };
-
-            let callback_gas = executor
-                .estimate_gas_cost(&callback_plan)
+            // Refused before the transaction is sent for a dry run
+            let error = executor
+                .quote_gas(&test_plan)
                 .await
-                .expect("Should estimate callback gas cost");
-
-            assert!(
-                callback_gas > gas_estimate,
-                "Callback should increase gas cost"
-            );
+                .expect_err("Should reject the callback recipient");
+            assert!(error.to_string().contains("Invalid callback recipient"));
         }
         Err(_) => {
             // Expected in test environment without network access
@@ -309,7 +347,7 @@ async fn test_transaction_simulation_end_to_end() {
 async fn test_routing_strategies() {
     // Test cheapest strategy
     let mut config = create_integration_test_config();
-    config.strategy = "cheapest".to_string();
+    config.strategy = StrategyKind::Cheapest;
 
     let collector = ProtocolDataCollector::new(config.clone()).await;
     let strategy = FlashLoanStrategy::new(config, collector.clone());
@@ -336,7 +374,7 @@ async fn test_routing_strategies() {
 
     // Test highest liquidity strategy
     let mut config2 = create_integration_test_config();
-    config2.strategy = "highest_liquidity".to_string();
+    config2.strategy = StrategyKind::HighestLiquidity;
 
     let collector2 = ProtocolDataCollector::new(config2.clone()).await;
     let strategy2 = FlashLoanStrategy::new(config2, collector2.clone());
@@ -391,6 +429,7 @@ async fn test_error_handling() {
     if let Ok(executor) = FlashLoanExecutor::new(config).await {
         let invalid_plan = crate::strategies::ExecutionPlan {
             protocol: Protocol::Navi,
+            asset: Asset::SUI,
             amount: 0,
             total_cost: 0,
             user_operation: "invalid_test".to_string(),
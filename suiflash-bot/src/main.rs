@@ -1,7 +1,25 @@
+mod adapters;
+mod admin;
+mod auth;
+mod cli;
 mod collectors;
 mod config;
+mod executions;
 mod executors;
+mod gas_policy;
+mod gas_pool;
+mod metrics;
+mod openapi;
+mod rate_limit;
+mod readiness;
+mod reload;
+mod rpc;
+mod shutdown;
+mod signer;
 mod strategies;
+mod stream;
+mod v1;
+mod webhooks;
 
 #[cfg(test)]
 mod tests;
@@ -12,36 +30,92 @@ mod integration_tests;
 #[cfg(test)]
 mod api_tests;
 
+use std::net::SocketAddr;
+
+use auth::{ApiClient, DailyLoanCounter};
 use axum::{
-    Router,
-    extract::State,
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    Extension, Router,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware,
+    response::{IntoResponse, Json, Response},
 };
+use clap::Parser;
+use cli::{Cli, Command};
 use collectors::ProtocolDataCollector;
-use config::{Config, FlashLoanRequest, FlashLoanResponse, ProtocolsResponse, StatusResponse};
+use config::{
+    BatchItemResult, Config, FlashLoanBatchRequest, FlashLoanBatchResponse, FlashLoanRequest,
+    FlashLoanResponse, Network, ProtocolsResponse, SharedConfig, StatusResponse,
+};
+use executions::{Begin, Execution, ExecutionStatus, ExecutionStore, IdempotencyKey, unix_now};
 use executors::FlashLoanExecutor;
 use eyre::Result;
-use strategies::FlashLoanStrategy;
-use tokio::net::TcpListener;
-use tracing::{error, info};
+use metrics::{METRICS, protocol_label, route_mode_label};
+use openapi::ApiDoc;
+use rate_limit::{ClientIp, Limited, RateLimiter};
+use readiness::{Readiness, ReadinessReport};
+use reload::{ConfigReloader, ReloadResponse};
+use shutdown::Shutdown;
+use strategies::{ExecutionPlan, FlashLoanStrategy};
+use tokio::{net::TcpListener, time::Duration};
+use tracing::{error, info, warn};
+use utoipa::OpenApi;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use webhooks::{WebhookDispatcher, WebhookEvent, WebhookPayload};
+
+/// How often config.toml is checked for changes
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
 
 #[derive(Clone)]
 pub struct AppState {
-    pub config: Config,
+    pub config: SharedConfig,
+    pub reloader: ConfigReloader,
     pub strategy: FlashLoanStrategy,
     pub executor: FlashLoanExecutor,
+    pub loan_counts: DailyLoanCounter,
+    pub limiter: RateLimiter,
+    pub executions: ExecutionStore,
+    pub webhooks: WebhookDispatcher,
+    pub shutdown: Shutdown,
+    pub readiness: Readiness,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
+    let cli = Cli::parse();
+
+    // Initialize tracing; logs go to stderr so subcommand output stays scriptable
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    cli::run(cli.command.unwrap_or(Command::Serve), cli.network).await
+}
 
-    // Load configuration from config.toml or fallback to environment variables
-    let config = Config::load().map_err(|e| eyre::eyre!("Failed to load configuration: {}", e))?;
+/// Run the HTTP server until it stops
+///
+/// # Errors
+///
+/// Returns an error if configuration is invalid, the RPC serves another chain or the
+/// server cannot bind its port
+pub async fn serve(network: Option<Network>) -> Result<()> {
+    let config = Config::load_for_network(network)
+        .map_err(|e| eyre::eyre!("Failed to load configuration: {}", e))?;
     info!("Starting SuiFlash bot with config: {:?}", config);
+
+    // Refuse to start on any configuration error; report warnings and carry on
+    let report = config.validate();
+    for warning in &report.warnings {
+        warn!("Configuration warning: {}", warning);
+    }
+    report.into_result()?;
+    if config.auth.disabled {
+        warn!("auth.disabled is set; /flashloan, webhook and /admin endpoints are unauthenticated");
+    }
+
+    // Make sure the RPC actually serves the selected network before touching it
+    let sui_client = rpc::connect_sui_client(&config).await?;
+    rpc::verify_chain_identifier(&sui_client, &config).await?;
     // Touch individual fields to avoid dead_code warnings until they are fully wired.
     let _touch = (
         &config.sui_rpc_url,
@@ -53,10 +127,18 @@ async fn main() -> Result<()> {
         &config.scallop_package_id,
     );
 
-    // Initialize components
-    let collector = ProtocolDataCollector::new(config.clone()).await;
-    let strategy = FlashLoanStrategy::new(config.clone(), collector.clone());
-    let executor = FlashLoanExecutor::new(config.clone()).await?;
+    // Initialize components; they share one config handle so reloads reach all of them
+    let shared_config = SharedConfig::new(config.clone());
+    let collector = ProtocolDataCollector::new(shared_config.clone()).await;
+    let strategy = FlashLoanStrategy::new(shared_config.clone(), collector.clone());
+    let executor = FlashLoanExecutor::new(shared_config.clone())
+        .await?
+        .with_adapters(collector.adapters().clone());
+    let reloader = ConfigReloader::new(shared_config.clone(), network);
+    let readiness = Readiness::new(sui_client, shared_config.clone());
+    let executions = ExecutionStore::open(&config.executions).await?;
+    let webhooks = WebhookDispatcher::new(&executions, shared_config.clone()).await?;
+    let loan_counts = DailyLoanCounter::new(&executions).await?;
 
     // Start background data collection
     let collector_handle = {
@@ -66,19 +148,62 @@ async fn main() -> Result<()> {
         })
     };
 
+    // Keep the signer's gas coins split for concurrent executions
+    let gas_pool_handle = executor.gas_pool().cloned().map(|pool| {
+        tokio::spawn(async move {
+            pool.maintain().await;
+        })
+    });
+
+    // Deliver queued webhook notifications, including retries left from a previous run
+    let webhook_handle = {
+        let webhooks = webhooks.clone();
+        tokio::spawn(async move {
+            webhooks.run().await;
+        })
+    };
+
+    // Delete finished executions past their retention; executions is restart-only
+    let retention_handle = (config.executions.retention_days > 0).then(|| {
+        let executions = executions.clone();
+        let retention_days = config.executions.retention_days;
+        tokio::spawn(async move {
+            executions.run_retention(retention_days).await;
+        })
+    });
+
+    // Reload config.toml whenever it changes on disk
+    let watcher_handle = {
+        let reloader = reloader.clone();
+        tokio::spawn(async move {
+            reloader.watch(CONFIG_WATCH_INTERVAL).await;
+        })
+    };
+
+    let limiter = RateLimiter::new(shared_config.clone());
+    let shutdown = Shutdown::default();
+    let (api, spec) = api_routes(&shared_config, &limiter, &shutdown).split_for_parts();
+    let legacy = legacy_routes(&shared_config, &limiter, &shutdown);
+
     // Create app state
     let app_state = AppState {
-        config: config.clone(),
+        config: shared_config.clone(),
+        reloader,
         strategy,
         executor,
+        loan_counts,
+        limiter: limiter.clone(),
+        executions: executions.clone(),
+        webhooks: webhooks.clone(),
+        shutdown: shutdown.clone(),
+        readiness,
     };
 
     // Build the router
     let app = Router::new()
-        .route("/flashloan", post(handle_flash_loan))
-        .route("/protocols", get(handle_protocols))
-        .route("/status", get(handle_status))
-        .route("/health", get(handle_health))
+        .merge(api)
+        .merge(legacy)
+        .merge(openapi::docs_routes(spec))
         .with_state(app_state);
 
     // Start the server
@@ -86,74 +211,750 @@ async fn main() -> Result<()> {
     info!("Starting server on {}", addr);
 
     let listener = TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    let mut server = {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(async move { shutdown.requested().await })
+            .await
+        })
+    };
+    tokio::select! {
+        result = &mut server => return Ok(result??),
+        () = shutdown::signal() => {}
+    }
+
+    // Refuse new loans and close the protocol streams, then let running loans finish
+    let drain_timeout = Duration::from_secs(shared_config.current().shutdown.drain_timeout_secs);
+    info!(
+        "Shutting down; waiting up to {}s for {} loan requests in flight",
+        drain_timeout.as_secs(),
+        shutdown.loans_in_flight()
+    );
+    shutdown.begin();
+    match tokio::time::timeout(drain_timeout, &mut server).await {
+        Ok(result) => result??,
+        Err(_) => {
+            warn!(
+                "{} loan requests still in flight after {}s; stopping them",
+                shutdown.loans_in_flight(),
+                drain_timeout.as_secs()
+            );
+            server.abort();
+        }
+    }
 
-    // Clean up background tasks
-    collector_handle.abort();
+    // Stop background work before closing the store it writes to
+    let mut background = vec![collector_handle, watcher_handle, webhook_handle];
+    background.extend(gas_pool_handle);
+    background.extend(retention_handle);
+    for handle in background {
+        handle.abort();
+        // Aborted tasks end with a cancellation error; nothing else is left to report
+        let _ = handle.await;
+    }
+    report_pending_jobs(&executions, &webhooks).await;
+    executions.close().await;
+    info!("Shutdown complete");
 
     Ok(())
 }
 
+/// Log loans and webhook deliveries left unfinished at shutdown
+async fn report_pending_jobs(executions: &ExecutionStore, webhooks: &WebhookDispatcher) {
+    match executions.unfinished().await {
+        Ok(unfinished) => {
+            for execution in &unfinished {
+                // Submitted loans may have landed on chain; earlier ones never left the bot
+                warn!(
+                    "Execution {} left {} at shutdown",
+                    execution.id,
+                    execution.status.as_str()
+                );
+            }
+            if !unfinished.is_empty() {
+                warn!(
+                    "{} executions left unfinished; check submitted ones on chain before retrying",
+                    unfinished.len()
+                );
+            }
+        }
+        Err(e) => error!("Failed to list unfinished executions: {}", e),
+    }
+    match webhooks.pending_deliveries().await {
+        Ok(0) => {}
+        Ok(pending) => info!(
+            "{} webhook deliveries pending; they resume on the next start",
+            pending
+        ),
+        Err(e) => error!("Failed to count pending webhook deliveries: {}", e),
+    }
+}
+
+/// Every documented route: operations and admin at the root, the API under `/v1`
+///
+/// The OpenAPI document is collected from these routes, so it lists exactly what is served.
+pub fn api_routes(
+    config: &SharedConfig,
+    limiter: &RateLimiter,
+    shutdown: &Shutdown,
+) -> OpenApiRouter<AppState> {
+    // Admin routes need a key with the admin scope unless auth.disabled is set
+    let admin_routes = OpenApiRouter::new()
+        .routes(routes!(handle_reload))
+        .route_layer(middleware::from_fn_with_state(
+            config.clone(),
+            auth::require_admin_scope,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            limiter.clone(),
+            rate_limit::limit_ip,
+        ));
+
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .routes(routes!(handle_health))
+        .routes(routes!(handle_ready))
+        .routes(routes!(handle_metrics))
+        .merge(admin_routes)
+        .nest("/v1", v1::routes(config, limiter, shutdown))
+}
+
+/// The API as served before `/v1`, kept for existing clients
+///
+/// Answers keep their original shapes, and the routes are left out of the OpenAPI document.
+pub fn legacy_routes(
+    config: &SharedConfig,
+    limiter: &RateLimiter,
+    shutdown: &Shutdown,
+) -> Router<AppState> {
+    let loan_routes = with_loan_limits(
+        OpenApiRouter::new()
+            .routes(routes!(handle_flash_loan))
+            .routes(routes!(handle_flash_loan_batch)),
+        config,
+        limiter,
+        shutdown,
+    );
+    let lookup_routes = with_loans_auth(
+        OpenApiRouter::new().routes(routes!(handle_execution)),
+        config,
+        limiter,
+    );
+    let (router, _) = OpenApiRouter::new()
+        .routes(routes!(handle_protocols))
+        .routes(routes!(handle_status))
+        .merge(loan_routes)
+        .merge(lookup_routes)
+        .merge(event_routes(config, limiter))
+        .split_for_parts();
+    router
+}
+
+/// Protocol streams and webhooks
+///
+/// Their payloads are dedicated wire types already, so `/v1` and the unversioned API serve
+/// the same handlers.
+fn event_routes(config: &SharedConfig, limiter: &RateLimiter) -> OpenApiRouter<AppState> {
+    let webhook_routes = with_loans_auth(
+        OpenApiRouter::new()
+            .routes(routes!(
+                webhooks::handle_create_webhook,
+                webhooks::handle_list_webhooks
+            ))
+            .routes(routes!(webhooks::handle_delete_webhook))
+            .routes(routes!(webhooks::handle_webhook_deliveries)),
+        config,
+        limiter,
+    );
+    OpenApiRouter::new()
+        .routes(routes!(stream::handle_protocol_sse))
+        .routes(routes!(stream::handle_protocol_ws))
+        .merge(webhook_routes)
+}
+
+/// Require a key with the loans scope unless auth.disabled is set, and apply the rate limits
+///
+/// Layers run bottom-up: shutdown check, IP limit, then authentication, then the client's
+/// limits.
+fn with_loan_limits(
+    routes: OpenApiRouter<AppState>,
+    config: &SharedConfig,
+    limiter: &RateLimiter,
+    shutdown: &Shutdown,
+) -> OpenApiRouter<AppState> {
+    routes
+        .route_layer(middleware::from_fn_with_state(
+            limiter.clone(),
+            rate_limit::limit_client,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            config.clone(),
+            auth::require_loans_scope,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            limiter.clone(),
+            rate_limit::limit_ip,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            shutdown.clone(),
+            shutdown::track_loans,
+        ))
+}
+
+/// Authenticate like [`with_loan_limits`] without holding an execution slot
+fn with_loans_auth(
+    routes: OpenApiRouter<AppState>,
+    config: &SharedConfig,
+    limiter: &RateLimiter,
+) -> OpenApiRouter<AppState> {
+    routes
+        .route_layer(middleware::from_fn_with_state(
+            config.clone(),
+            auth::require_loans_scope,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            limiter.clone(),
+            rate_limit::limit_ip,
+        ))
+}
+
 /// Handle flash loan requests
 ///
+/// A request with an `Idempotency-Key` already used for the same body is answered from the
+/// earlier execution: its response once confirmed, its status once failed, and
+/// `202 Accepted` with the execution while it is still running.
+///
 /// # Errors
 ///
-/// Returns `StatusCode::INTERNAL_SERVER_ERROR` if:
-/// - Data collection fails
-/// - Execution plan generation fails  
-/// - Flash loan execution fails
-/// - Service fee calculation overflows
+/// Returns `StatusCode::BAD_REQUEST` for a malformed `Idempotency-Key` or a request no
+/// protocol can serve, `StatusCode::FORBIDDEN` if the request breaks the client's policy,
+/// `StatusCode::CONFLICT` if the key was used with a different body,
+/// `StatusCode::TOO_MANY_REQUESTS` once the daily loan limit is used up, and
+/// `StatusCode::INTERNAL_SERVER_ERROR` if the execution cannot be recorded or fails
+#[utoipa::path(
+    post,
+    path = "/flashloan",
+    description = "Picks a protocol by `route_mode`, executes the loan and returns the fees charged",
+    tag = "loans",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Retries with the same key and body return the original result instead of borrowing again")
+    ),
+    request_body = FlashLoanRequest,
+    responses(
+        (status = 200, description = "Loan executed", body = FlashLoanResponse),
+        (status = 202, description = "An earlier request with this Idempotency-Key is still running", body = Execution),
+        (status = 400, description = "Malformed Idempotency-Key or no protocol can serve the request"),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "The API key may not make this request"),
+        (status = 409, description = "The Idempotency-Key was used with a different request"),
+        (status = 429, description = "Rate or daily loan limit reached"),
+        (status = 500, description = "Execution failed")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
 pub async fn handle_flash_loan(
     State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    headers: HeaderMap,
     Json(request): Json<FlashLoanRequest>,
-) -> Result<Json<FlashLoanResponse>, StatusCode> {
+) -> Response {
+    let client = client.map(|Extension(client)| client);
+    match run_flash_loan(&state, client.as_ref(), &headers, &request).await {
+        LoanReply::Executed { response, .. } => Json(response).into_response(),
+        LoanReply::Replayed(execution) => replay(*execution),
+        LoanReply::Refused(failure) => failure.into_response(),
+    }
+}
+
+/// What became of a loan request, before it is shaped for an API version
+enum LoanReply {
+    /// Executed as execution `id`
+    Executed {
+        id: String,
+        response: FlashLoanResponse,
+    },
+    /// Answered from the execution an earlier request with the same `Idempotency-Key` started
+    Replayed(Box<Execution>),
+    Refused(LoanFailure),
+}
+
+/// Record, plan and execute one loan, honouring its `Idempotency-Key`
+async fn run_flash_loan(
+    state: &AppState,
+    client: Option<&ApiClient>,
+    headers: &HeaderMap,
+    request: &FlashLoanRequest,
+) -> LoanReply {
     info!("Received flash loan request: {:?}", request);
-    info!("Current strategy mode: {}", state.config.strategy);
+    let config = state.config.current();
+    info!("Current strategy mode: {}", config.strategy);
+    let route_mode = route_mode_label(&request.route_mode);
+    let record_outcome = |outcome: &str| {
+        METRICS
+            .requests
+            .with_label_values(&[route_mode, outcome])
+            .inc()
+    };
+    let refuse = |status, outcome, reason: String| {
+        record_outcome(outcome);
+        LoanReply::Refused(LoanFailure::new(status, outcome, reason))
+    };
 
-    // Determine protocol if explicit routing requested
-    let execution_plan = if let Some(p) = &request.explicit_protocol {
-        match state.strategy.override_protocol(&request, *p).await {
-            Ok(plan) => plan,
+    let key = match executions::idempotency_key(headers) {
+        Ok(key) => key,
+        Err(e) => return refuse(StatusCode::BAD_REQUEST, "rejected", e.to_string()),
+    };
+    if let Some(client) = client
+        && let Err(e) = client.check_request(request)
+    {
+        warn!("Flash loan request rejected by client policy: {}", e);
+        return refuse(StatusCode::FORBIDDEN, "forbidden", e.to_string());
+    }
+
+    let client_name = client.map(|ApiClient(key)| key.name.as_str());
+    let idempotency = key.map(|key| IdempotencyKey {
+        key,
+        window_secs: config.executions.idempotency_window_secs,
+    });
+    let id = match state
+        .executions
+        .begin(client_name, request, idempotency, unix_now())
+        .await
+    {
+        Ok(Begin::Started(id)) => id,
+        Ok(Begin::Replay(execution)) => {
+            info!(
+                "Replaying execution {} for its Idempotency-Key",
+                execution.id
+            );
+            record_outcome("replayed");
+            return LoanReply::Replayed(execution);
+        }
+        Ok(Begin::Conflict) => {
+            return refuse(
+                StatusCode::CONFLICT,
+                "conflict",
+                "Idempotency-Key was already used with a different request".to_string(),
+            );
+        }
+        Err(e) => {
+            error!("Failed to record flash loan execution: {}", e);
+            return refuse(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed",
+                "Failed to record the execution".to_string(),
+            );
+        }
+    };
+
+    match execute_loan(state, client, request, &id).await {
+        Ok(response) => {
+            record_outcome("success");
+            info!("Flash loan executed successfully: {:?}", response);
+            LoanReply::Executed { id, response }
+        }
+        Err(failure) => {
+            record_failure(state, client, &id, &failure).await;
+            record_outcome(failure.outcome);
+            LoanReply::Refused(failure)
+        }
+    }
+}
+
+/// Plan several loans together, then execute each on its own
+///
+/// All loans are planned against one snapshot of the collected protocol data, and each
+/// plan's amount is taken off its protocol's liquidity before the next loan is planned.
+/// Loans then run one after another, and every loan gets its own execution record,
+/// webhooks and result. With an `Idempotency-Key`, loan `i` uses the key `<key>/<i>`,
+/// so a retried batch only runs the loans that did not go through.
+///
+/// # Errors
+///
+/// Returns `StatusCode::BAD_REQUEST` for a malformed `Idempotency-Key` or a batch that is
+/// empty or larger than `rate_limits.max_batch_loans`; failures of single loans are
+/// reported in their results
+#[utoipa::path(
+    post,
+    path = "/flashloan/batch",
+    description = "Plans every loan against the same protocol data without reusing liquidity, then executes them in order. Each loan succeeds or fails on its own.",
+    tag = "loans",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Loan `i` is recorded under `<key>/<i>`, so a retry only runs loans that did not go through")
+    ),
+    request_body = FlashLoanBatchRequest,
+    responses(
+        (status = 200, description = "Result of each loan, in request order", body = FlashLoanBatchResponse),
+        (status = 400, description = "Malformed Idempotency-Key, or no loans or too many"),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 429, description = "Rate limit reached")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_flash_loan_batch(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    Json(batch): Json<FlashLoanBatchRequest>,
+) -> Response {
+    let client = client.map(|Extension(client)| client);
+    let ip = ip.map(|Extension(ip)| ip);
+    match run_flash_loan_batch(&state, client.as_ref(), ip, &headers, &batch.loans).await {
+        Ok(response) => Json(response).into_response(),
+        Err(failure) => failure.into_response(),
+    }
+}
+
+/// Record every loan, plan the new ones against one snapshot, then execute them in order
+///
+/// Fails only when the batch as a whole is refused; each loan's own outcome is in its result.
+async fn run_flash_loan_batch(
+    state: &AppState,
+    client: Option<&ApiClient>,
+    ip: Option<ClientIp>,
+    headers: &HeaderMap,
+    loans: &[FlashLoanRequest],
+) -> Result<FlashLoanBatchResponse, LoanFailure> {
+    let config = state.config.current();
+    let max_loans = config.rate_limits.max_batch_loans;
+    info!("Received batch of {} flash loans", loans.len());
+    if loans.is_empty() || loans.len() > max_loans {
+        return Err(LoanFailure::new(
+            StatusCode::BAD_REQUEST,
+            "rejected",
+            format!("A batch holds 1 to {max_loans} loans"),
+        ));
+    }
+    // The rate limit middleware took one token for the request; each further loan costs one more
+    let extra = u32::try_from(loans.len() - 1).unwrap_or(u32::MAX);
+    if let Some(ClientIp(ip)) = ip
+        && let Err(limited) = state.limiter.check_ip_n(ip, extra)
+    {
+        warn!("Rate limited {} on a batch of {} loans", ip, loans.len());
+        return Err(LoanFailure::rate_limited(limited));
+    }
+    if let Some(client) = client
+        && let Err(limited) = state.limiter.check_client_n(client, extra)
+    {
+        warn!(
+            "Rate limited client {} on a batch of {} loans",
+            client.0.name,
+            loans.len()
+        );
+        return Err(LoanFailure::rate_limited(limited));
+    }
+    let key = executions::idempotency_key(headers)
+        .map_err(|e| LoanFailure::new(StatusCode::BAD_REQUEST, "rejected", e))?;
+    let client_name = client.map(|ApiClient(key)| key.name.as_str());
+    let record_outcome = |request: &FlashLoanRequest, outcome: &str| {
+        METRICS
+            .requests
+            .with_label_values(&[route_mode_label(&request.route_mode), outcome])
+            .inc()
+    };
+
+    // Record every loan first, so replays and refusals never reserve liquidity
+    let mut results = Vec::with_capacity(loans.len());
+    let mut started = Vec::new();
+    for (index, request) in loans.iter().enumerate() {
+        if let Some(client) = client
+            && let Err(e) = client.check_request(request)
+        {
+            warn!("Batch loan {} rejected by client policy: {}", index, e);
+            record_outcome(request, "forbidden");
+            results.push(Some(batch_failure(index, None, StatusCode::FORBIDDEN, e)));
+            continue;
+        }
+        let item_key = key.map(|key| format!("{key}/{index}"));
+        let idempotency = item_key.as_deref().map(|key| IdempotencyKey {
+            key,
+            window_secs: config.executions.idempotency_window_secs,
+        });
+        match state
+            .executions
+            .begin(client_name, request, idempotency, unix_now())
+            .await
+        {
+            Ok(Begin::Started(id)) => {
+                started.push((index, id));
+                results.push(None);
+            }
+            Ok(Begin::Replay(execution)) => {
+                record_outcome(request, "replayed");
+                results.push(Some(batch_replay(index, *execution)));
+            }
+            Ok(Begin::Conflict) => {
+                record_outcome(request, "conflict");
+                results.push(Some(batch_failure(
+                    index,
+                    None,
+                    StatusCode::CONFLICT,
+                    "Idempotency-Key was already used with a different request",
+                )));
+            }
             Err(e) => {
-                error!("Explicit protocol override failed: {}", e);
-                return Err(StatusCode::BAD_REQUEST);
+                error!("Failed to record batch loan {}: {}", index, e);
+                record_outcome(request, "failed");
+                results.push(Some(batch_failure(
+                    index,
+                    None,
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to record the execution",
+                )));
             }
         }
-    } else {
-        match state.strategy.generate_execution_plan(&request).await {
-            Ok(plan) => plan,
+    }
+
+    // Count every new loan against the daily limit before any of them reserves liquidity
+    let mut counted = Vec::with_capacity(started.len());
+    for (index, id) in started {
+        match take_daily_loan(state, client).await {
+            Ok(()) => counted.push((index, id)),
+            Err(failure) => {
+                record_failure(state, client, &id, &failure).await;
+                record_outcome(&loans[index], failure.outcome);
+                results[index] = Some(batch_failure(
+                    index,
+                    Some(id),
+                    failure.status,
+                    failure.reason,
+                ));
+            }
+        }
+    }
+    let started = counted;
+
+    let allowed = |protocol| client.is_none_or(|client| client.allows_protocol(protocol));
+    let plans = state
+        .strategy
+        .generate_batch_plans(started.iter().map(|(index, _)| &loans[*index]), allowed)
+        .await;
+    for ((index, id), plan) in started.into_iter().zip(plans) {
+        let request = &loans[index];
+        let result = match plan {
+            Ok(plan) => execute_plan(state, client, plan, &id).await,
             Err(e) => {
-                error!("Failed to generate execution plan: {}", e);
-                return Err(StatusCode::BAD_REQUEST);
+                warn!("Failed to plan batch loan {}: {}", index, e);
+                Err(LoanFailure::new(StatusCode::BAD_REQUEST, "rejected", e))
             }
+        };
+        results[index] = Some(match result {
+            Ok(response) => {
+                record_outcome(request, "success");
+                BatchItemResult {
+                    index,
+                    status: StatusCode::OK.as_u16(),
+                    execution_id: Some(id),
+                    response: Some(response),
+                    error: None,
+                    replayed: false,
+                }
+            }
+            Err(failure) => {
+                record_failure(state, client, &id, &failure).await;
+                record_outcome(request, failure.outcome);
+                batch_failure(index, Some(id), failure.status, failure.reason)
+            }
+        });
+    }
+    // Every loan was either answered up front or started and executed above
+    let results: Vec<_> = results.into_iter().flatten().collect();
+
+    let succeeded = results
+        .iter()
+        .filter(|result| result.status == StatusCode::OK.as_u16())
+        .count();
+    let failed = results
+        .iter()
+        .filter(|result| result.status >= StatusCode::BAD_REQUEST.as_u16())
+        .count();
+    info!(
+        "Batch finished: {} succeeded, {} failed of {}",
+        succeeded,
+        failed,
+        results.len()
+    );
+    Ok(FlashLoanBatchResponse {
+        succeeded,
+        failed,
+        results,
+    })
+}
+
+/// Why a loan was refused or failed
+struct LoanFailure {
+    status: StatusCode,
+    /// `outcome` label of `suiflash_flashloan_requests_total`
+    outcome: &'static str,
+    reason: String,
+    /// Sent as `Retry-After` when a rate limit refused the loan
+    retry_after: Option<Limited>,
+}
+
+impl LoanFailure {
+    fn new(status: StatusCode, outcome: &'static str, reason: impl ToString) -> Self {
+        Self {
+            status,
+            outcome,
+            reason: reason.to_string(),
+            retry_after: None,
         }
+    }
+
+    fn rate_limited(limited: Limited) -> Self {
+        Self {
+            retry_after: Some(limited),
+            ..Self::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                "Rate limit exceeded",
+            )
+        }
+    }
+}
+
+impl IntoResponse for LoanFailure {
+    fn into_response(self) -> Response {
+        if let Some(limited) = self.retry_after {
+            rate_limit::too_many_requests(limited)
+        } else if self.status.is_server_error() {
+            // Internal errors are logged, not echoed to the caller
+            self.status.into_response()
+        } else {
+            (self.status, self.reason).into_response()
+        }
+    }
+}
+
+/// Plan and execute recorded execution `id`, moving it through its lifecycle
+async fn execute_loan(
+    state: &AppState,
+    client: Option<&ApiClient>,
+    request: &FlashLoanRequest,
+    id: &str,
+) -> Result<FlashLoanResponse, LoanFailure> {
+    take_daily_loan(state, client).await?;
+    let execution_plan = plan_loan(state, client, request).await?;
+    execute_plan(state, client, execution_plan, id).await
+}
+
+/// Count a loan against the client's `daily_loan_limit`, refusing it once the limit is reached
+async fn take_daily_loan(state: &AppState, client: Option<&ApiClient>) -> Result<(), LoanFailure> {
+    let Some(ApiClient(key)) = client else {
+        return Ok(());
     };
+    let Some(limit) = key.daily_loan_limit else {
+        return Ok(());
+    };
+    let acquired = state
+        .loan_counts
+        .try_acquire(&key.name, limit, auth::current_utc_day())
+        .await
+        .map_err(|e| {
+            error!("Failed to count a loan for {}: {}", key.name, e);
+            LoanFailure::new(StatusCode::INTERNAL_SERVER_ERROR, "failed", e)
+        })?;
+    if !acquired {
+        warn!("{} reached its daily limit of {} loans", key.name, limit);
+        return Err(LoanFailure::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "rate_limited",
+            format!("daily limit of {limit} loans reached"),
+        ));
+    }
+    Ok(())
+}
+
+/// Route `request` to a protocol `client` may use
+async fn plan_loan(
+    state: &AppState,
+    client: Option<&ApiClient>,
+    request: &FlashLoanRequest,
+) -> Result<ExecutionPlan, LoanFailure> {
+    let allowed = |protocol| client.is_none_or(|client| client.allows_protocol(protocol));
+    // Determine protocol if explicit routing requested
+    if let Some(p) = &request.explicit_protocol {
+        state
+            .strategy
+            .override_protocol_among(request, *p, allowed)
+            .await
+            .map_err(|e| {
+                error!("Explicit protocol override failed: {}", e);
+                LoanFailure::new(StatusCode::BAD_REQUEST, "rejected", e)
+            })
+    } else {
+        state
+            .strategy
+            .generate_execution_plan_among(request, allowed)
+            .await
+            .map_err(|e| {
+                error!("Failed to generate execution plan: {}", e);
+                LoanFailure::new(StatusCode::BAD_REQUEST, "rejected", e)
+            })
+    }
+}
+
+/// Execute the plan made for recorded execution `id`
+async fn execute_plan(
+    state: &AppState,
+    client: Option<&ApiClient>,
+    execution_plan: ExecutionPlan,
+    id: &str,
+) -> Result<FlashLoanResponse, LoanFailure> {
+    let config = state.config.current();
     // Use user_operation to avoid dead code warnings and for observability.
     info!(
         "User operation length: {}",
         execution_plan.user_operation.len()
     );
+    record_status(state, id, ExecutionStatus::Planned).await?;
+    let planned = WebhookPayload {
+        protocol: Some(execution_plan.protocol),
+        ..WebhookPayload::new(WebhookEvent::Planned, id)
+    };
+    notify_webhooks(state, client, &planned).await;
 
-    // Execute the flash loan
-    let tx_digest = match state.executor.execute_flash_loan(&execution_plan).await {
-        Ok(digest) => digest,
-        Err(e) => {
-            error!("Failed to execute flash loan: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    // Record the submission first, so a retry never borrows twice
+    record_status(state, id, ExecutionStatus::Submitted).await?;
+    let submitted = WebhookPayload {
+        event: WebhookEvent::Submitted,
+        created_at: unix_now(),
+        ..planned
     };
+    notify_webhooks(state, client, &submitted).await;
+    let tx_digest = state
+        .executor
+        .execute_flash_loan(&execution_plan)
+        .await
+        .map_err(|e| {
+            error!("Failed to execute flash loan: {}", e);
+            LoanFailure::new(StatusCode::INTERNAL_SERVER_ERROR, "failed", e)
+        })?;
 
     // Calculate fees (protocol + service)
     let protocol_fee = execution_plan.total_cost - execution_plan.amount;
-    let service_fee = u64::try_from(
-        u128::from(execution_plan.amount) * u128::from(state.config.service_fee_bps) / 10_000,
-    )
-    .map_err(|_| {
-        error!("Service fee calculation overflow");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let service_fee = config.service_fee_bps.fee_on(execution_plan.amount);
     let total_fee = protocol_fee + service_fee;
 
+    let label = protocol_label(execution_plan.protocol);
+    let protocol = label.as_ref();
+    METRICS
+        .protocol_selected
+        .with_label_values(&[protocol])
+        .inc();
+    METRICS
+        .fees_charged
+        .with_label_values(&[protocol, "protocol"])
+        .inc_by(protocol_fee);
+    METRICS
+        .fees_charged
+        .with_label_values(&[protocol, "service"])
+        .inc_by(service_fee);
+
     let response = FlashLoanResponse {
         transaction_digest: tx_digest,
         protocol_used: execution_plan.protocol,
@@ -161,26 +962,308 @@ pub async fn handle_flash_loan(
         service_fee,
         total_fee,
     };
+    // The loan went through; bookkeeping errors from here on are only logged
+    if let Err(e) = state.executions.confirm(id, &response, unix_now()).await {
+        error!("Failed to record confirmation of execution {}: {}", id, e);
+    }
+    let flash_loan_event = state
+        .executor
+        .flash_loan_event(&execution_plan)
+        .map_err(|e| warn!("Failed to build flash loan event for {}: {}", id, e))
+        .ok();
+    let confirmed = WebhookPayload {
+        event: WebhookEvent::Confirmed,
+        created_at: unix_now(),
+        digest: Some(response.transaction_digest.clone()),
+        flash_loan_event,
+        ..submitted
+    };
+    notify_webhooks(state, client, &confirmed).await;
 
-    info!("Flash loan executed successfully: {:?}", response);
-    Ok(Json(response))
+    Ok(response)
+}
+
+async fn record_status(
+    state: &AppState,
+    id: &str,
+    status: ExecutionStatus,
+) -> Result<(), LoanFailure> {
+    state
+        .executions
+        .set_status(id, status, unix_now())
+        .await
+        .map_err(|e| {
+            error!("Failed to record execution {} as {:?}: {}", id, status, e);
+            LoanFailure::new(StatusCode::INTERNAL_SERVER_ERROR, "failed", e)
+        })
+}
+
+/// Record `failure` of execution `id` and tell the client's webhooks
+async fn record_failure(
+    state: &AppState,
+    client: Option<&ApiClient>,
+    id: &str,
+    failure: &LoanFailure,
+) {
+    if let Err(e) = state
+        .executions
+        .fail(id, failure.status.as_u16(), &failure.reason, unix_now())
+        .await
+    {
+        error!("Failed to record failure of execution {}: {}", id, e);
+    }
+    let failed = WebhookPayload {
+        error: Some(failure.reason.clone()),
+        ..WebhookPayload::new(WebhookEvent::Failed, id)
+    };
+    notify_webhooks(state, client, &failed).await;
 }
 
+/// Queue `payload` for the client's webhooks; failures are logged, never returned
+async fn notify_webhooks(state: &AppState, client: Option<&ApiClient>, payload: &WebhookPayload) {
+    let client = client.map(|ApiClient(key)| key.name.as_str());
+    if let Err(e) = state.webhooks.notify(client, payload).await {
+        error!(
+            "Failed to queue {} webhooks for execution {}: {}",
+            payload.event.as_str(),
+            payload.execution_id,
+            e
+        );
+    }
+}
+
+/// Answer a repeated request from the execution its `Idempotency-Key` started
+fn replay(execution: Execution) -> Response {
+    let mut response = match (execution.status, &execution.response) {
+        (ExecutionStatus::Confirmed, Some(body)) => Json(body).into_response(),
+        (ExecutionStatus::Failed, _) => execution
+            .http_status
+            .and_then(|status| StatusCode::from_u16(status).ok())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            .into_response(),
+        _ => {
+            let location = format!("/executions/{}", execution.id);
+            let mut response = (StatusCode::ACCEPTED, Json(execution)).into_response();
+            if let Ok(location) = HeaderValue::from_str(&location) {
+                response.headers_mut().insert(header::LOCATION, location);
+            }
+            response
+        }
+    };
+    response.headers_mut().insert(
+        executions::IDEMPOTENT_REPLAYED_HEADER,
+        HeaderValue::from_static("true"),
+    );
+    response
+}
+
+fn batch_failure(
+    index: usize,
+    execution_id: Option<String>,
+    status: StatusCode,
+    reason: impl ToString,
+) -> BatchItemResult {
+    BatchItemResult {
+        index,
+        status: status.as_u16(),
+        execution_id,
+        response: None,
+        error: Some(reason.to_string()),
+        replayed: false,
+    }
+}
+
+/// Answer a batch loan from the execution its `Idempotency-Key` started, like [`replay`]
+fn batch_replay(index: usize, execution: Execution) -> BatchItemResult {
+    let status = match execution.status {
+        ExecutionStatus::Confirmed => StatusCode::OK.as_u16(),
+        ExecutionStatus::Failed => execution
+            .http_status
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR.as_u16()),
+        _ => StatusCode::ACCEPTED.as_u16(),
+    };
+    BatchItemResult {
+        index,
+        status,
+        execution_id: Some(execution.id),
+        response: execution.response,
+        error: execution.error,
+        replayed: true,
+    }
+}
+
+/// Look up an execution started by `POST /flashloan` or `POST /flashloan/batch`
+///
+/// # Errors
+///
+/// Returns `StatusCode::NOT_FOUND` if there is no such execution or another client started
+/// it, and `StatusCode::INTERNAL_SERVER_ERROR` if the store cannot be read
+#[utoipa::path(
+    get,
+    path = "/executions/{id}",
+    description = "Status of a loan, including one still running after an Idempotency-Key replay",
+    tag = "loans",
+    params(("id" = String, Path, description = "Execution id")),
+    responses(
+        (status = 200, description = "The execution", body = Execution),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 404, description = "No execution with this id for the caller")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_execution(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    Path(id): Path<String>,
+) -> Result<Json<Execution>, StatusCode> {
+    let client = client.map(|Extension(client)| client);
+    find_execution(&state, client.as_ref(), &id).await.map(Json)
+}
+
+/// Execution `id`, if `client` started it
+async fn find_execution(
+    state: &AppState,
+    client: Option<&ApiClient>,
+    id: &str,
+) -> Result<Execution, StatusCode> {
+    let execution = state
+        .executions
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to read execution {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Clients only see their own loans
+    if let Some(ApiClient(key)) = client
+        && execution.client.as_deref() != Some(key.name.as_str())
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(execution)
+}
+
+/// Reload configuration from config.toml and the environment
+///
+/// # Errors
+///
+/// Returns `StatusCode::UNPROCESSABLE_ENTITY` with the reason if the new config is invalid
+/// or changes fields that need a restart; the running config is left untouched
+#[utoipa::path(
+    post,
+    path = "/admin/reload",
+    description = "Fields that need a restart are rejected and the running config is kept",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Changed fields, secrets redacted", body = ReloadResponse),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "The API key lacks the admin scope"),
+        (status = 422, description = "The new config was rejected")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_reload(
+    State(state): State<AppState>,
+) -> Result<Json<ReloadResponse>, (StatusCode, String)> {
+    match state.reloader.reload().await {
+        Ok(changes) => Ok(Json(ReloadResponse { changes })),
+        Err(e) => {
+            warn!("Configuration reload rejected: {}", e);
+            Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))
+        }
+    }
+}
+
+/// Liveness check
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "operations",
+    responses((status = 200, description = "The server is up", body = String, content_type = "text/plain"))
+)]
 pub async fn handle_health() -> &'static str {
     "OK"
 }
 
+/// Check the dependencies a loan needs
+#[utoipa::path(
+    get,
+    path = "/ready",
+    description = "Failed checks make the instance unready; warnings such as one stale protocol do not",
+    tag = "operations",
+    responses(
+        (status = 200, description = "Every critical check passed", body = ReadinessReport),
+        (status = 503, description = "At least one critical check failed", body = ReadinessReport)
+    )
+)]
+pub async fn handle_ready(State(state): State<AppState>) -> (StatusCode, Json<ReadinessReport>) {
+    let report = state
+        .readiness
+        .check(
+            state.strategy.collector(),
+            &state.executions,
+            &state.shutdown,
+        )
+        .await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// Expose Prometheus metrics
+///
+/// # Errors
+///
+/// Returns `StatusCode::INTERNAL_SERVER_ERROR` if metrics cannot be encoded
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    description = "Series are prefixed with `suiflash_`",
+    tag = "operations",
+    responses(
+        (status = 200, description = "Prometheus text format", body = String, content_type = "text/plain"),
+        (status = 500, description = "Metrics could not be encoded")
+    )
+)]
+pub async fn handle_metrics(
+    State(state): State<AppState>,
+) -> Result<([(header::HeaderName, &'static str); 1], String), StatusCode> {
+    let data = state.strategy.collector().get_all_protocol_data().await;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    METRICS.observe_data_age(data.values(), now);
+
+    let body = METRICS.encode().map_err(|e| {
+        error!("Failed to encode metrics: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
+
 /// Get available protocols and their data
 ///
 /// # Errors
 ///
 /// Returns `StatusCode::INTERNAL_SERVER_ERROR` if data collection fails
+#[utoipa::path(
+    get,
+    path = "/protocols",
+    description = "Registry entries without a configured adapter are listed under `unknown_protocols`",
+    tag = "protocols",
+    responses((status = 200, description = "Latest collected data per protocol", body = ProtocolsResponse))
+)]
 pub async fn handle_protocols(
     State(state): State<AppState>,
 ) -> Result<Json<ProtocolsResponse>, StatusCode> {
     let data = state.strategy.collector().get_all_protocol_data().await;
     Ok(Json(ProtocolsResponse {
         protocols: data.into_values().collect(),
+        unknown_protocols: state.strategy.collector().unknown_protocols().await,
     }))
 }
 
@@ -189,15 +1272,15 @@ pub async fn handle_protocols(
 /// # Errors
 ///
 /// Returns `StatusCode::INTERNAL_SERVER_ERROR` if data collection fails
+#[utoipa::path(
+    get,
+    path = "/status",
+    description = "Active strategy, service fee and data freshness",
+    tag = "operations",
+    responses((status = 200, description = "Aggregator status", body = StatusResponse))
+)]
 pub async fn handle_status(
     State(state): State<AppState>,
 ) -> Result<Json<StatusResponse>, StatusCode> {
-    let map = state.strategy.collector().get_all_protocol_data().await;
-    let last_updated_any = map.values().map(|d| d.last_updated).max();
-    Ok(Json(StatusResponse {
-        strategy: state.config.strategy.clone(),
-        service_fee_bps: state.config.service_fee_bps,
-        protocol_count: map.len(),
-        last_updated_any,
-    }))
+    Ok(Json(state.strategy.status().await))
 }
@@ -0,0 +1,168 @@
+use std::{borrow::Cow, sync::LazyLock};
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use crate::config::{Protocol, ProtocolData, RouteMode};
+
+/// Process-wide metrics, exposed in Prometheus text format on `GET /metrics`.
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    registry: Registry,
+    /// Flash loan requests by route mode and outcome
+    pub requests: IntCounterVec,
+    /// Protocols chosen for executed plans
+    pub protocol_selected: IntCounterVec,
+    /// Fees charged in base units, split into protocol and service fees
+    pub fees_charged: IntCounterVec,
+    /// Collector fetch latency per protocol and source
+    pub fetch_duration: HistogramVec,
+    /// Collector fetch failures per protocol and source
+    pub fetch_failures: IntCounterVec,
+    /// Seconds since each protocol's data for an asset was last refreshed
+    pub data_age: IntGaugeVec,
+    /// Executor outcomes per stage (dry_run or submission)
+    pub executor_outcomes: IntCounterVec,
+    /// Gas used per flash loan transaction in MIST
+    pub gas_used: HistogramVec,
+}
+
+impl Metrics {
+    #[allow(clippy::expect_used)] // Metric definitions are static; failure is a programming error.
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("suiflash".to_string()), None)
+            .expect("valid metrics registry prefix");
+
+        let requests = IntCounterVec::new(
+            Opts::new("flashloan_requests_total", "Flash loan requests"),
+            &["route_mode", "outcome"],
+        )
+        .expect("valid requests metric");
+        let protocol_selected = IntCounterVec::new(
+            Opts::new(
+                "protocol_selected_total",
+                "Protocols chosen for flash loans",
+            ),
+            &["protocol"],
+        )
+        .expect("valid protocol_selected metric");
+        let fees_charged = IntCounterVec::new(
+            Opts::new("fees_charged_total", "Fees charged in base units"),
+            &["protocol", "kind"],
+        )
+        .expect("valid fees_charged metric");
+        let fetch_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "collector_fetch_duration_seconds",
+                "Protocol data fetch latency",
+            ),
+            &["protocol", "source"],
+        )
+        .expect("valid fetch_duration metric");
+        let fetch_failures = IntCounterVec::new(
+            Opts::new(
+                "collector_fetch_failures_total",
+                "Protocol data fetch failures",
+            ),
+            &["protocol", "source"],
+        )
+        .expect("valid fetch_failures metric");
+        let data_age = IntGaugeVec::new(
+            Opts::new(
+                "protocol_data_age_seconds",
+                "Seconds since protocol data was last refreshed",
+            ),
+            &["protocol", "asset"],
+        )
+        .expect("valid data_age metric");
+        let executor_outcomes = IntCounterVec::new(
+            Opts::new("executor_outcomes_total", "Executor outcomes per stage"),
+            &["stage", "outcome"],
+        )
+        .expect("valid executor_outcomes metric");
+        let gas_used = HistogramVec::new(
+            HistogramOpts::new("gas_used_mist", "Gas used per flash loan transaction")
+                .buckets(prometheus::exponential_buckets(1_000_000.0, 2.0, 10).unwrap_or_default()),
+            &["protocol"],
+        )
+        .expect("valid gas_used metric");
+
+        for collector in [
+            Box::new(requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(protocol_selected.clone()),
+            Box::new(fees_charged.clone()),
+            Box::new(fetch_duration.clone()),
+            Box::new(fetch_failures.clone()),
+            Box::new(data_age.clone()),
+            Box::new(executor_outcomes.clone()),
+            Box::new(gas_used.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            requests,
+            protocol_selected,
+            fees_charged,
+            fetch_duration,
+            fetch_failures,
+            data_age,
+            executor_outcomes,
+            gas_used,
+        }
+    }
+
+    /// Refresh the data age gauges from a collector snapshot
+    pub fn observe_data_age<'a>(&self, data: impl IntoIterator<Item = &'a ProtocolData>, now: u64) {
+        for entry in data {
+            let age = i64::try_from(now.saturating_sub(entry.last_updated)).unwrap_or(i64::MAX);
+            let asset = entry.asset.to_string();
+            self.data_age
+                .with_label_values(&[protocol_label(entry.protocol).as_ref(), &asset])
+                .set(age);
+        }
+    }
+
+    /// Count an executor stage result as success or failure
+    pub fn record_executor_outcome<T>(&self, stage: &str, result: &eyre::Result<T>) {
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        self.executor_outcomes
+            .with_label_values(&[stage, outcome])
+            .inc();
+    }
+
+    /// Encode all metrics in the Prometheus text exposition format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails
+    pub fn encode(&self) -> eyre::Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+pub fn protocol_label(protocol: Protocol) -> Cow<'static, str> {
+    match protocol {
+        Protocol::Navi => "navi".into(),
+        Protocol::Bucket => "bucket".into(),
+        Protocol::Scallop => "scallop".into(),
+        Protocol::DeepBook => "deepbook".into(),
+        Protocol::Suilend => "suilend".into(),
+        Protocol::Registered(id) => format!("protocol_{id}").into(),
+    }
+}
+
+pub const fn route_mode_label(mode: &RouteMode) -> &'static str {
+    match mode {
+        RouteMode::Explicit => "explicit",
+        RouteMode::BestCost => "best_cost",
+        RouteMode::BestLiquidity => "best_liquidity",
+    }
+}
@@ -0,0 +1,61 @@
+use axum::{Router, response::Json, routing::get};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        self,
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+use utoipa_scalar::{Scalar, Servable};
+
+use crate::{auth::API_KEY_HEADER, webhooks::WebhookPayload};
+
+/// Top-level document; paths are added by the routes registered in `api_routes`
+///
+/// `WebhookPayload` is listed by hand since it is sent to receivers rather than served.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "SuiFlash",
+        description = "Flash loan aggregator for Sui: routes each loan to the cheapest or deepest protocol",
+        license(name = "Apache-2.0")
+    ),
+    components(schemas(WebhookPayload)),
+    modifiers(&ApiKeySchemes),
+    tags(
+        (name = "loans", description = "Flash loan execution"),
+        (name = "protocols", description = "Protocol fees and liquidity"),
+        (name = "operations", description = "Health, status and metrics"),
+        (name = "webhooks", description = "Loan lifecycle callbacks"),
+        (name = "admin", description = "Runtime administration")
+    )
+)]
+pub struct ApiDoc;
+
+/// `bearer` for `Authorization: Bearer <key>` and `api_key` for the `X-API-Key` header
+struct ApiKeySchemes;
+
+impl Modify for ApiKeySchemes {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+    }
+}
+
+/// Serve `spec` at `/openapi.json` and a documentation UI for it at `/docs`
+pub fn docs_routes<S>(spec: openapi::OpenApi) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let json = spec.clone();
+    Router::new()
+        .route("/openapi.json", get(move || async move { Json(json) }))
+        .merge(Scalar::with_url("/docs", spec))
+}
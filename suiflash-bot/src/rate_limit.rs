@@ -0,0 +1,321 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::{
+    auth::ApiClient,
+    config::{RateLimitConfig, RateTier, SharedConfig, TokenBucketConfig},
+};
+
+/// Buckets kept before idle ones are first dropped
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// Buckets untouched for this long are dropped by a sweep
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(600);
+
+/// Suggested wait when a concurrency cap is hit, since no refill time applies
+const CONCURRENCY_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Token bucket state for one IP or client
+#[derive(Debug, Clone, Copy)]
+pub struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket
+    pub fn new(config: &TokenBucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(config.burst),
+            updated: now,
+        }
+    }
+
+    /// Take `count` tokens, or return how long until they are available
+    ///
+    /// Config validation keeps batches within the burst, so a full bucket always suffices.
+    ///
+    /// # Errors
+    ///
+    /// Returns the wait until enough tokens are available
+    pub fn try_take(
+        &mut self,
+        config: &TokenBucketConfig,
+        now: Instant,
+        count: u32,
+    ) -> Result<(), Duration> {
+        self.refill(config, now);
+        let needed = f64::from(count);
+        if self.tokens >= needed {
+            self.tokens -= needed;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (needed - self.tokens) / config.requests_per_second,
+        ))
+    }
+
+    fn refill(&mut self, config: &TokenBucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * config.requests_per_second).min(f64::from(config.burst));
+        self.updated = now;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BucketKey {
+    Ip(IpAddr),
+    Client(String),
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    buckets: HashMap<BucketKey, TokenBucket>,
+    /// Bucket count that triggers the next sweep, twice what the last one kept
+    sweep_at: usize,
+    /// In-flight loans per client name
+    in_flight: HashMap<String, usize>,
+    total_in_flight: usize,
+}
+
+/// Limit reached by a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limited {
+    pub retry_after: Duration,
+}
+
+/// Token buckets per IP and per API client plus caps on in-flight loans
+///
+/// Limits are read from the shared config on every request, so reloads apply immediately.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: SharedConfig,
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: SharedConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(LimiterState::default())),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Take a token for `ip` under `rate_limits.per_ip`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Limited`] when the IP's bucket is empty
+    pub fn check_ip(&self, ip: IpAddr) -> Result<(), Limited> {
+        self.check_ip_n(ip, 1)
+    }
+
+    /// Take `count` tokens for `ip`, one per loan of a batch
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Limited`] when the IP's bucket holds fewer tokens
+    pub fn check_ip_n(&self, ip: IpAddr, count: u32) -> Result<(), Limited> {
+        let Some(bucket) = self.config.current().rate_limits.per_ip else {
+            return Ok(());
+        };
+        self.take(BucketKey::Ip(ip), &bucket, count)
+    }
+
+    /// Take a token for `client` under its tier's bucket
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Limited`] when the client's bucket is empty
+    pub fn check_client(&self, client: &ApiClient) -> Result<(), Limited> {
+        self.check_client_n(client, 1)
+    }
+
+    /// Take `count` tokens for `client`, one per loan of a batch
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Limited`] when the client's bucket holds fewer tokens
+    pub fn check_client_n(&self, client: &ApiClient, count: u32) -> Result<(), Limited> {
+        let config = self.config.current();
+        let Some(tier) = tier(&config.rate_limits, client) else {
+            return Ok(());
+        };
+        self.take(
+            BucketKey::Client(client.0.name.clone()),
+            &tier.bucket,
+            count,
+        )
+    }
+
+    fn take(&self, key: BucketKey, config: &TokenBucketConfig, count: u32) -> Result<(), Limited> {
+        let now = Instant::now();
+        let mut state = self.state();
+        // Sweeping only once the map doubles keeps the cost per request constant on average
+        if state.buckets.len() >= state.sweep_at.max(MAX_TRACKED_BUCKETS)
+            && !state.buckets.contains_key(&key)
+        {
+            state.buckets.retain(|_, bucket| {
+                now.saturating_duration_since(bucket.updated) < IDLE_BUCKET_TTL
+            });
+            state.sweep_at = state.buckets.len() * 2;
+        }
+        state
+            .buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::new(config, now))
+            .try_take(config, now, count)
+            .map_err(|retry_after| Limited { retry_after })
+    }
+
+    /// Reserve an in-flight loan slot, released when the permit drops
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Limited`] when the global or the client's tier cap is reached
+    pub fn begin_execution(&self, client: Option<&ApiClient>) -> Result<ExecutionPermit, Limited> {
+        let config = self.config.current();
+        let limited = Limited {
+            retry_after: CONCURRENCY_RETRY_AFTER,
+        };
+        let client_cap = client.and_then(|client| {
+            let cap = tier(&config.rate_limits, client)?.max_concurrent?;
+            Some((client.0.name.clone(), cap))
+        });
+
+        let mut state = self.state();
+        if state.total_in_flight >= config.rate_limits.max_concurrent_executions {
+            return Err(limited);
+        }
+        if let Some((name, cap)) = &client_cap {
+            let in_flight = state.in_flight.entry(name.clone()).or_default();
+            if *in_flight >= *cap {
+                return Err(limited);
+            }
+            *in_flight += 1;
+        }
+        state.total_in_flight += 1;
+
+        Ok(ExecutionPermit {
+            limiter: self.clone(),
+            client: client_cap.map(|(name, _)| name),
+        })
+    }
+}
+
+/// The client's own tier, else `rate_limits.default_tier`
+fn tier<'a>(limits: &'a RateLimitConfig, client: &ApiClient) -> Option<&'a RateTier> {
+    let name = client.0.tier.as_ref().or(limits.default_tier.as_ref())?;
+    limits.tiers.get(name)
+}
+
+/// An in-flight loan slot
+#[derive(Debug)]
+pub struct ExecutionPermit {
+    limiter: RateLimiter,
+    client: Option<String>,
+}
+
+impl Drop for ExecutionPermit {
+    fn drop(&mut self) {
+        let mut state = self.limiter.state();
+        state.total_in_flight = state.total_in_flight.saturating_sub(1);
+        if let Some(name) = &self.client
+            && let Some(in_flight) = state.in_flight.get_mut(name)
+        {
+            *in_flight = in_flight.saturating_sub(1);
+            if *in_flight == 0 {
+                state.in_flight.remove(name);
+            }
+        }
+    }
+}
+
+/// Caller IP: the socket peer, or the first `X-Forwarded-For` hop if trusted
+pub fn client_ip(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trust_forwarded_for: bool,
+) -> Option<IpAddr> {
+    let forwarded = trust_forwarded_for
+        .then(|| headers.get("x-forwarded-for")?.to_str().ok())
+        .flatten()
+        .and_then(|value| value.split(',').next())
+        .and_then(|ip| ip.trim().parse().ok());
+    forwarded.or_else(|| peer.map(|peer| peer.ip()))
+}
+
+/// `429 Too Many Requests` with a whole-second `Retry-After`
+pub fn too_many_requests(limited: Limited) -> Response {
+    let seconds = limited.retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded").into_response();
+    if let Ok(value) = HeaderValue::from_str(&seconds.to_string()) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
+/// Caller IP that `rate_limits.per_ip` was applied to, added to the request by [`limit_ip`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Apply `rate_limits.per_ip`; runs before authentication so key guessing is throttled too
+pub async fn limit_ip(
+    State(limiter): State<RateLimiter>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| *peer);
+    let trust = limiter.config.current().rate_limits.trust_forwarded_for;
+    if let Some(ip) = client_ip(request.headers(), peer, trust) {
+        if let Err(limited) = limiter.check_ip(ip) {
+            warn!("Rate limited {} on {}", ip, request.uri());
+            return too_many_requests(limited);
+        }
+        request.extensions_mut().insert(ClientIp(ip));
+    }
+    next.run(request).await
+}
+
+/// Apply the client's tier and hold an in-flight slot for the rest of the request
+pub async fn limit_client(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client = request.extensions().get::<ApiClient>().cloned();
+    if let Some(client) = &client
+        && let Err(limited) = limiter.check_client(client)
+    {
+        warn!("Rate limited client {}", client.0.name);
+        return too_many_requests(limited);
+    }
+    let _permit = match limiter.begin_execution(client.as_ref()) {
+        Ok(permit) => permit,
+        Err(limited) => {
+            warn!("Too many in-flight loans; rejecting request");
+            return too_many_requests(limited);
+        }
+    };
+    next.run(request).await
+}
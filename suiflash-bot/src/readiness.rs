@@ -0,0 +1,264 @@
+use std::{future::Future, sync::Arc};
+
+use eyre::Result;
+use serde::Serialize;
+use sui_sdk::SuiClient;
+use tokio::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use utoipa::ToSchema;
+
+use crate::{
+    admin,
+    collectors::ProtocolDataCollector,
+    config::{ProtocolData, SharedConfig},
+    executions::{ExecutionStore, unix_now},
+    metrics::protocol_label,
+    rpc,
+    shutdown::Shutdown,
+    signer,
+};
+
+/// Outcome of one readiness check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    /// Degraded, but loans can still be served
+    Warn,
+    /// Loans cannot be served; makes the instance unready
+    Fail,
+}
+
+/// One dependency and how it fared
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ReadinessCheck {
+    /// `rpc`, `signer_balance`, `router`, `database`, `collector`, `shutdown` or
+    /// `protocol:<name>:<asset>`
+    pub component: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl ReadinessCheck {
+    fn new(component: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            component: component.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    /// Pass with `detail`, or fail with the error
+    fn from_result(component: &str, result: Result<String>) -> Self {
+        match result {
+            Ok(detail) => Self::new(component, CheckStatus::Pass, detail),
+            Err(e) => Self::new(component, CheckStatus::Fail, e.to_string()),
+        }
+    }
+}
+
+/// Answer of `GET /ready`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ReadinessReport {
+    /// False when any check failed
+    pub ready: bool,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+impl ReadinessReport {
+    pub fn new(checks: Vec<ReadinessCheck>) -> Self {
+        Self {
+            ready: checks.iter().all(|check| check.status != CheckStatus::Fail),
+            checks,
+        }
+    }
+}
+
+/// Last results of the RPC and database checks
+///
+/// `/ready` is unauthenticated, so without it every request would fan out into RPC calls.
+#[derive(Debug, Clone, Default)]
+pub struct ProbeCache(Arc<Mutex<Option<ProbedChecks>>>);
+
+/// Checks and when they ran
+type ProbedChecks = (Instant, Vec<ReadinessCheck>);
+
+impl ProbeCache {
+    /// Results younger than `ttl`, or those of a fresh `probe`
+    ///
+    /// The lock is held while probing, so concurrent callers share one round.
+    pub async fn get_or_probe(
+        &self,
+        ttl: Duration,
+        probe: impl Future<Output = Vec<ReadinessCheck>>,
+    ) -> Vec<ReadinessCheck> {
+        let mut cached = self.0.lock().await;
+        if let Some((probed_at, checks)) = cached.as_ref()
+            && probed_at.elapsed() < ttl
+        {
+            return checks.clone();
+        }
+        let checks = probe.await;
+        *cached = Some((Instant::now(), checks.clone()));
+        checks
+    }
+}
+
+/// Probes the dependencies a loan needs, with thresholds from `[readiness]`
+#[derive(Clone)]
+pub struct Readiness {
+    client: SuiClient,
+    config: SharedConfig,
+    cache: ProbeCache,
+}
+
+impl Readiness {
+    pub fn new(client: SuiClient, config: SharedConfig) -> Self {
+        Self {
+            client,
+            config,
+            cache: ProbeCache::default(),
+        }
+    }
+
+    /// Run every check; RPC and database checks run concurrently, fail once they exceed
+    /// `readiness.check_timeout_ms` and are reused for `readiness.cache_ttl_ms`
+    pub async fn check(
+        &self,
+        collector: &ProtocolDataCollector,
+        executions: &ExecutionStore,
+        shutdown: &Shutdown,
+    ) -> ReadinessReport {
+        let config = self.config.current();
+        let ttl = Duration::from_millis(config.readiness.cache_ttl_ms);
+        let mut checks = self
+            .cache
+            .get_or_probe(ttl, self.probe_dependencies(executions))
+            .await;
+        checks.extend(freshness_checks(
+            collector.get_all_protocol_data().await.values(),
+            unix_now(),
+            config.readiness.max_data_age_secs,
+        ));
+        checks.push(if shutdown.is_draining() {
+            ReadinessCheck::new("shutdown", CheckStatus::Fail, "Draining in-flight loans")
+        } else {
+            ReadinessCheck::new("shutdown", CheckStatus::Pass, "running")
+        });
+        ReadinessReport::new(checks)
+    }
+
+    /// The RPC, signer balance, router and database checks
+    async fn probe_dependencies(&self, executions: &ExecutionStore) -> Vec<ReadinessCheck> {
+        let config = self.config.current();
+        let timeout = Duration::from_millis(config.readiness.check_timeout_ms);
+
+        let rpc = bounded(timeout, async {
+            let actual = self.client.read_api().get_chain_identifier().await?;
+            rpc::check_chain_identifier(&config, &actual)?;
+            Ok(format!("chain {actual}"))
+        });
+        let balance = bounded(timeout, async {
+            let address = signer::signer_address(&config.private_key)?;
+            let balance = self
+                .client
+                .coin_read_api()
+                .get_balance(address, None)
+                .await?;
+            Ok(balance.total_balance)
+        });
+        let router = bounded(timeout, async {
+            let fields =
+                rpc::read_move_fields(&self.client, config.sui_flash_config_object_id).await?;
+            if admin::router_paused(&fields)? {
+                eyre::bail!("Router is paused");
+            }
+            Ok("not paused".to_string())
+        });
+        let database = bounded(timeout, async {
+            executions.ping().await?;
+            Ok("reachable".to_string())
+        });
+        let (rpc, balance, router, database) = tokio::join!(rpc, balance, router, database);
+
+        vec![
+            ReadinessCheck::from_result("rpc", rpc),
+            match balance {
+                Ok(balance) => balance_check(balance, config.readiness.min_signer_balance_mist),
+                Err(e) => ReadinessCheck::new("signer_balance", CheckStatus::Fail, e.to_string()),
+            },
+            ReadinessCheck::from_result("router", router),
+            ReadinessCheck::from_result("database", database),
+        ]
+    }
+}
+
+/// Await `check`, failing it once `timeout` has passed
+async fn bounded<T>(timeout: Duration, check: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| Err(eyre::eyre!("Timed out after {}ms", timeout.as_millis())))
+}
+
+/// Fail when the signer holds less than `min_mist` SUI to pay gas with
+pub fn balance_check(balance: u128, min_mist: u64) -> ReadinessCheck {
+    let status = if balance < u128::from(min_mist) {
+        CheckStatus::Fail
+    } else {
+        CheckStatus::Pass
+    };
+    ReadinessCheck::new(
+        "signer_balance",
+        status,
+        format!("{balance} MIST (minimum {min_mist})"),
+    )
+}
+
+/// One warning per protocol and asset whose data is older than `max_age_secs`, and a
+/// `collector` check that fails when nothing is fresh, since then nothing can be routed to
+pub fn freshness_checks<'a>(
+    data: impl IntoIterator<Item = &'a ProtocolData>,
+    now: u64,
+    max_age_secs: u64,
+) -> Vec<ReadinessCheck> {
+    let mut data: Vec<_> = data.into_iter().collect();
+    data.sort_by_key(|data| (data.protocol, data.asset));
+
+    let mut fresh = 0;
+    let mut protocols = Vec::with_capacity(data.len());
+    for entry in &data {
+        let age = now.saturating_sub(entry.last_updated);
+        let status = if age > max_age_secs {
+            CheckStatus::Warn
+        } else {
+            fresh += 1;
+            CheckStatus::Pass
+        };
+        protocols.push(ReadinessCheck::new(
+            format!(
+                "protocol:{}:{}",
+                protocol_label(entry.protocol),
+                entry.asset
+            ),
+            status,
+            format!("updated {age}s ago"),
+        ));
+    }
+
+    let collector = if fresh == 0 {
+        ReadinessCheck::new(
+            "collector",
+            CheckStatus::Fail,
+            format!("No protocol data newer than {max_age_secs}s"),
+        )
+    } else {
+        ReadinessCheck::new(
+            "collector",
+            CheckStatus::Pass,
+            format!("{fresh} of {} protocol assets fresh", data.len()),
+        )
+    };
+    std::iter::once(collector).chain(protocols).collect()
+}
@@ -0,0 +1,197 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use eyre::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::{Duration, interval};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::config::{Config, Network, SharedConfig};
+
+/// Fields that take effect on the next request or collection round.
+///
+/// Everything else is read once at startup (RPC connections, signer, listener) or
+/// identifies the deployment, so changing it requires a restart.
+pub const LIVE_FIELDS: [&str; 15] = [
+    "strategy",
+    "service_fee_bps",
+    "refresh_interval_ms",
+    "protocol_apis",
+    "asset_types",
+    "adapters",
+    "deepbook",
+    "suilend",
+    "gas_pool",
+    "gas_policy",
+    "api_keys",
+    "rate_limits",
+    "webhooks",
+    "shutdown",
+    "readiness",
+];
+
+/// Profiles are applied on load, so only their effect on the resolved fields matters
+const IGNORED_FIELDS: [&str; 1] = ["networks"];
+
+/// One changed top-level config field
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ConfigChange {
+    pub field: String,
+    #[schema(value_type = Object)]
+    pub old: Value,
+    #[schema(value_type = Object)]
+    pub new: Value,
+}
+
+/// Body of `POST /admin/reload`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReloadResponse {
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Top-level fields that differ between `old` and `new`, secrets redacted
+pub fn diff_configs(old: &Config, new: &Config) -> Vec<ConfigChange> {
+    let as_map = |config: &Config| match serde_json::to_value(config) {
+        Ok(Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (old_map, new_map) = (as_map(old), as_map(new));
+
+    let mut changes: Vec<_> = new_map
+        .iter()
+        .filter(|(field, _)| !IGNORED_FIELDS.contains(&field.as_str()))
+        .filter_map(|(field, value)| {
+            let previous = old_map.get(field).cloned().unwrap_or(Value::Null);
+            (previous != *value).then(|| ConfigChange {
+                field: field.clone(),
+                old: previous,
+                new: value.clone(),
+            })
+        })
+        .collect();
+
+    // Secrets are not serialized, so compare them directly
+    if old.private_key != new.private_key {
+        changes.push(ConfigChange {
+            field: "private_key".to_string(),
+            old: Value::from(old.private_key.to_string()),
+            new: Value::from(new.private_key.to_string()),
+        });
+    }
+    changes
+}
+
+/// Validate `new` and swap it into `shared` if every change can be applied live
+///
+/// # Errors
+///
+/// Returns an error, leaving the current config untouched, if `new` fails validation
+/// or changes a field outside [`LIVE_FIELDS`]
+pub fn apply_config(shared: &SharedConfig, new: Config) -> Result<Vec<ConfigChange>> {
+    let report = new.validate();
+    for warning in &report.warnings {
+        warn!("Configuration warning: {}", warning);
+    }
+    report.into_result()?;
+
+    // Diff and swap under one lock, so a concurrent reload cannot change the base in between
+    shared.try_update(|current| {
+        let changes = diff_configs(current, &new);
+        let restart_only: Vec<_> = changes
+            .iter()
+            .map(|change| change.field.as_str())
+            .filter(|field| !LIVE_FIELDS.contains(field))
+            .collect();
+        if !restart_only.is_empty() {
+            eyre::bail!(
+                "Changes to {} require a restart; reload rejected",
+                restart_only.join(", ")
+            );
+        }
+        Ok((new, changes))
+    })
+}
+
+/// Reloads the config the bot was started with and applies it to the shared handle
+#[derive(Debug, Clone)]
+pub struct ConfigReloader {
+    shared: SharedConfig,
+    network: Option<Network>,
+    path: PathBuf,
+}
+
+impl ConfigReloader {
+    /// `network` is the `--network` override given at startup, kept for every reload
+    pub fn new(shared: SharedConfig, network: Option<Network>) -> Self {
+        Self {
+            shared,
+            network,
+            path: PathBuf::from("config.toml"),
+        }
+    }
+
+    /// Load config from the same sources as startup and apply it
+    ///
+    /// Files and the environment are read on the blocking thread pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if loading fails or [`apply_config`] rejects the result
+    pub async fn reload(&self) -> Result<Vec<ConfigChange>> {
+        let reloader = self.clone();
+        tokio::task::spawn_blocking(move || reloader.reload_blocking()).await?
+    }
+
+    fn reload_blocking(&self) -> Result<Vec<ConfigChange>> {
+        let config = Config::load_for_network(self.network)
+            .map_err(|e| eyre::eyre!("Failed to load configuration: {}", e))?;
+        let changes = apply_config(&self.shared, config)?;
+
+        if changes.is_empty() {
+            info!("Configuration reloaded, nothing changed");
+        }
+        for change in &changes {
+            info!(
+                "Configuration reloaded: {} {} -> {}",
+                change.field, change.old, change.new
+            );
+        }
+        Ok(changes)
+    }
+
+    /// Poll config.toml and reload whenever its modification time changes
+    pub async fn watch(&self, poll_interval: Duration) {
+        let mut last_modified = modified_at(&self.path);
+        let mut ticker = interval(poll_interval);
+
+        info!(
+            "Watching {} for changes every {}ms",
+            self.path.display(),
+            poll_interval.as_millis()
+        );
+
+        loop {
+            ticker.tick().await;
+            let modified = modified_at(&self.path);
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            info!("{} changed, reloading configuration", self.path.display());
+            if let Err(e) = self.reload().await {
+                error!("Configuration reload failed: {}", e);
+            }
+        }
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
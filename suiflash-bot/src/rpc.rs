@@ -0,0 +1,141 @@
+use eyre::Result;
+use serde_json::Value;
+use sui_json_rpc_types::{SuiObjectDataOptions, SuiObjectResponse, SuiParsedData};
+use sui_sdk::{SuiClient, SuiClientBuilder};
+use sui_types::{
+    base_types::ObjectID, dynamic_field::DynamicFieldName, object::Owner, transaction::ObjectArg,
+};
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Connect to the first reachable RPC URL, trying `sui_rpc_url` before the fallbacks
+///
+/// # Errors
+///
+/// Returns an error if none of the configured URLs accepts a connection
+pub async fn connect_sui_client(config: &Config) -> Result<SuiClient> {
+    let mut last_error = None;
+    for url in config.rpc_urls() {
+        match SuiClientBuilder::default().build(url).await {
+            Ok(client) => {
+                info!("Connected to Sui RPC at {}", url);
+                return Ok(client);
+            }
+            Err(e) => {
+                warn!("Sui RPC {} unreachable: {}", url, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(match last_error {
+        Some(e) => eyre::eyre!("No Sui RPC URL reachable for {}: {}", config.network, e),
+        None => eyre::eyre!("No Sui RPC URL configured for {}", config.network),
+    })
+}
+
+/// Refuse to run against an RPC that serves a different chain than the selected network
+///
+/// # Errors
+///
+/// Returns an error if the chain identifier cannot be fetched or does not match
+pub async fn verify_chain_identifier(client: &SuiClient, config: &Config) -> Result<()> {
+    let actual = client.read_api().get_chain_identifier().await?;
+    check_chain_identifier(config, &actual)?;
+    info!("Sui RPC reports chain {} ({})", actual, config.network);
+    Ok(())
+}
+
+/// Compare a reported chain identifier with the one expected for the selected network
+///
+/// # Errors
+///
+/// Returns an error if an expected identifier is known and differs from `actual`
+pub fn check_chain_identifier(config: &Config, actual: &str) -> Result<()> {
+    match config.expected_chain_id() {
+        Some(expected) if expected != actual => Err(eyre::eyre!(
+            "Sui RPC serves chain {} but {} expects {}",
+            actual,
+            config.network,
+            expected
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fields of a Move object as JSON, e.g. `{"entries": ["0x..."]}` for a `ProtocolRegistry`
+///
+/// # Errors
+///
+/// Returns an error if the object does not exist or is a package
+pub async fn read_move_fields(client: &SuiClient, id: ObjectID) -> Result<Value> {
+    let response = client
+        .read_api()
+        .get_object_with_options(id, SuiObjectDataOptions::new().with_content())
+        .await?;
+    move_fields(response, &id.to_string())
+}
+
+/// Fields of the dynamic field `name` under `parent`, as [`read_move_fields`] returns them
+///
+/// # Errors
+///
+/// Returns an error if the field does not exist
+pub async fn read_dynamic_field(
+    client: &SuiClient,
+    parent: ObjectID,
+    name: DynamicFieldName,
+) -> Result<Value> {
+    let description = format!("Dynamic field {} of {}", name.value, parent);
+    let response = client
+        .read_api()
+        .get_dynamic_field_object(parent, name)
+        .await?;
+    move_fields(response, &description)
+}
+
+/// Transaction argument for the shared object `id`, taken by `&mut` when `mutable`
+///
+/// # Errors
+///
+/// Returns an error if the object does not exist or is not shared
+pub async fn shared_object_arg(
+    client: &SuiClient,
+    id: ObjectID,
+    mutable: bool,
+) -> Result<ObjectArg> {
+    let owner = client
+        .read_api()
+        .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+        .await?
+        .data
+        .ok_or_else(|| eyre::eyre!("Object {} not found", id))?
+        .owner;
+    match owner {
+        Some(Owner::Shared {
+            initial_shared_version,
+        }) => Ok(ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable,
+        }),
+        owner => eyre::bail!("Object {} is not shared (owner: {:?})", id, owner),
+    }
+}
+
+/// Builder and identifier errors come from `anyhow`, which does not convert into `eyre`
+pub fn sdk_error(e: impl std::fmt::Display) -> eyre::Report {
+    eyre::eyre!("{}", e)
+}
+
+fn move_fields(response: SuiObjectResponse, description: &str) -> Result<Value> {
+    let content = response
+        .data
+        .and_then(|data| data.content)
+        .ok_or_else(|| eyre::eyre!("Object {} not found", description))?;
+    match content {
+        SuiParsedData::MoveObject(object) => Ok(object.fields.to_json_value()),
+        SuiParsedData::Package(_) => eyre::bail!("Object {} is a package", description),
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Seconds a client refused during shutdown is told to wait before retrying elsewhere
+const RETRY_AFTER_SECS: &str = "5";
+
+/// Shutdown progress shared by the loan routes, the protocol streams and `serve`
+///
+/// Once [`Shutdown::begin`] is called new loans are refused with `503`, while loans already
+/// running keep their slot until they finish.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    draining: watch::Sender<bool>,
+    /// Loan requests currently being handled
+    loans: watch::Sender<usize>,
+}
+
+/// Marks one loan request in flight until dropped
+#[derive(Debug)]
+pub struct LoanGuard(Shutdown);
+
+impl Drop for LoanGuard {
+    fn drop(&mut self) {
+        self.0.0.loans.send_modify(|loans| *loans -= 1);
+    }
+}
+
+impl Shutdown {
+    /// Stop taking new loans; idempotent
+    pub fn begin(&self) {
+        self.0.draining.send_replace(true);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.0.draining.borrow()
+    }
+
+    /// Resolves once [`Shutdown::begin`] has been called
+    pub async fn requested(&self) {
+        let mut draining = self.0.draining.subscribe();
+        // The sender lives in `self`, so the channel cannot close while this waits
+        let _ = draining.wait_for(|draining| *draining).await;
+    }
+
+    /// Count a loan request in flight, or `None` once shutdown has begun
+    pub fn track_loan(&self) -> Option<LoanGuard> {
+        if self.is_draining() {
+            return None;
+        }
+        self.0.loans.send_modify(|loans| *loans += 1);
+        Some(LoanGuard(self.clone()))
+    }
+
+    pub fn loans_in_flight(&self) -> usize {
+        *self.0.loans.borrow()
+    }
+}
+
+/// Refuse loans once shutdown has begun, and count the rest until they are answered
+pub async fn track_loans(
+    State(shutdown): State<Shutdown>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(_guard) = shutdown.track_loan() else {
+        let mut response = (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Shutting down; retry shortly",
+        )
+            .into_response();
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            HeaderValue::from_static(RETRY_AFTER_SECS),
+        );
+        return response;
+    };
+    next.run(request).await
+}
+
+/// Resolves on SIGINT or, on Unix, SIGTERM
+pub async fn signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => info!("Received SIGINT"),
+        () = terminate => info!("Received SIGTERM"),
+    }
+}
@@ -0,0 +1,37 @@
+use eyre::Result;
+use sui_types::{
+    base_types::SuiAddress,
+    crypto::{EncodeDecodeBase64, SuiKeyPair},
+};
+
+use crate::config::SecretString;
+
+/// Decode the configured private key
+///
+/// Accepts the Bech32 `suiprivkey1...` form printed by `sui keytool export` and the
+/// legacy Base64 `flag || key` form stored in `sui.keystore`.
+///
+/// # Errors
+///
+/// Returns an error if the key is in neither format; the key itself is never included
+pub fn decode_keypair(private_key: &SecretString) -> Result<SuiKeyPair> {
+    let value = private_key.expose().trim();
+    let decoded = if value.starts_with("suiprivkey") {
+        SuiKeyPair::decode(value)
+    } else {
+        SuiKeyPair::decode_base64(value)
+    };
+    decoded.map_err(|_| {
+        eyre::eyre!("private_key is not a valid Bech32 (suiprivkey1...) or Base64 Sui key")
+    })
+}
+
+/// Address of the account that signs flash loan transactions
+///
+/// # Errors
+///
+/// Returns an error if the private key cannot be decoded
+pub fn signer_address(private_key: &SecretString) -> Result<SuiAddress> {
+    let keypair = decode_keypair(private_key)?;
+    Ok(SuiAddress::from(&keypair.public()))
+}
@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use artemis::types::Strategy;
 use async_trait::async_trait;
 use eyre::Result;
@@ -5,64 +7,143 @@ use tracing::{debug, info};
 
 use crate::{
     collectors::ProtocolDataCollector,
-    config::{Config, FlashLoanRequest, Protocol, ProtocolData},
+    config::{
+        Asset, FlashLoanRequest, Protocol, ProtocolData, SharedConfig, StatusResponse, StrategyKind,
+    },
 };
 
 #[derive(Debug, Clone)]
 pub struct FlashLoanStrategy {
-    config: Config,
+    config: SharedConfig,
     collector: ProtocolDataCollector,
 }
 
 impl FlashLoanStrategy {
-    pub const fn new(config: Config, collector: ProtocolDataCollector) -> Self {
-        Self { config, collector }
+    pub fn new(config: impl Into<SharedConfig>, collector: ProtocolDataCollector) -> Self {
+        Self {
+            config: config.into(),
+            collector,
+        }
     }
 
     pub const fn collector(&self) -> &ProtocolDataCollector {
         &self.collector
     }
 
+    /// Active strategy settings and freshness of the collected protocol data
+    pub async fn status(&self) -> StatusResponse {
+        let data = self.collector.get_all_protocol_data().await;
+        let config = self.config.current();
+        let protocols: HashSet<_> = data.keys().map(|(protocol, _)| *protocol).collect();
+        StatusResponse {
+            strategy: config.strategy,
+            service_fee_bps: config.service_fee_bps.get(),
+            protocol_count: protocols.len(),
+            last_updated_any: data.values().map(|d| d.last_updated).max(),
+        }
+    }
+
     /// Find the best protocol for a flash loan request based on strategy
     pub async fn find_best_protocol(&self, request: &FlashLoanRequest) -> Result<Protocol> {
+        self.find_best_protocol_among(request, |_| true).await
+    }
+
+    /// Find the best protocol among those accepted by `allowed`
+    pub async fn find_best_protocol_among(
+        &self,
+        request: &FlashLoanRequest,
+        allowed: impl Fn(Protocol) -> bool,
+    ) -> Result<Protocol> {
         let protocol_data = self.collector.get_all_protocol_data().await;
+        self.select_protocol(&protocol_data, request, &allowed)
+    }
 
-        // Filter protocols that have sufficient liquidity
+    /// Pick the protocol for `request` from `protocol_data` by the configured strategy
+    fn select_protocol(
+        &self,
+        protocol_data: &HashMap<(Protocol, Asset), ProtocolData>,
+        request: &FlashLoanRequest,
+        allowed: &impl Fn(Protocol) -> bool,
+    ) -> Result<Protocol> {
+        let asset = request.asset.parse::<Asset>()?;
+        let adapters = self.collector.adapters();
+        let config = self.config.current();
+
+        // Filter protocols that have sufficient liquidity of the asset and can still lend it
         let viable_protocols: Vec<_> = protocol_data
             .iter()
+            .filter(|((protocol, quoted), _)| *quoted == asset && allowed(*protocol))
             .filter(|(_, data)| data.available_liquidity >= request.amount)
+            .filter(|((protocol, _), _)| adapters.supports(*protocol, asset, &config))
+            .map(|((protocol, _), data)| (protocol, data))
             .collect();
 
         if viable_protocols.is_empty() {
             eyre::bail!(
-                "No protocol has sufficient liquidity for amount: {}",
+                "No protocol has sufficient {} liquidity for amount: {}",
+                asset,
                 request.amount
             );
         }
 
-        let best_protocol = match self.config.strategy.as_str() {
-            "cheapest" => Self::find_cheapest_protocol(&viable_protocols),
-            "highest_liquidity" => Self::find_highest_liquidity_protocol(&viable_protocols),
-            _ => {
-                debug!(
-                    "Unknown strategy '{}', defaulting to cheapest",
-                    self.config.strategy
-                );
-                Self::find_cheapest_protocol(&viable_protocols)
+        let best_protocol = match config.strategy {
+            StrategyKind::Cheapest => {
+                let gas = |protocol| adapters.gas_estimate(protocol, &config);
+                Self::find_cheapest_protocol(&viable_protocols, asset, request.amount, gas)
+            }
+            StrategyKind::HighestLiquidity => {
+                Self::find_highest_liquidity_protocol(&viable_protocols)
             }
         };
 
         info!(
-            "Selected protocol {:?} for flash loan of {} SUI",
-            best_protocol, request.amount
+            "Selected protocol {:?} for flash loan of {} {}",
+            best_protocol, request.amount, asset
         );
         Ok(best_protocol)
     }
 
-    fn find_cheapest_protocol(protocols: &[(&Protocol, &ProtocolData)]) -> Protocol {
+    /// Refuse an explicitly requested `protocol` that [`Self::select_protocol`] would skip
+    fn check_routable(
+        &self,
+        protocol: Protocol,
+        asset: Asset,
+        allowed: &impl Fn(Protocol) -> bool,
+    ) -> Result<()> {
+        if !allowed(protocol) {
+            eyre::bail!("Protocol {:?} is not allowed for this loan", protocol);
+        }
+        if !self
+            .collector
+            .adapters()
+            .supports(protocol, asset, &self.config.current())
+        {
+            eyre::bail!("Protocol {:?} cannot lend {}", protocol, asset);
+        }
+        Ok(())
+    }
+
+    /// Protocol with the lowest fee plus gas for a loan of `amount`
+    ///
+    /// Gas is paid in SUI, so it only adds to the fee of SUI loans; for other assets it
+    /// breaks ties between equal fees.
+    pub fn find_cheapest_protocol(
+        protocols: &[(&Protocol, &ProtocolData)],
+        asset: Asset,
+        amount: u64,
+        gas: impl Fn(Protocol) -> u64,
+    ) -> Protocol {
         protocols
             .iter()
-            .min_by_key(|(_, data)| data.fee_bps)
+            .min_by_key(|(protocol, data)| {
+                let fee = u128::from(amount) * u128::from(data.fee_bps) / 10_000;
+                let gas = u128::from(gas(**protocol));
+                if asset == Asset::SUI {
+                    (fee + gas, 0)
+                } else {
+                    (fee, gas)
+                }
+            })
             .map_or(Protocol::Navi, |(protocol, _)| **protocol) // Default fallback
     }
 
@@ -79,12 +160,18 @@ impl FlashLoanStrategy {
         request: &FlashLoanRequest,
         protocol: Protocol,
     ) -> Result<u64> {
+        let asset = request.asset.parse::<Asset>()?;
         let protocol_data = self
             .collector
-            .get_protocol_data(protocol)
+            .get_protocol_data(protocol, asset)
             .await
-            .ok_or_else(|| eyre::eyre!("No data available for protocol {:?}", protocol))?;
+            .ok_or_else(|| {
+                eyre::eyre!("No {} data available for protocol {:?}", asset, protocol)
+            })?;
+        Self::cost_with(request, &protocol_data)
+    }
 
+    fn cost_with(request: &FlashLoanRequest, protocol_data: &ProtocolData) -> Result<u64> {
         // Protocol fee = amount * fee_bps / 10000
         let protocol_fee =
             (u128::from(request.amount) * u128::from(protocol_data.fee_bps)) / 10_000;
@@ -105,17 +192,18 @@ impl FlashLoanStrategy {
         &self,
         request: &FlashLoanRequest,
     ) -> Result<ExecutionPlan> {
-        let best_protocol = self.find_best_protocol(request).await?;
-        let total_cost = self.calculate_cost(request, best_protocol).await?;
+        self.generate_execution_plan_among(request, |_| true).await
+    }
 
-        Ok(ExecutionPlan {
-            protocol: best_protocol,
-            amount: request.amount,
-            total_cost,
-            user_operation: request.user_operation.clone(),
-            callback_recipient: request.callback_recipient.clone(),
-            callback_payload: request.callback_payload.clone(),
-        })
+    /// Generate an execution plan routed only to protocols accepted by `allowed`
+    pub async fn generate_execution_plan_among(
+        &self,
+        request: &FlashLoanRequest,
+        allowed: impl Fn(Protocol) -> bool,
+    ) -> Result<ExecutionPlan> {
+        let best_protocol = self.find_best_protocol_among(request, allowed).await?;
+        let total_cost = self.calculate_cost(request, best_protocol).await?;
+        ExecutionPlan::new(request, best_protocol, total_cost)
     }
 
     pub async fn override_protocol(
@@ -123,30 +211,83 @@ impl FlashLoanStrategy {
         request: &FlashLoanRequest,
         protocol: Protocol,
     ) -> Result<ExecutionPlan> {
+        self.override_protocol_among(request, protocol, |_| true)
+            .await
+    }
+
+    /// Plan `request` on `protocol`, refused unless `allowed` accepts it
+    pub async fn override_protocol_among(
+        &self,
+        request: &FlashLoanRequest,
+        protocol: Protocol,
+        allowed: impl Fn(Protocol) -> bool,
+    ) -> Result<ExecutionPlan> {
+        let asset = request.asset.parse::<Asset>()?;
+        self.check_routable(protocol, asset, &allowed)?;
         // Ensure liquidity
         let data = self
             .collector
-            .get_protocol_data(protocol)
+            .get_protocol_data(protocol, asset)
             .await
-            .ok_or_else(|| eyre::eyre!("No data for protocol {:?}", protocol))?;
+            .ok_or_else(|| eyre::eyre!("No {} data for protocol {:?}", asset, protocol))?;
         if data.available_liquidity < request.amount {
-            eyre::bail!("Protocol {:?} insufficient liquidity", protocol);
+            eyre::bail!("Protocol {:?} insufficient {} liquidity", protocol, asset);
         }
         let total_cost = self.calculate_cost(request, protocol).await?;
-        Ok(ExecutionPlan {
-            protocol,
-            amount: request.amount,
-            total_cost,
-            user_operation: request.user_operation.clone(),
-            callback_recipient: request.callback_recipient.clone(),
-            callback_payload: request.callback_payload.clone(),
-        })
+        ExecutionPlan::new(request, protocol, total_cost)
+    }
+
+    /// Plan several loans against one snapshot of the collected data
+    ///
+    /// Requests are planned in order and each plan's amount is taken off its protocol's
+    /// liquidity of that asset, so two loans in a batch never count on the same funds. A request that
+    /// cannot be planned gets an error without affecting the others.
+    pub async fn generate_batch_plans<'a>(
+        &self,
+        requests: impl IntoIterator<Item = &'a FlashLoanRequest>,
+        allowed: impl Fn(Protocol) -> bool,
+    ) -> Vec<Result<ExecutionPlan>> {
+        let mut snapshot = self.collector.get_all_protocol_data().await;
+        requests
+            .into_iter()
+            .map(|request| self.plan_against(&mut snapshot, request, &allowed))
+            .collect()
+    }
+
+    /// Plan `request` against `snapshot` and reserve the liquidity it borrows
+    fn plan_against(
+        &self,
+        snapshot: &mut HashMap<(Protocol, Asset), ProtocolData>,
+        request: &FlashLoanRequest,
+        allowed: &impl Fn(Protocol) -> bool,
+    ) -> Result<ExecutionPlan> {
+        let asset = request.asset.parse::<Asset>()?;
+        let protocol = match request.explicit_protocol {
+            Some(protocol) => {
+                self.check_routable(protocol, asset, allowed)?;
+                protocol
+            }
+            None => self.select_protocol(snapshot, request, allowed)?,
+        };
+        let data = snapshot
+            .get_mut(&(protocol, asset))
+            .ok_or_else(|| eyre::eyre!("No {} data for protocol {:?}", asset, protocol))?;
+        if data.available_liquidity < request.amount {
+            eyre::bail!(
+                "Protocol {:?} insufficient liquidity left in this batch",
+                protocol
+            );
+        }
+        let total_cost = Self::cost_with(request, data)?;
+        data.available_liquidity -= request.amount;
+        ExecutionPlan::new(request, protocol, total_cost)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct ExecutionPlan {
     pub protocol: Protocol,
+    pub asset: Asset,
     pub amount: u64,
     pub total_cost: u64,
     pub user_operation: String, // User's arbitrary operation
@@ -154,6 +295,20 @@ pub struct ExecutionPlan {
     pub callback_payload: Option<String>,
 }
 
+impl ExecutionPlan {
+    fn new(request: &FlashLoanRequest, protocol: Protocol, total_cost: u64) -> Result<Self> {
+        Ok(Self {
+            protocol,
+            asset: request.asset.parse()?,
+            amount: request.amount,
+            total_cost,
+            user_operation: request.user_operation.clone(),
+            callback_recipient: request.callback_recipient.clone(),
+            callback_payload: request.callback_payload.clone(),
+        })
+    }
+}
+
 // Placeholder Event type for Artemis integration
 #[allow(dead_code)] // Placeholder event type; retained for future Artemis integration.
 #[derive(Debug, Clone)]
@@ -0,0 +1,299 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{
+        Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::StatusCode,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{Stream, StreamExt, wrappers::ReceiverStream};
+use tracing::{debug, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    AppState,
+    adapters::AdapterRegistry,
+    collectors::{ProtocolChange, ProtocolDataCollector},
+    config::{Asset, Config, Protocol, ProtocolData, SharedConfig},
+    shutdown::Shutdown,
+};
+
+/// Events buffered per subscriber before the feed waits on the client
+const FEED_BUFFER: usize = 64;
+
+/// A message on the protocol stream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProtocolEvent {
+    /// Every matching protocol; sent on connect and again after the subscriber falls behind
+    Snapshot { protocols: Vec<ProtocolData> },
+    /// A protocol's fee or liquidity changed
+    Update { protocol: ProtocolData },
+    /// A protocol no longer has data for the asset and is not routed to for it
+    Removed { protocol: Protocol, asset: Asset },
+}
+
+impl ProtocolEvent {
+    /// SSE event name
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Snapshot { .. } => "snapshot",
+            Self::Update { .. } => "update",
+            Self::Removed { .. } => "removed",
+        }
+    }
+}
+
+/// Query string of the stream endpoints
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct StreamQuery {
+    /// Only protocols that can lend this asset
+    pub asset: Option<String>,
+    /// Comma-separated protocols to follow
+    pub protocols: Option<String>,
+}
+
+/// Which protocols a subscriber follows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtocolFilter {
+    pub asset: Option<Asset>,
+    /// Empty means every protocol
+    pub protocols: Vec<Protocol>,
+}
+
+impl TryFrom<StreamQuery> for ProtocolFilter {
+    type Error = eyre::Report;
+
+    fn try_from(query: StreamQuery) -> Result<Self> {
+        let asset = query.asset.as_deref().map(str::parse).transpose()?;
+        let protocols = query
+            .protocols
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|protocol| !protocol.is_empty())
+            .map(str::parse)
+            .collect::<Result<_>>()?;
+        Ok(Self { asset, protocols })
+    }
+}
+
+impl ProtocolFilter {
+    fn follows(&self, protocol: Protocol) -> bool {
+        self.protocols.is_empty() || self.protocols.contains(&protocol)
+    }
+
+    /// Whether `protocol` is followed and `asset` is the filtered one
+    fn follows_asset(&self, protocol: Protocol, asset: Asset) -> bool {
+        self.follows(protocol) && self.asset.is_none_or(|filtered| filtered == asset)
+    }
+
+    /// Whether `data` is followed and its protocol still lends the asset
+    pub fn matches(
+        &self,
+        data: &ProtocolData,
+        adapters: &AdapterRegistry,
+        config: &Config,
+    ) -> bool {
+        self.follows_asset(data.protocol, data.asset)
+            && adapters.supports(data.protocol, data.asset, config)
+    }
+
+    /// The event a subscriber sees for `change`, if any
+    ///
+    /// Removals are not checked against the adapters, since the asset may have gone with them.
+    pub fn event(
+        &self,
+        change: ProtocolChange,
+        adapters: &AdapterRegistry,
+        config: &Config,
+    ) -> Option<ProtocolEvent> {
+        match change {
+            ProtocolChange::Updated(data) => self
+                .matches(&data, adapters, config)
+                .then_some(ProtocolEvent::Update { protocol: data }),
+            ProtocolChange::Removed(protocol, asset) => self
+                .follows_asset(protocol, asset)
+                .then_some(ProtocolEvent::Removed { protocol, asset }),
+        }
+    }
+
+    /// A snapshot of the matching protocols in `data`, ordered by protocol and asset
+    pub fn snapshot(
+        &self,
+        data: impl IntoIterator<Item = ProtocolData>,
+        adapters: &AdapterRegistry,
+        config: &Config,
+    ) -> ProtocolEvent {
+        let mut protocols: Vec<_> = data
+            .into_iter()
+            .filter(|data| self.matches(data, adapters, config))
+            .collect();
+        protocols.sort_by_key(|data| (data.protocol, data.asset));
+        ProtocolEvent::Snapshot { protocols }
+    }
+}
+
+/// Feed `filter`'s events for one subscriber until it disconnects or shutdown begins
+fn subscribe(
+    collector: ProtocolDataCollector,
+    config: SharedConfig,
+    filter: ProtocolFilter,
+    shutdown: Shutdown,
+) -> mpsc::Receiver<ProtocolEvent> {
+    let (tx, rx) = mpsc::channel(FEED_BUFFER);
+    let feed = async move {
+        // Subscribe before the snapshot so no change falls between the two
+        let mut changes = collector.subscribe();
+        loop {
+            let data = collector.get_all_protocol_data().await;
+            let snapshot =
+                filter.snapshot(data.into_values(), collector.adapters(), &config.current());
+            if tx.send(snapshot).await.is_err() {
+                return;
+            }
+            loop {
+                match changes.recv().await {
+                    Ok(change) => {
+                        let Some(event) =
+                            filter.event(change, collector.adapters(), &config.current())
+                        else {
+                            continue;
+                        };
+                        if tx.send(event).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Stream subscriber skipped {} changes; resending snapshot",
+                            skipped
+                        );
+                        break;
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        }
+    };
+    tokio::spawn(async move {
+        // Dropping the sender ends the SSE response or closes the WebSocket
+        tokio::select! {
+            () = feed => {}
+            () = shutdown.requested() => {}
+        }
+    });
+    rx
+}
+
+fn parse_filter(query: StreamQuery) -> Result<ProtocolFilter, (StatusCode, String)> {
+    ProtocolFilter::try_from(query).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Stream protocol rates and liquidity as server-sent events
+///
+/// # Errors
+///
+/// Returns `StatusCode::BAD_REQUEST` if the asset or a protocol in the filter is unknown
+#[utoipa::path(
+    get,
+    path = "/stream/protocols",
+    description = "Starts with a snapshot, then sends fee and liquidity changes",
+    tag = "protocols",
+    params(StreamQuery),
+    responses(
+        (status = 200, description = "Server-sent events named after their `type`", body = ProtocolEvent, content_type = "text/event-stream"),
+        (status = 400, description = "Unknown asset or protocol in the filter")
+    )
+)]
+pub async fn handle_protocol_sse(
+    State(state): State<AppState>,
+    Query(query): Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let filter = parse_filter(query)?;
+    let events = subscribe(
+        state.strategy.collector().clone(),
+        state.config,
+        filter,
+        state.shutdown,
+    );
+    let events = ReceiverStream::new(events).filter_map(|event| {
+        match Event::default().event(event.kind()).json_data(&event) {
+            Ok(sse) => Some(Ok(sse)),
+            Err(e) => {
+                warn!("Failed to encode {} event: {}", event.kind(), e);
+                None
+            }
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Stream protocol rates and liquidity over a WebSocket, one JSON event per text message
+///
+/// # Errors
+///
+/// Returns `StatusCode::BAD_REQUEST` if the asset or a protocol in the filter is unknown
+#[utoipa::path(
+    get,
+    path = "/stream/protocols/ws",
+    description = "Same events as `/stream/protocols`",
+    tag = "protocols",
+    params(StreamQuery),
+    responses(
+        (status = 101, description = "WebSocket carrying one JSON event per text message", body = ProtocolEvent),
+        (status = 400, description = "Unknown asset or protocol in the filter")
+    )
+)]
+pub async fn handle_protocol_ws(
+    State(state): State<AppState>,
+    Query(query): Query<StreamQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let filter = match parse_filter(query) {
+        Ok(filter) => filter,
+        Err(rejection) => return rejection.into_response(),
+    };
+    let events = subscribe(
+        state.strategy.collector().clone(),
+        state.config,
+        filter,
+        state.shutdown,
+    );
+    upgrade.on_upgrade(|socket| forward(socket, events))
+}
+
+async fn forward(mut socket: WebSocket, mut events: mpsc::Receiver<ProtocolEvent>) {
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Some(event) = event else { break };
+                let text = match serde_json::to_string(&event) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        warn!("Failed to encode {} event: {}", event.kind(), e);
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                // Pings are answered by the socket; anything else from the client is ignored
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!("Protocol stream subscriber disconnected");
+}
@@ -2,28 +2,122 @@
 #[cfg(test)]
 #[allow(clippy::module_inception)]
 mod tests {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        sync::Arc,
+    };
+
+    use ::config::{Config as ConfigSources, File, FileFormat};
+    use clap::Parser;
+
     use crate::{
-        collectors::ProtocolDataCollector,
-        config::{Asset, Config, FlashLoanRequest, Protocol, RouteMode},
+        adapters::{
+            AdapterRegistry, BucketAdapter, DEFAULT_ADAPTER_GAS_OVERHEAD, DeepBookAdapter,
+            NaviAdapter, ProtocolAdapter, RegisteredAdapter, ScallopAdapter, SuilendAdapter,
+            parse_suilend_reserve, parse_vault_balances, parse_versioned,
+        },
+        admin::AdminCall,
+        auth::{ApiClient, DailyLoanCounter, authenticate, hash_api_key, presented_key},
+        cli::{AdminCommand, Cli, Command, ConfigCommand},
+        collectors::{
+            ProtocolChange, ProtocolDataCollector, diff_protocol_data, parse_market_quote,
+            parse_registry_entries,
+        },
+        config::{
+            AdapterConfig, ApiKeyConfig, ApiScope, Asset, AssetTypes, AuthConfig, Bps, Config,
+            DeepBookConfig, DeepBookPool, ExecutionStoreConfig, FlashLoanRequest,
+            FlashLoanResponse, GasPolicyConfig, GasPoolConfig, MarketQuote, Network,
+            NetworkProfile, NetworkProfiles, Protocol, ProtocolApis, RateLimitConfig, RateTier,
+            ReadinessConfig, RegistryEntry, RouteMode, SharedConfig, ShutdownConfig, StrategyKind,
+            SuilendConfig, TokenBucketConfig, WebhookConfig,
+        },
+        executions::{
+            self, Begin, ExecutionStatus, ExecutionStore, IDEMPOTENCY_KEY_HEADER, IdempotencyKey,
+            idempotency_key,
+        },
         executors::FlashLoanExecutor,
+        gas_policy::GasQuote,
+        gas_pool::{GasCoin, GasCoins, plan_rebalance},
+        metrics::METRICS,
+        rate_limit::{Limited, RateLimiter, TokenBucket, client_ip, too_many_requests},
+        readiness::{self, CheckStatus, ReadinessCheck, ReadinessReport},
+        reload::apply_config,
+        rpc::check_chain_identifier,
+        shutdown::Shutdown,
         strategies::FlashLoanStrategy,
+        stream::{ProtocolEvent, ProtocolFilter, StreamQuery},
+        v1,
+        webhooks::{
+            CreateWebhook, DeliveryStatus, WebhookDispatcher, WebhookEvent, WebhookPayload,
+            check_webhook_target, is_public_ip, retry_delay, sign,
+        },
+    };
+    use sui_types::{
+        base_types::{ObjectDigest, ObjectID, SequenceNumber},
+        gas::GasCostSummary,
     };
 
+    fn gas_coin(id: &str, version: u64, balance: u64) -> GasCoin {
+        GasCoin {
+            object_ref: (
+                ObjectID::from_hex_literal(id).unwrap(),
+                SequenceNumber::from_u64(version),
+                ObjectDigest::MIN,
+            ),
+            balance,
+        }
+    }
+
     /// Helper function to create test configuration
+    ///
+    /// Opts into static fallbacks so routing tests do not depend on live protocol APIs.
     fn create_test_config() -> Config {
+        let mut protocol_apis = ProtocolApis::default();
+        protocol_apis.navi.static_fallback = Some(MarketQuote {
+            fee_bps: 8,
+            available_liquidity: 10_000_000_000,
+        });
+        protocol_apis.bucket.static_fallback = Some(MarketQuote {
+            fee_bps: 5,
+            available_liquidity: 5_000_000_000,
+        });
+        protocol_apis.scallop.static_fallback = Some(MarketQuote {
+            fee_bps: 9,
+            available_liquidity: 8_000_000_000,
+        });
+
         Config {
+            network: Network::Testnet,
             sui_rpc_url: "https://fullnode.testnet.sui.io:443".to_string(),
-            private_key: "test_private_key".to_string(),
-            sui_flash_package_id: "0x1234567890abcdef".to_string(),
-            sui_flash_config_object_id: "0xabcdef1234567890".to_string(),
+            sui_rpc_fallback_urls: Vec::new(),
+            private_key: "test_private_key".into(),
+            sui_flash_package_id: ObjectID::from_hex_literal("0x1234567890abcdef").unwrap(),
+            sui_flash_config_object_id: ObjectID::from_hex_literal("0xabcdef1234567890").unwrap(),
+            admin_cap_id: None,
+            registry_object_id: None,
             server_port: 3000,
             refresh_interval_ms: 10000,
-            strategy: "cheapest".to_string(),
-            contract_package_id: "0x1".to_string(),
-            navi_package_id: "0x2".to_string(),
-            bucket_package_id: "0x3".to_string(),
-            scallop_package_id: "0x4".to_string(),
-            service_fee_bps: 40,
+            strategy: StrategyKind::Cheapest,
+            contract_package_id: ObjectID::from_hex_literal("0x1").unwrap(),
+            navi_package_id: ObjectID::from_hex_literal("0x2").unwrap(),
+            bucket_package_id: ObjectID::from_hex_literal("0x3").unwrap(),
+            scallop_package_id: ObjectID::from_hex_literal("0x4").unwrap(),
+            service_fee_bps: Bps::try_from(40).unwrap(),
+            protocol_apis,
+            asset_types: AssetTypes::default(),
+            adapters: BTreeMap::new(),
+            deepbook: DeepBookConfig::default(),
+            suilend: SuilendConfig::default(),
+            gas_pool: GasPoolConfig::default(),
+            gas_policy: GasPolicyConfig::default(),
+            api_keys: Vec::new(),
+            auth: AuthConfig { disabled: true },
+            rate_limits: RateLimitConfig::default(),
+            executions: ExecutionStoreConfig::default(),
+            webhooks: WebhookConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            readiness: ReadinessConfig::default(),
+            networks: NetworkProfiles::default(),
         }
     }
 
@@ -51,9 +145,9 @@ mod tests {
         let data = collector.get_all_protocol_data().await;
 
         // Should have data for all protocols
-        assert!(data.contains_key(&Protocol::Navi));
-        assert!(data.contains_key(&Protocol::Bucket));
-        assert!(data.contains_key(&Protocol::Scallop));
+        assert!(data.contains_key(&(Protocol::Navi, Asset::SUI)));
+        assert!(data.contains_key(&(Protocol::Bucket, Asset::SUI)));
+        assert!(data.contains_key(&(Protocol::Scallop, Asset::SUI)));
 
         // Verify protocol data structure
         for (_protocol, protocol_data) in data {
@@ -84,10 +178,93 @@ mod tests {
         assert!(execution_plan.total_cost > execution_plan.amount);
     }
 
+    #[tokio::test]
+    async fn test_strategy_batch_reserves_liquidity() {
+        let config = create_test_config();
+        let collector = ProtocolDataCollector::new(config.clone()).await;
+        let strategy = FlashLoanStrategy::new(config, collector.clone());
+        collector.collect_all_data().await.unwrap();
+
+        let data = collector.get_all_protocol_data().await;
+        let (&(protocol, _), deepest) = data
+            .iter()
+            .filter(|((_, asset), _)| *asset == Asset::SUI)
+            .max_by_key(|(_, data)| data.available_liquidity)
+            .unwrap();
+        let drain = FlashLoanRequest {
+            amount: deepest.available_liquidity,
+            route_mode: RouteMode::Explicit,
+            explicit_protocol: Some(protocol),
+            ..create_test_request()
+        };
+        let small = create_test_request();
+
+        let plans = strategy
+            .generate_batch_plans([&drain, &drain, &small], |_| true)
+            .await;
+        assert_eq!(plans.len(), 3);
+        assert_eq!(plans[0].as_ref().unwrap().protocol, protocol);
+        // The first loan took all of the protocol's liquidity
+        assert!(plans[1].is_err());
+        // Routing skips the drained protocol
+        assert_ne!(plans[2].as_ref().unwrap().protocol, protocol);
+        // Reservations only live for the batch
+        assert_eq!(collector.get_all_protocol_data().await, data);
+
+        // An explicit protocol is held to the same filter as routing
+        let plans = strategy
+            .generate_batch_plans([&drain], |candidate| candidate != protocol)
+            .await;
+        assert!(plans[0].is_err());
+        assert!(
+            strategy
+                .override_protocol_among(&small, protocol, |candidate| candidate != protocol)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strategy_cheapest_counts_gas() {
+        let data = |protocol, fee_bps| crate::config::ProtocolData {
+            protocol,
+            asset: Asset::SUI,
+            fee_bps,
+            available_liquidity: 10_000_000_000,
+            last_updated: 1,
+        };
+        let navi = data(Protocol::Navi, 8);
+        let bucket = data(Protocol::Bucket, 5);
+        let scallop = data(Protocol::Scallop, 5);
+        let gas = |protocol| match protocol {
+            Protocol::Navi => 1_000_000,
+            Protocol::Bucket => 2_000_000,
+            _ => 1_500_000,
+        };
+
+        // 1 SUI: Navi's 800_000 fee plus gas undercuts Bucket's 500_000 plus twice the gas
+        let protocols = [(&Protocol::Navi, &navi), (&Protocol::Bucket, &bucket)];
+        assert_eq!(
+            FlashLoanStrategy::find_cheapest_protocol(&protocols, Asset::SUI, 1_000_000_000, gas),
+            Protocol::Navi
+        );
+        // Gas is paid in SUI, so a USDC loan goes by fee alone
+        assert_eq!(
+            FlashLoanStrategy::find_cheapest_protocol(&protocols, Asset::USDC, 1_000_000_000, gas),
+            Protocol::Bucket
+        );
+        // Equal fees are settled by gas
+        let protocols = [(&Protocol::Bucket, &bucket), (&Protocol::Scallop, &scallop)];
+        assert_eq!(
+            FlashLoanStrategy::find_cheapest_protocol(&protocols, Asset::USDC, 1_000_000_000, gas),
+            Protocol::Scallop
+        );
+    }
+
     #[tokio::test]
     async fn test_strategy_highest_liquidity_routing() {
         let mut config = create_test_config();
-        config.strategy = "highest_liquidity".to_string();
+        config.strategy = StrategyKind::HighestLiquidity;
 
         let collector = ProtocolDataCollector::new(config.clone()).await;
         let strategy = FlashLoanStrategy::new(config, collector.clone());
@@ -175,6 +352,7 @@ mod tests {
                 // Create a test execution plan
                 let test_plan = crate::strategies::ExecutionPlan {
                     protocol: Protocol::Navi,
+                    asset: Asset::SUI,
                     amount: 1_000_000_000,
                     total_cost: 1_006_000_000, // 1 SUI + 0.6% fee
                     user_operation: "test_operation".to_string(),
@@ -182,12 +360,12 @@ mod tests {
                     callback_payload: None,
                 };
 
-                // This will likely fail in test environment, but tests the gas estimation logic
-                let result = executor.estimate_gas_cost(&test_plan).await;
+                // This will likely fail in test environment, but tests the dry run
+                let result = executor.quote_gas(&test_plan).await;
 
                 match result {
-                    Ok(gas_cost) => {
-                        assert!(gas_cost > 0);
+                    Ok(quote) => {
+                        assert!(quote.gas_budget >= quote.computation_cost + quote.storage_cost);
                     }
                     Err(_) => {
                         // Expected in test environment - error handling works correctly
@@ -209,6 +387,7 @@ mod tests {
                 // Create a test execution plan
                 let test_plan = crate::strategies::ExecutionPlan {
                     protocol: Protocol::Navi,
+                    asset: Asset::SUI,
                     amount: 1_000_000_000,
                     total_cost: 1_006_000_000, // 1 SUI + 0.6% fee
                     user_operation: "test_operation".to_string(),
@@ -233,15 +412,344 @@ mod tests {
 
         // Test required fields are present
         assert!(!config.sui_rpc_url.is_empty());
-        assert!(!config.private_key.is_empty());
-        assert!(!config.sui_flash_package_id.is_empty());
-        assert!(!config.sui_flash_config_object_id.is_empty());
+        assert!(!config.private_key.expose().is_empty());
+        assert_ne!(config.sui_flash_package_id, ObjectID::ZERO);
+        assert_ne!(config.sui_flash_config_object_id, ObjectID::ZERO);
 
         // Test default values
         assert_eq!(config.server_port, 3000);
         assert_eq!(config.refresh_interval_ms, 10000);
-        assert_eq!(config.strategy, "cheapest");
-        assert_eq!(config.service_fee_bps, 40);
+        assert_eq!(config.strategy, StrategyKind::Cheapest);
+        assert_eq!(config.service_fee_bps.get(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_every_problem() {
+        let mut config = create_test_config();
+        assert!(config.validate().is_ok());
+
+        config.network = Network::Mainnet;
+        config.sui_rpc_url = "fullnode.mainnet.sui.io".to_string();
+        config.refresh_interval_ms = 0;
+        config.protocol_apis.bucket.fee_bps_pointer = "flashLoanFee".to_string();
+
+        let report = config.validate();
+        // Bad URL, zero interval, bad pointer, the four 0x1..0x4 placeholder ids and
+        // auth.disabled on mainnet
+        assert_eq!(report.errors.len(), 8, "{report}");
+        assert!(report.errors.iter().any(|e| e.contains("sui_rpc_url")));
+        assert!(
+            report
+                .errors
+                .iter()
+                .any(|e| e.contains("refresh_interval_ms"))
+        );
+        assert!(report.errors.iter().any(|e| e.contains("navi_package_id")));
+        assert!(
+            report
+                .errors
+                .iter()
+                .any(|e| e.contains("bucket.fee_bps_pointer"))
+        );
+        assert!(report.into_result().is_err());
+
+        // Placeholders are only warnings off mainnet
+        let report = create_test_config().validate();
+        assert!(report.is_ok());
+        assert_eq!(report.warnings.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_typed_config_fields_reject_invalid_values() {
+        assert!(serde_json::from_str::<Bps>("10000").is_ok());
+        assert!(serde_json::from_str::<Bps>("10001").is_err());
+        assert_eq!(
+            serde_json::from_str::<StrategyKind>("\"highest_liquidity\"").unwrap(),
+            StrategyKind::HighestLiquidity
+        );
+        assert!(serde_json::from_str::<StrategyKind>("\"fastest\"").is_err());
+        assert!("fastest".parse::<StrategyKind>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_reports_every_malformed_field() {
+        let dir = std::env::temp_dir().join(format!("suiflash-fields-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        let mut toml = create_test_config().to_toml_string(true).unwrap();
+        for (field, bad) in [
+            ("service_fee_bps = 40", "service_fee_bps = 10001"),
+            ("strategy = \"cheapest\"", "strategy = \"fastest\""),
+            ("navi_package_id = \"0x", "navi_package_id = \"0xnothex"),
+        ] {
+            assert!(toml.contains(field), "{field}");
+            toml = toml.replacen(field, bad, 1);
+        }
+        toml = toml.replacen("server_port = 3000\n", "", 1);
+        std::fs::write(&path, toml).unwrap();
+
+        let err = Config::from_toml(&path).unwrap_err().to_string();
+        for field in [
+            "service_fee_bps",
+            "strategy",
+            "navi_package_id",
+            "server_port",
+        ] {
+            assert!(err.contains(field), "{field} missing from: {err}");
+        }
+        assert!(err.contains("10001 bps exceeds the maximum"), "{err}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_private_key_is_redacted() {
+        let config = create_test_config();
+
+        assert!(!format!("{config:?}").contains("test_private_key"));
+        assert_eq!(config.private_key.to_string(), "[REDACTED]");
+        assert_eq!(config.private_key.expose(), "test_private_key");
+    }
+
+    #[tokio::test]
+    async fn test_save_to_toml_skips_secrets_unless_requested() {
+        let config = create_test_config();
+        let dir = std::env::temp_dir().join(format!("suiflash-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let public_path = dir.join("public.toml");
+        config.save_to_toml(&public_path).unwrap();
+        let public = std::fs::read_to_string(&public_path).unwrap();
+        assert!(!public.contains("private_key"));
+        assert!(public.contains("sui_rpc_url"));
+
+        let full_path = dir.join("full.toml");
+        config.save_to_toml_with_secrets(&full_path).unwrap();
+        let reloaded = Config::from_toml(&full_path).unwrap();
+        assert_eq!(reloaded.private_key.expose(), "test_private_key");
+
+        // A key file referenced from the config replaces the inline value
+        let key_path = dir.join("private_key");
+        std::fs::write(&key_path, "file_private_key\n").unwrap();
+        let mut with_file = public;
+        with_file.insert_str(0, &format!("private_key_file = {:?}\n", key_path));
+        std::fs::write(&public_path, with_file).unwrap();
+        let from_file = Config::from_toml(&public_path).unwrap();
+        assert_eq!(from_file.private_key.expose(), "file_private_key");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_network_profile_overrides_top_level_values() {
+        let mut config = create_test_config();
+        let package = ObjectID::from_hex_literal("0xabc").unwrap();
+        config.networks.devnet = Some(NetworkProfile {
+            rpc_urls: vec![
+                "https://devnet.example.com".to_string(),
+                "https://fullnode.devnet.sui.io:443".to_string(),
+            ],
+            chain_id: Some("deadbeef".to_string()),
+            sui_flash_package_id: Some(package),
+            ..NetworkProfile::default()
+        });
+        let file = config.to_toml_string(true).unwrap();
+        let load = |vars: &[(&str, &str)]| {
+            let base = ConfigSources::builder()
+                .add_source(File::from_str(&file, FileFormat::Toml))
+                .build()
+                .unwrap();
+            let vars = vars
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect();
+            let overrides = crate::config::environment(Some(vars)).build().unwrap();
+            Config::from_layers(base, overrides).unwrap()
+        };
+
+        // Profiles for other networks are ignored
+        let loaded = load(&[]);
+        assert_eq!(loaded.sui_rpc_url, "https://fullnode.testnet.sui.io:443");
+
+        let loaded = load(&[("SUIFLASH_NETWORK", "devnet")]);
+        assert_eq!(loaded.sui_rpc_url, "https://devnet.example.com");
+        assert_eq!(
+            loaded.sui_rpc_fallback_urls,
+            vec!["https://fullnode.devnet.sui.io:443".to_string()]
+        );
+        assert_eq!(loaded.sui_flash_package_id, package);
+        assert_eq!(loaded.expected_chain_id(), Some("deadbeef"));
+        assert!("betanet".parse::<Network>().is_err());
+
+        // Environment variables, prefixed or legacy, win over the profile
+        let loaded = load(&[
+            ("SUIFLASH_NETWORK", "devnet"),
+            ("SUIFLASH_SUI_RPC_URL", "https://rpc.example.com"),
+            ("SUI_FLASH_PACKAGE_ID", "0xdef"),
+            ("SUIFLASH_EXECUTIONS__DATABASE_URL", "sqlite::memory:"),
+        ]);
+        assert_eq!(loaded.sui_rpc_url, "https://rpc.example.com");
+        assert_eq!(
+            loaded.sui_flash_package_id,
+            ObjectID::from_hex_literal("0xdef").unwrap()
+        );
+        assert_eq!(loaded.executions.database_url, "sqlite::memory:");
+    }
+
+    #[tokio::test]
+    async fn test_chain_identifier_mismatch_is_rejected() {
+        let mut config = create_test_config();
+        assert!(check_chain_identifier(&config, "4c78adac").is_ok());
+        assert!(check_chain_identifier(&config, "35834a8a").is_err());
+
+        // Localnet resets its chain, so any identifier is accepted
+        config.network = Network::Localnet;
+        assert!(check_chain_identifier(&config, "35834a8a").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reload_applies_live_changes_atomically() {
+        let shared = SharedConfig::new(create_test_config());
+        let reader = shared.clone();
+
+        let mut updated = create_test_config();
+        updated.strategy = StrategyKind::HighestLiquidity;
+        updated.service_fee_bps = Bps::try_from(25).unwrap();
+        let changes = apply_config(&shared, updated).unwrap();
+
+        let fields: Vec<_> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["service_fee_bps", "strategy"]);
+        assert_eq!(changes[1].old, "cheapest");
+        assert_eq!(reader.current().strategy, StrategyKind::HighestLiquidity);
+        assert_eq!(reader.current().service_fee_bps.get(), 25);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reloads_diff_against_each_other() {
+        let shared = SharedConfig::new(create_test_config());
+        let reloads: Vec<_> = (0..8)
+            .map(|fee| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    let mut updated = create_test_config();
+                    updated.service_fee_bps = Bps::try_from(fee).unwrap();
+                    apply_config(&shared, updated).unwrap()
+                })
+            })
+            .collect();
+
+        // Every reload saw the fee the one before it left, so no base is reported twice
+        let mut olds: Vec<_> = reloads
+            .into_iter()
+            .flat_map(|reload| reload.join().unwrap())
+            .map(|change| change.old.as_u64().unwrap())
+            .collect();
+        olds.sort_unstable();
+        olds.dedup();
+        assert_eq!(olds.len(), 8);
+        assert!(olds.contains(&40));
+    }
+
+    #[tokio::test]
+    async fn test_reload_rejects_restart_only_and_invalid_changes() {
+        let shared = SharedConfig::new(create_test_config());
+
+        let mut moved = create_test_config();
+        moved.server_port = 4000;
+        moved.private_key = "rotated".into();
+        moved.strategy = StrategyKind::HighestLiquidity;
+        let err = apply_config(&shared, moved).unwrap_err().to_string();
+        assert!(err.contains("server_port"));
+        assert!(err.contains("private_key"));
+        assert!(!err.contains("rotated"));
+
+        let mut invalid = create_test_config();
+        invalid.refresh_interval_ms = 0;
+        assert!(apply_config(&shared, invalid).is_err());
+
+        // Rejected reloads leave the running config untouched
+        let current = shared.current();
+        assert_eq!(current.server_port, 3000);
+        assert_eq!(current.strategy, StrategyKind::Cheapest);
+        assert_eq!(current.refresh_interval_ms, 10000);
+    }
+
+    #[tokio::test]
+    async fn test_cli_parses_subcommands() {
+        let cli = Cli::try_parse_from([
+            "suiflash",
+            "quote",
+            "--asset",
+            "usdc",
+            "--amount",
+            "1000",
+            "--protocol",
+            "Bucket",
+            "--network",
+            "devnet",
+        ])
+        .unwrap();
+        assert_eq!(cli.network, Some(Network::Devnet));
+        let Some(Command::Quote(loan)) = cli.command else {
+            panic!("expected quote, got {:?}", cli.command);
+        };
+        assert_eq!(loan.asset, Asset::USDC);
+        assert_eq!(loan.amount, 1000);
+        assert_eq!(loan.protocol, Some(Protocol::Bucket));
+
+        // No subcommand means serve, as before the CLI existed
+        assert!(Cli::try_parse_from(["suiflash"]).unwrap().command.is_none());
+        assert!(
+            Cli::try_parse_from(["suiflash", "quote", "--asset", "ETH", "--amount", "1"]).is_err()
+        );
+        assert!(matches!(
+            Cli::try_parse_from(["suiflash", "config", "init", "--force"])
+                .unwrap()
+                .command,
+            Some(Command::Config {
+                action: ConfigCommand::Init { force: true, .. }
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_admin_commands_map_to_move_calls() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(["suiflash", "admin"].iter().chain(args)).map(|cli| cli.command)
+        };
+
+        let Ok(Some(Command::Admin { dry_run, action })) =
+            parse(&["--dry-run", "set-service-fee", "--bps", "25"])
+        else {
+            panic!("admin set-service-fee should parse");
+        };
+        assert!(dry_run);
+        assert!(matches!(action, AdminCommand::SetServiceFee { bps } if bps.get() == 25));
+        assert!(parse(&["set-service-fee", "--bps", "10001"]).is_err());
+        assert!(parse(&["append-adapter", "--package", "not-an-address"]).is_err());
+
+        assert_eq!(AdminCall::SetPaused(true).target(), ("state", "set_paused"));
+        assert_eq!(
+            AdminCall::InitRegistry.target(),
+            ("registry", "init_and_share_registry")
+        );
+        assert_eq!(
+            AdminCall::UpdateAdapter {
+                protocol_id: 1,
+                adapter_package: "0x42".parse().unwrap(),
+            }
+            .target(),
+            ("registry", "update_adapter")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_service_fee_rounds_down() {
+        let bps = Bps::try_from(40).unwrap();
+        assert_eq!(bps.fee_on(1_000_000), 4_000);
+        assert_eq!(bps.fee_on(249), 0);
+        assert_eq!(Bps::try_from(10_000).unwrap().fee_on(u64::MAX), u64::MAX);
     }
 
     #[tokio::test]
@@ -258,9 +766,9 @@ mod tests {
     #[tokio::test]
     async fn test_protocol_enum() {
         // Test Protocol enum values
-        assert_eq!(Protocol::Navi as u8, 0);
-        assert_eq!(Protocol::Bucket as u8, 1);
-        assert_eq!(Protocol::Scallop as u8, 2);
+        assert_eq!(Protocol::Navi.id(), Some(0));
+        assert_eq!(Protocol::Bucket.id(), Some(1));
+        assert_eq!(Protocol::Scallop.id(), Some(2));
 
         // Test serialization
         let protocol = Protocol::Navi;
@@ -268,6 +776,1273 @@ mod tests {
         assert!(serialized.contains("Navi"));
     }
 
+    #[tokio::test]
+    async fn test_registered_protocol_ids() {
+        assert_eq!(Protocol::from_id(1), Protocol::Bucket);
+        assert_eq!(Protocol::from_id(7), Protocol::Registered(7));
+        assert_eq!(Protocol::Registered(7).id(), Some(7));
+        assert_eq!("scallop".parse::<Protocol>().unwrap(), Protocol::Scallop);
+        assert_eq!("4".parse::<Protocol>().unwrap(), Protocol::Registered(4));
+        assert!("aave".parse::<Protocol>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_registry_entries() {
+        let fields = serde_json::json!({
+            "id": { "id": "0x99" },
+            "entries": ["0xa", "0xb", "0xc", "0xd"]
+        });
+        let entries = parse_registry_entries(&fields).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[3].protocol_id, 3);
+        assert_eq!(entries[3].adapter_package, "0xd");
+
+        assert!(parse_registry_entries(&serde_json::json!({ "entries": [1] })).is_err());
+        assert!(parse_registry_entries(&serde_json::json!({})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_adapter_keys_validated() {
+        let adapter = AdapterConfig {
+            name: "example".to_string(),
+            module: "adapter".to_string(),
+            api: ProtocolApis::default().navi,
+            gas_overhead: None,
+        };
+        let mut config = create_test_config();
+        config.adapters.insert("3".to_string(), adapter.clone());
+        assert!(config.validate().is_ok());
+        assert!(config.adapter(3).is_some());
+
+        config.adapters.insert("1".to_string(), adapter.clone());
+        config.adapters.insert(
+            "deepbook".to_string(),
+            AdapterConfig {
+                module: String::new(),
+                ..adapter
+            },
+        );
+        let errors = config.validate().errors;
+        assert!(errors.iter().any(|e| e.contains("adapters.1")));
+        assert!(errors.iter().any(|e| e.contains("adapters.deepbook: key")));
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("adapters.deepbook.module"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adapter_registry() {
+        let config = create_test_config();
+        let adapters = AdapterRegistry::default();
+        assert_eq!(
+            adapters.protocols(),
+            vec![
+                Protocol::Navi,
+                Protocol::Bucket,
+                Protocol::Scallop,
+                Protocol::DeepBook,
+                Protocol::Suilend
+            ]
+        );
+        assert!(adapters.supports(Protocol::Scallop, Asset::USDC, &config));
+        assert!(!adapters.supports(Protocol::DeepBook, Asset::SUI, &config));
+        assert!(!adapters.supports(Protocol::Registered(3), Asset::SUI, &config));
+
+        let entry = RegistryEntry {
+            protocol_id: 3,
+            adapter_package: "0xabc".to_string(),
+        };
+        adapters.sync_discovered(vec![Arc::new(RegisteredAdapter::new(&entry))]);
+        assert_eq!(adapters.protocols().last(), Some(&Protocol::Registered(3)));
+
+        // Clones share the set, and a later sync drops adapters no longer listed
+        adapters.clone().sync_discovered(Vec::new());
+        assert_eq!(adapters.protocols().len(), 5);
+
+        // Adapters registered in code survive discovery
+        adapters.register(Arc::new(RegisteredAdapter::new(&entry)));
+        adapters.sync_discovered(Vec::new());
+        assert!(adapters.get(Protocol::Registered(3)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_adapter_router_ids_and_gas() {
+        let mut config = create_test_config();
+        assert_eq!(NaviAdapter.router_id(&config), Some(0));
+        assert_eq!(BucketAdapter.router_id(&config), Some(1));
+        assert_eq!(ScallopAdapter.router_id(&config), Some(2));
+        assert_eq!(NaviAdapter.gas_overhead(&config), 1_500_000);
+
+        let registered = RegisteredAdapter::new(&RegistryEntry {
+            protocol_id: 3,
+            adapter_package: "0xabc".to_string(),
+        });
+        assert_eq!(registered.router_id(&config), Some(3));
+        assert_eq!(
+            registered.gas_overhead(&config),
+            DEFAULT_ADAPTER_GAS_OVERHEAD
+        );
+        config.adapters.insert(
+            "3".to_string(),
+            AdapterConfig {
+                name: "example".to_string(),
+                module: "example_adapter".to_string(),
+                api: ProtocolApis::default().navi,
+                gas_overhead: Some(700),
+            },
+        );
+        assert_eq!(registered.gas_overhead(&config), 700);
+    }
+
+    #[tokio::test]
+    async fn test_deepbook_adapter() {
+        let mut config = create_test_config();
+        let deepbook = DeepBookAdapter;
+        assert!(!deepbook.enabled(&config));
+        assert!(deepbook.api(&config).is_none());
+
+        config.deepbook = DeepBookConfig {
+            package_id: Some(ObjectID::from_hex_literal("0xdee9").unwrap()),
+            protocol_id: None,
+            pools: vec![DeepBookPool {
+                pool_id: ObjectID::from_hex_literal("0x5001").unwrap(),
+                base: Asset::SUI,
+                quote: Asset::USDC,
+            }],
+        };
+        assert!(config.validate().is_ok());
+        assert!(deepbook.enabled(&config));
+        assert_eq!(
+            deepbook.supported_assets(&config),
+            vec![Asset::SUI, Asset::USDC]
+        );
+        assert_eq!(deepbook.router_id(&config), None);
+
+        config.deepbook.protocol_id = Some(1);
+        config.deepbook.pools[0].quote = Asset::SUI;
+        config.deepbook.package_id = None;
+        let errors = config.validate().errors;
+        assert!(errors.iter().any(|e| e.contains("deepbook.package_id")));
+        assert!(errors.iter().any(|e| e.contains("deepbook.pools[0]")));
+        assert!(errors.iter().any(|e| e.contains("deepbook.protocol_id")));
+    }
+
+    #[tokio::test]
+    async fn test_parse_deepbook_pool_state() {
+        let pool = serde_json::json!({
+            "id": { "id": "0x5001" },
+            "inner": { "id": { "id": "0x77" }, "version": "1" }
+        });
+        let (inner_id, version) = parse_versioned(&pool).unwrap();
+        assert_eq!(inner_id, ObjectID::from_hex_literal("0x77").unwrap());
+        assert_eq!(version, 1);
+
+        let field = serde_json::json!({
+            "name": "1",
+            "value": {
+                "vault": {
+                    "base_balance": "5000000000",
+                    "quote_balance": { "value": "1200" },
+                    "deep_balance": "0"
+                }
+            }
+        });
+        assert_eq!(parse_vault_balances(&field).unwrap(), (5_000_000_000, 1200));
+        assert!(parse_vault_balances(&serde_json::json!({ "value": {} })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_suilend_reserve() {
+        let market = serde_json::json!({
+            "reserves": [
+                {
+                    "coin_type": { "name": "dba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC" },
+                    "available_amount": "1",
+                    "config": { "element": { "borrow_fee_bps": "20" } }
+                },
+                {
+                    "coin_type": { "name": "0000000000000000000000000000000000000000000000000000000000000002::sui::SUI" },
+                    "available_amount": "7000000000",
+                    "config": { "element": { "borrow_fee_bps": "30" } }
+                }
+            ]
+        });
+        let quote = parse_suilend_reserve(&market, "0x2::sui::SUI").unwrap();
+        assert_eq!(
+            quote,
+            MarketQuote {
+                fee_bps: 30,
+                available_liquidity: 7_000_000_000,
+            }
+        );
+        assert!(parse_suilend_reserve(&market, "0x2::coin::COIN<0x5d4b::coin::COIN>").is_err());
+
+        let mut config = create_test_config();
+        assert!(!SuilendAdapter.enabled(&config));
+        config.suilend.lending_market_id = Some(ObjectID::from_hex_literal("0x84").unwrap());
+        assert!(SuilendAdapter.enabled(&config));
+        assert_eq!(SuilendAdapter.router_id(&config), None);
+
+        config.suilend.protocol_id = Some(4);
+        assert_eq!(SuilendAdapter.router_id(&config), Some(4));
+
+        config.deepbook.protocol_id = Some(4);
+        let errors = config.validate().errors;
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("suilend.protocol_id: protocol id 4 is also deepbook"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suilend_quotes_requested_asset_reserve() {
+        let mut config = create_test_config();
+        config.asset_types.usdc =
+            "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"
+                .to_string();
+        let market = serde_json::json!({
+            "reserves": [
+                {
+                    "coin_type": { "name": "0000000000000000000000000000000000000000000000000000000000000002::sui::SUI" },
+                    "available_amount": "7000000000",
+                    "config": { "element": { "borrow_fee_bps": "30" } }
+                },
+                {
+                    "coin_type": { "name": "dba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC" },
+                    "available_amount": "2500000",
+                    "config": { "element": { "borrow_fee_bps": "20" } }
+                }
+            ]
+        });
+
+        // Every asset is collected, and a USDC loan is quoted from the USDC reserve
+        assert!(
+            SuilendAdapter
+                .supported_assets(&config)
+                .contains(&Asset::USDC)
+        );
+        let request = FlashLoanRequest {
+            asset: "USDC".to_string(),
+            ..create_test_request()
+        };
+        let asset = request.asset.parse::<Asset>().unwrap();
+        assert_eq!(
+            SuilendAdapter::reserve_quote(&config, &market, asset).unwrap(),
+            MarketQuote {
+                fee_bps: 20,
+                available_liquidity: 2_500_000,
+            }
+        );
+        assert_eq!(
+            SuilendAdapter::reserve_quote(&config, &market, Asset::SUI)
+                .unwrap()
+                .fee_bps,
+            30
+        );
+        // No reserve lends USDT, so it drops out of routing rather than borrowing SUI data
+        assert!(SuilendAdapter::reserve_quote(&config, &market, Asset::USDT).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_gas_coin_leases() {
+        let mut coins = GasCoins::default();
+        coins.sync(vec![gas_coin("0xa", 1, 100), gas_coin("0xb", 1, 300)]);
+
+        let largest = coins.take().unwrap();
+        assert_eq!(largest, gas_coin("0xb", 1, 300));
+        let other = coins.take().unwrap();
+        assert!(coins.take().is_none());
+        assert_eq!(coins.leased(), 2);
+
+        // A stale read must neither re-offer leased coins nor roll back a settled one
+        coins.restore(largest.id(), Some(gas_coin("0xb", 2, 250)));
+        coins.sync(vec![gas_coin("0xa", 1, 100), gas_coin("0xb", 1, 300)]);
+        assert_eq!(coins.available(), &[gas_coin("0xb", 2, 250)]);
+
+        // A coin returned without effects waits for the next read
+        coins.restore(other.id(), None);
+        assert_eq!(coins.leased(), 0);
+        assert_eq!(coins.available().len(), 1);
+        coins.sync(vec![gas_coin("0xa", 3, 90), gas_coin("0xb", 2, 250)]);
+        assert_eq!(coins.available().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_plan_gas_pool_rebalance() {
+        let settings = GasPoolConfig {
+            target_size: 4,
+            coin_balance: 100_000_000,
+            min_coin_balance: 10_000_000,
+            ..GasPoolConfig::default()
+        };
+        assert!(plan_rebalance(&[], 0, &settings).is_none());
+
+        let whale = gas_coin("0x1", 1, 1_000_000_000);
+        let dust = gas_coin("0x2", 1, 1_000);
+        let plan = plan_rebalance(&[dust, whale], 0, &settings).unwrap();
+        assert_eq!(plan.primary, whale);
+        assert_eq!(plan.merge, vec![dust]);
+        assert_eq!(plan.split, vec![100_000_000; 3]);
+
+        // Splits are capped by what the largest coin can fund beyond its own share and gas
+        let small = gas_coin("0x3", 1, 250_000_000);
+        let plan = plan_rebalance(&[small], 0, &settings).unwrap();
+        assert_eq!(plan.split, vec![100_000_000]);
+
+        let full = [whale, small, gas_coin("0x4", 1, 100_000_000)];
+        assert!(plan_rebalance(&full, 1, &settings).is_none());
+
+        let mut config = create_test_config();
+        config.gas_pool = GasPoolConfig {
+            min_coin_balance: 200_000_000,
+            lease_timeout_ms: 0,
+            ..settings
+        };
+        let errors = config.validate().errors;
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("gas_pool.min_coin_balance"))
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("gas_pool.lease_timeout_ms"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gas_quote_policy() {
+        let costs = GasCostSummary {
+            computation_cost: 4_000_000,
+            storage_cost: 2_000_000,
+            ..GasCostSummary::default()
+        };
+        let policy = GasPolicyConfig {
+            safety_factor: 1.5,
+            priority_fee_multiplier: 2.0,
+            max_gas_per_loan: 15_000_000,
+        };
+        let quote = GasQuote::new(750, &costs, &policy).unwrap();
+        assert_eq!(
+            quote,
+            GasQuote {
+                reference_gas_price: 750,
+                gas_price: 1_500,
+                computation_cost: 8_000_000,
+                storage_cost: 2_000_000,
+                gas_budget: 15_000_000,
+            }
+        );
+
+        let capped = GasPolicyConfig {
+            max_gas_per_loan: 14_999_999,
+            ..policy
+        };
+        assert!(GasQuote::new(750, &costs, &capped).is_err());
+
+        let mut config = create_test_config();
+        config.gas_policy = GasPolicyConfig {
+            safety_factor: 0.9,
+            priority_fee_multiplier: f64::NAN,
+            max_gas_per_loan: 0,
+        };
+        let errors = config.validate().errors;
+        for key in [
+            "safety_factor",
+            "priority_fee_multiplier",
+            "max_gas_per_loan",
+        ] {
+            assert!(
+                errors
+                    .iter()
+                    .any(|e| e.contains(&format!("gas_policy.{key}")))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_key_authentication() {
+        let key = ApiKeyConfig {
+            name: "arb".to_string(),
+            key_hash: hash_api_key("secret-key"),
+            scopes: vec![ApiScope::Loans],
+            allowed_assets: vec![Asset::SUI],
+            allowed_protocols: vec![Protocol::Navi, Protocol::Bucket],
+            max_loan_amount: Some(5_000_000_000),
+            daily_loan_limit: Some(2),
+            tier: None,
+        };
+        let keys = [key.clone()];
+        assert_eq!(authenticate(&keys, "secret-key").unwrap().name, "arb");
+        assert!(authenticate(&keys, "secret-kez").is_none());
+
+        let mut headers = axum::http::HeaderMap::new();
+        assert!(presented_key(&headers).is_none());
+        headers.insert("x-api-key", "secret-key".parse().unwrap());
+        assert_eq!(presented_key(&headers), Some("secret-key"));
+        headers.insert("authorization", "Bearer other".parse().unwrap());
+        assert_eq!(presented_key(&headers), Some("other"));
+
+        let client = ApiClient(key.clone());
+        let mut request = create_test_request();
+        assert!(client.check_request(&request).is_ok());
+        request.asset = "USDC".to_string();
+        assert!(client.check_request(&request).is_err());
+        request.asset = "SUI".to_string();
+        request.amount = 6_000_000_000;
+        assert!(client.check_request(&request).is_err());
+        request.amount = 1;
+        request.explicit_protocol = Some(Protocol::Scallop);
+        assert!(client.check_request(&request).is_err());
+        assert!(client.allows_protocol(Protocol::Bucket));
+
+        let store = ExecutionStore::open(&ExecutionStoreConfig {
+            database_url: "sqlite::memory:".to_string(),
+            ..ExecutionStoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let counter = DailyLoanCounter::new(&store).await.unwrap();
+        assert!(counter.try_acquire("arb", 2, 100).await.unwrap());
+        assert!(counter.try_acquire("arb", 2, 100).await.unwrap());
+        assert!(!counter.try_acquire("arb", 2, 100).await.unwrap());
+        assert!(counter.try_acquire("other", 2, 100).await.unwrap());
+        // A counter reopened on the same store keeps the day's counts
+        let counter = DailyLoanCounter::new(&store).await.unwrap();
+        assert!(!counter.try_acquire("arb", 2, 100).await.unwrap());
+        assert!(counter.try_acquire("arb", 2, 101).await.unwrap());
+
+        let mut config = create_test_config();
+        config.api_keys = vec![
+            key.clone(),
+            ApiKeyConfig {
+                key_hash: "not-a-hash".to_string(),
+                scopes: Vec::new(),
+                ..key
+            },
+        ];
+        let errors = config.validate().errors;
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors.iter().any(|e| e.contains("name is not unique")));
+        assert!(errors.iter().any(|e| e.contains("key_hash")));
+        assert!(errors.iter().any(|e| e.contains("scopes is empty")));
+
+        // Without keys the endpoints stay closed unless auth is switched off, never on mainnet
+        let mut config = create_test_config();
+        config.auth.disabled = false;
+        let errors = config.validate().errors;
+        assert!(errors.iter().any(|e| e.contains("no API keys configured")));
+        config.auth.disabled = true;
+        config.network = Network::Mainnet;
+        let errors = config.validate().errors;
+        assert!(errors.iter().any(|e| e.contains("auth.disabled")));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits() {
+        let bucket = TokenBucketConfig {
+            requests_per_second: 2.0,
+            burst: 2,
+        };
+        let start = std::time::Instant::now();
+        let mut tokens = TokenBucket::new(&bucket, start);
+        assert!(tokens.try_take(&bucket, start, 1).is_ok());
+        assert!(tokens.try_take(&bucket, start, 1).is_ok());
+        let wait = tokens.try_take(&bucket, start, 1).unwrap_err();
+        assert_eq!(wait, std::time::Duration::from_millis(500));
+        assert!(
+            tokens
+                .try_take(&bucket, start + std::time::Duration::from_millis(500), 1)
+                .is_ok()
+        );
+        // A batch takes one token per loan
+        let mut tokens = TokenBucket::new(&bucket, start);
+        assert!(tokens.try_take(&bucket, start, 2).is_ok());
+        assert_eq!(
+            tokens.try_take(&bucket, start, 2).unwrap_err(),
+            std::time::Duration::from_secs(1)
+        );
+
+        let response = too_many_requests(Limited { retry_after: wait });
+        assert_eq!(response.status(), axum::http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "1");
+
+        let mut config = create_test_config();
+        config.rate_limits.max_concurrent_executions = 2;
+        config.rate_limits.tiers.insert(
+            "basic".to_string(),
+            RateTier {
+                bucket,
+                max_concurrent: Some(1),
+            },
+        );
+        let client = ApiClient(ApiKeyConfig {
+            name: "arb".to_string(),
+            key_hash: hash_api_key("key"),
+            scopes: vec![ApiScope::Loans],
+            allowed_assets: Vec::new(),
+            allowed_protocols: Vec::new(),
+            max_loan_amount: None,
+            daily_loan_limit: None,
+            tier: Some("basic".to_string()),
+        });
+        let limiter = RateLimiter::new(SharedConfig::new(config.clone()));
+        let first = limiter.begin_execution(Some(&client)).unwrap();
+        assert!(limiter.begin_execution(Some(&client)).is_err());
+        let other = limiter.begin_execution(None).unwrap();
+        assert!(limiter.begin_execution(None).is_err());
+        drop(first);
+        assert!(limiter.begin_execution(Some(&client)).is_ok());
+        drop(other);
+        assert!(limiter.check_client(&client).is_ok());
+        assert!(limiter.check_client(&client).is_ok());
+        assert!(limiter.check_client(&client).is_err());
+
+        // A key without a tier falls back to rate_limits.default_tier
+        let untiered = ApiClient(ApiKeyConfig {
+            name: "untiered".to_string(),
+            tier: None,
+            ..client.0.clone()
+        });
+        assert!(limiter.check_client(&untiered).is_ok());
+        assert!(limiter.check_client(&untiered).is_ok());
+        assert!(limiter.check_client(&untiered).is_ok());
+        let mut tiered = config.clone();
+        tiered.rate_limits.default_tier = Some("basic".to_string());
+        let limiter = RateLimiter::new(SharedConfig::new(tiered));
+        assert!(limiter.check_client(&untiered).is_ok());
+        assert!(limiter.check_client(&untiered).is_ok());
+        assert!(limiter.check_client(&untiered).is_err());
+
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        let peer = "10.0.0.1:4000".parse().ok();
+        assert_eq!(
+            client_ip(&headers, peer, false),
+            Some("10.0.0.1".parse().unwrap())
+        );
+        assert_eq!(
+            client_ip(&headers, peer, true),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        // Each further loan of a batch costs the caller's IP a token too
+        let ip = "203.0.113.7".parse().unwrap();
+        let per_ip = config.rate_limits.per_ip.unwrap().burst;
+        assert!(limiter.check_ip(ip).is_ok());
+        assert!(limiter.check_ip_n(ip, per_ip - 1).is_ok());
+        assert!(limiter.check_ip(ip).is_err());
+
+        config.api_keys = vec![ApiKeyConfig {
+            tier: Some("gold".to_string()),
+            ..client.0
+        }];
+        config.rate_limits.per_ip = Some(TokenBucketConfig {
+            requests_per_second: 0.0,
+            burst: 0,
+        });
+        config.rate_limits.default_tier = Some("silver".to_string());
+        config.rate_limits.max_batch_loans = 4;
+        config.rate_limits.tiers.insert(
+            "bronze".to_string(),
+            RateTier {
+                bucket: TokenBucketConfig {
+                    requests_per_second: 1.0,
+                    burst: 3,
+                },
+                max_concurrent: None,
+            },
+        );
+        let errors = config.validate().errors;
+        assert!(errors.iter().any(|e| e.contains("tier 'gold'")));
+        assert!(errors.iter().any(|e| e.contains("default_tier 'silver'")));
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("per_ip.requests_per_second"))
+        );
+        assert!(errors.iter().any(|e| e.contains("per_ip.burst")));
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("tiers.bronze.burst 3 is below rate_limits.max_batch_loans"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_market_quote_with_configured_pointers() {
+        let apis = ProtocolApis::default();
+        let navi_response = serde_json::json!({
+            "pools": [
+                { "coinType": "0x2::coin::COIN<0xdead::usdc::USDC>", "flashLoanFeeBps": 3 },
+                { "coinType": "0x2::sui::SUI", "flashLoanFeeBps": 8, "availableLiquidity": "42" }
+            ]
+        });
+
+        let quote = parse_market_quote(&navi_response, &apis.navi, "0x2::sui::SUI").unwrap();
+        assert_eq!(quote.fee_bps, 8);
+        assert_eq!(quote.available_liquidity, 42);
+        assert!(parse_market_quote(&navi_response, &apis.navi, "0x2::missing::COIN").is_err());
+        // Adapters pick each asset out of the round's single API response
+        let config = create_test_config();
+        let quote = NaviAdapter
+            .api_quote(&config, &navi_response, Asset::SUI)
+            .unwrap();
+        assert_eq!(quote.available_liquidity, 42);
+        assert!(
+            NaviAdapter
+                .api_quote(&config, &navi_response, Asset::USDT)
+                .is_err()
+        );
+
+        // Root-level markets are read as-is
+        let bucket_response = serde_json::json!({ "flashLoanFee": 5, "availableLiquidity": 7 });
+        let quote = parse_market_quote(&bucket_response, &apis.bucket, "0x2::sui::SUI").unwrap();
+        assert_eq!(quote.fee_bps, 5);
+        assert_eq!(quote.available_liquidity, 7);
+    }
+
+    #[tokio::test]
+    async fn test_parse_market_quote_rejects_missing_and_malformed_fields() {
+        let apis = ProtocolApis::default();
+
+        // Missing liquidity must not be invented
+        let missing = serde_json::json!({ "flashLoanFee": 9 });
+        assert!(parse_market_quote(&missing, &apis.scallop, "0x2::sui::SUI").is_err());
+
+        // Negative, fractional and non-numeric values are rejected
+        for liquidity in [
+            serde_json::json!(-1),
+            serde_json::json!(1.5),
+            serde_json::json!("lots"),
+        ] {
+            let malformed = serde_json::json!({ "flashLoanFee": 9, "totalLiquidity": liquidity });
+            assert!(parse_market_quote(&malformed, &apis.scallop, "0x2::sui::SUI").is_err());
+        }
+
+        // Fees above 100% are not a valid schema
+        let fee_too_high = serde_json::json!({ "flashLoanFee": 10_001, "totalLiquidity": 1 });
+        assert!(parse_market_quote(&fee_too_high, &apis.scallop, "0x2::sui::SUI").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_exposition() {
+        METRICS
+            .requests
+            .with_label_values(&["best_cost", "success"])
+            .inc();
+        METRICS.record_executor_outcome("dry_run", &Ok::<_, eyre::Report>(()));
+
+        let stale = crate::config::ProtocolData {
+            protocol: Protocol::Bucket,
+            asset: Asset::USDC,
+            fee_bps: 5,
+            available_liquidity: 1,
+            last_updated: 100,
+        };
+        METRICS.observe_data_age([&stale], 160);
+
+        let body = METRICS.encode().unwrap();
+        assert!(body.contains("suiflash_flashloan_requests_total"));
+        assert!(
+            body.contains(
+                "suiflash_executor_outcomes_total{outcome=\"success\",stage=\"dry_run\"}"
+            )
+        );
+        assert!(
+            body.contains(
+                "suiflash_protocol_data_age_seconds{asset=\"USDC\",protocol=\"bucket\"} 60"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_protocol_stream_filter() {
+        let data =
+            |protocol, fee_bps, available_liquidity, last_updated| crate::config::ProtocolData {
+                protocol,
+                asset: Asset::SUI,
+                fee_bps,
+                available_liquidity,
+                last_updated,
+            };
+        let keyed = |entries: [crate::config::ProtocolData; 3]| {
+            entries
+                .into_iter()
+                .map(|data| ((data.protocol, data.asset), data))
+                .collect::<HashMap<_, _>>()
+        };
+        let old = keyed([
+            data(Protocol::Navi, 8, 100, 1),
+            data(Protocol::Bucket, 5, 100, 1),
+            data(Protocol::Scallop, 9, 100, 1),
+        ]);
+        let new = keyed([
+            // Only the timestamp moved, so subscribers are not told
+            data(Protocol::Navi, 8, 100, 2),
+            data(Protocol::Bucket, 5, 90, 2),
+            data(Protocol::DeepBook, 0, 50, 2),
+        ]);
+        let mut changes = diff_protocol_data(&old, &new);
+        changes.sort_by_key(|change| match change {
+            ProtocolChange::Updated(data) => data.protocol,
+            ProtocolChange::Removed(protocol, _) => *protocol,
+        });
+        assert_eq!(
+            changes,
+            vec![
+                ProtocolChange::Updated(data(Protocol::Bucket, 5, 90, 2)),
+                ProtocolChange::Removed(Protocol::Scallop, Asset::SUI),
+                ProtocolChange::Updated(data(Protocol::DeepBook, 0, 50, 2)),
+            ]
+        );
+
+        let config = create_test_config();
+        let adapters = AdapterRegistry::default();
+        let filter = ProtocolFilter::try_from(StreamQuery {
+            asset: Some("SUI".to_string()),
+            protocols: Some("navi, deepbook,scallop".to_string()),
+        })
+        .unwrap();
+        assert_eq!(
+            filter.protocols,
+            vec![Protocol::Navi, Protocol::DeepBook, Protocol::Scallop]
+        );
+
+        // DeepBook cannot lend SUI and Bucket is not followed
+        let snapshot = filter.snapshot(new.into_values(), &adapters, &config);
+        assert_eq!(
+            snapshot,
+            ProtocolEvent::Snapshot {
+                protocols: vec![data(Protocol::Navi, 8, 100, 2)]
+            }
+        );
+        let events: Vec<_> = changes
+            .into_iter()
+            .filter_map(|change| filter.event(change, &adapters, &config))
+            .collect();
+        assert_eq!(
+            events,
+            vec![ProtocolEvent::Removed {
+                protocol: Protocol::Scallop,
+                asset: Asset::SUI,
+            }]
+        );
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["type"], "removed");
+
+        assert!(
+            ProtocolFilter::try_from(StreamQuery {
+                asset: Some("DOGE".to_string()),
+                protocols: None,
+            })
+            .is_err()
+        );
+        assert!(
+            ProtocolFilter::try_from(StreamQuery {
+                asset: None,
+                protocols: Some("navi,nope".to_string()),
+            })
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execution_store_idempotency() {
+        let store = ExecutionStore::open(&ExecutionStoreConfig {
+            database_url: "sqlite::memory:".to_string(),
+            ..ExecutionStoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let request = FlashLoanRequest {
+            asset: "SUI".to_string(),
+            amount: 1_000,
+            route_mode: RouteMode::BestCost,
+            explicit_protocol: None,
+            user_operation: "arbitrage".to_string(),
+            callback_recipient: None,
+            callback_payload: None,
+        };
+        let other = FlashLoanRequest {
+            amount: 2_000,
+            ..request.clone()
+        };
+        let key = |key| {
+            Some(IdempotencyKey {
+                key,
+                window_secs: 60,
+            })
+        };
+        let started = |begin| match begin {
+            Begin::Started(id) => id,
+            other => panic!("expected a new execution, got {other:?}"),
+        };
+        let replayed = |begin| match begin {
+            Begin::Replay(execution) => execution,
+            other => panic!("expected a replay, got {other:?}"),
+        };
+
+        // Requests without a key always run
+        started(store.begin(None, &request, None, 100).await.unwrap());
+        started(store.begin(None, &request, None, 100).await.unwrap());
+
+        let first = started(store.begin(None, &request, key("a"), 100).await.unwrap());
+        let repeat = replayed(store.begin(None, &request, key("a"), 101).await.unwrap());
+        assert_eq!(repeat.id, first);
+        assert_eq!(repeat.status, ExecutionStatus::Pending);
+        assert!(matches!(
+            store.begin(None, &other, key("a"), 101).await.unwrap(),
+            Begin::Conflict
+        ));
+        // Keys are scoped per client
+        started(
+            store
+                .begin(Some("desk"), &request, key("a"), 101)
+                .await
+                .unwrap(),
+        );
+
+        // Failing before submission frees the key for a retry
+        store.fail(&first, 400, "no route", 102).await.unwrap();
+        let retry = started(store.begin(None, &request, key("a"), 103).await.unwrap());
+        assert_ne!(retry, first);
+
+        // Failing after submission is replayed, since the loan may have landed
+        store
+            .set_status(&retry, ExecutionStatus::Submitted, 104)
+            .await
+            .unwrap();
+        store.fail(&retry, 500, "timeout", 105).await.unwrap();
+        let failed = replayed(store.begin(None, &request, key("a"), 106).await.unwrap());
+        assert_eq!(failed.status, ExecutionStatus::Failed);
+        assert_eq!(failed.http_status, Some(500));
+        assert_eq!(failed.error.as_deref(), Some("timeout"));
+
+        let response = FlashLoanResponse {
+            transaction_digest: "digest".to_string(),
+            protocol_used: Protocol::Bucket,
+            protocol_fee: 1,
+            service_fee: 4,
+            total_fee: 5,
+        };
+        let confirmed = started(store.begin(None, &request, key("b"), 100).await.unwrap());
+        store.confirm(&confirmed, &response, 101).await.unwrap();
+        let stored = store.get(&confirmed).await.unwrap().unwrap();
+        assert_eq!(stored.status, ExecutionStatus::Confirmed);
+        assert_eq!(
+            stored.response.map(|response| response.transaction_digest),
+            Some("digest".to_string())
+        );
+        assert!(store.get("missing").await.unwrap().is_none());
+
+        // Keys expire after the window
+        replayed(store.begin(None, &request, key("b"), 159).await.unwrap());
+        started(store.begin(None, &other, key("b"), 160).await.unwrap());
+
+        // Pruning drops finished executions and their keys, never running ones
+        let running = started(store.begin(None, &request, key("c"), 100).await.unwrap());
+        assert_eq!(store.prune(150).await.unwrap(), 3);
+        assert!(store.get(&confirmed).await.unwrap().is_none());
+        assert_eq!(
+            store.get(&running).await.unwrap().unwrap().status,
+            ExecutionStatus::Pending
+        );
+        started(store.begin(None, &other, key("a"), 151).await.unwrap());
+        replayed(store.begin(None, &request, key("c"), 151).await.unwrap());
+
+        let mut headers = axum::http::HeaderMap::new();
+        assert_eq!(idempotency_key(&headers).unwrap(), None);
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "retry-1".parse().unwrap());
+        assert_eq!(idempotency_key(&headers).unwrap(), Some("retry-1"));
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "".parse().unwrap());
+        assert!(idempotency_key(&headers).is_err());
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "k".repeat(256).parse().unwrap());
+        assert!(idempotency_key(&headers).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execution_history_pages() {
+        let store = ExecutionStore::open(&ExecutionStoreConfig {
+            database_url: "sqlite::memory:".to_string(),
+            ..ExecutionStoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let request = FlashLoanRequest {
+            asset: "SUI".to_string(),
+            amount: 1_000,
+            route_mode: RouteMode::BestCost,
+            explicit_protocol: None,
+            user_operation: "arbitrage".to_string(),
+            callback_recipient: None,
+            callback_payload: None,
+        };
+        let mut ids = Vec::new();
+        // Two loans share a timestamp, so pages must break ties by id
+        for now in [100, 101, 101, 102] {
+            match store
+                .begin(Some("desk"), &request, None, now)
+                .await
+                .unwrap()
+            {
+                Begin::Started(id) => ids.push((now, id)),
+                other => panic!("expected a new execution, got {other:?}"),
+            }
+        }
+        store
+            .begin(Some("other"), &request, None, 103)
+            .await
+            .unwrap();
+        store.fail(&ids[0].1, 400, "no route", 104).await.unwrap();
+        ids.sort_by(|a, b| b.cmp(a));
+
+        let mut seen = Vec::new();
+        let mut before = None;
+        loop {
+            let page = store
+                .history(
+                    Some("desk"),
+                    None,
+                    before
+                        .as_ref()
+                        .map(|(t, id): &(u64, String)| (*t, id.as_str())),
+                    3,
+                )
+                .await
+                .unwrap();
+            let Some(last) = page.last() else { break };
+            before = Some((last.created_at, last.id.clone()));
+            seen.extend(page.into_iter().map(|e| (e.created_at, e.id)));
+        }
+        assert_eq!(seen, ids);
+
+        let failed = store
+            .history(Some("desk"), Some(ExecutionStatus::Failed), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].error.as_deref(), Some("no route"));
+        assert!(
+            store
+                .history(None, None, None, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let view = suiflash_client::v1::Execution::from(failed[0].clone());
+        let cursor = v1::history_cursor(&view);
+        assert_eq!(
+            v1::parse_history_cursor(&cursor).unwrap(),
+            (100, failed[0].id.as_str())
+        );
+        assert!(v1::parse_history_cursor("latest").is_err());
+        assert!(v1::parse_history_cursor("soon.id").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_loans_and_reports_unfinished() {
+        let shutdown = Shutdown::default();
+        let running = shutdown.track_loan().unwrap();
+        assert_eq!(shutdown.loans_in_flight(), 1);
+
+        let requested = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.requested().await }
+        });
+        shutdown.begin();
+        requested.await.unwrap();
+        // New loans are refused while the running one keeps its slot
+        assert!(shutdown.is_draining());
+        assert!(shutdown.track_loan().is_none());
+        assert_eq!(shutdown.loans_in_flight(), 1);
+        drop(running);
+        assert_eq!(shutdown.loans_in_flight(), 0);
+
+        let store = ExecutionStore::open(&ExecutionStoreConfig {
+            database_url: "sqlite::memory:".to_string(),
+            ..ExecutionStoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let request = FlashLoanRequest {
+            asset: "SUI".to_string(),
+            amount: 1_000,
+            route_mode: RouteMode::BestCost,
+            explicit_protocol: None,
+            user_operation: "arbitrage".to_string(),
+            callback_recipient: None,
+            callback_payload: None,
+        };
+        let mut ids = Vec::new();
+        for now in [100, 101, 102] {
+            match store.begin(None, &request, None, now).await.unwrap() {
+                Begin::Started(id) => ids.push(id),
+                other => panic!("expected a new execution, got {other:?}"),
+            }
+        }
+        store.fail(&ids[0], 400, "no route", 103).await.unwrap();
+        store
+            .set_status(&ids[2], ExecutionStatus::Submitted, 103)
+            .await
+            .unwrap();
+        let unfinished: Vec<_> = store
+            .unfinished()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|execution| (execution.id, execution.status))
+            .collect();
+        assert_eq!(
+            unfinished,
+            [
+                (ids[1].clone(), ExecutionStatus::Pending),
+                (ids[2].clone(), ExecutionStatus::Submitted)
+            ]
+        );
+        store.close().await;
+        assert!(store.get(&ids[1]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_readiness_checks() {
+        let data = |protocol, last_updated| crate::config::ProtocolData {
+            protocol,
+            asset: Asset::SUI,
+            fee_bps: 8,
+            available_liquidity: 1_000,
+            last_updated,
+        };
+        let summary = |checks: &[ReadinessCheck]| -> Vec<(String, CheckStatus)> {
+            checks
+                .iter()
+                .map(|check| (check.component.clone(), check.status))
+                .collect()
+        };
+
+        // One stale protocol is a warning while another is still fresh
+        let fresh = data(Protocol::Navi, 1_000);
+        let stale = data(Protocol::Bucket, 600);
+        let checks = readiness::freshness_checks([&fresh, &stale], 1_100, 300);
+        assert_eq!(
+            summary(&checks),
+            [
+                ("collector".to_string(), CheckStatus::Pass),
+                ("protocol:navi:SUI".to_string(), CheckStatus::Pass),
+                ("protocol:bucket:SUI".to_string(), CheckStatus::Warn),
+            ]
+        );
+        assert_eq!(checks[2].detail, "updated 500s ago");
+        assert!(ReadinessReport::new(checks).ready);
+
+        // Nothing routable once every protocol is stale, or before the first refresh
+        let checks = readiness::freshness_checks([&stale], 1_100, 300);
+        assert_eq!(checks[0].status, CheckStatus::Fail);
+        assert!(!ReadinessReport::new(checks).ready);
+        let checks = readiness::freshness_checks([], 1_100, 300);
+        assert_eq!(
+            summary(&checks),
+            [("collector".to_string(), CheckStatus::Fail)]
+        );
+
+        assert_eq!(
+            readiness::balance_check(999, 1_000).status,
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            readiness::balance_check(1_000, 1_000).status,
+            CheckStatus::Pass
+        );
+
+        let store = ExecutionStore::open(&ExecutionStoreConfig {
+            database_url: "sqlite::memory:".to_string(),
+            ..ExecutionStoreConfig::default()
+        })
+        .await
+        .unwrap();
+        store.ping().await.unwrap();
+        store.close().await;
+        assert!(store.ping().await.is_err());
+
+        // Dependency probes are reused until the TTL passes
+        let cache = readiness::ProbeCache::default();
+        let probes = std::sync::atomic::AtomicUsize::new(0);
+        let probe = || async {
+            probes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            vec![readiness::balance_check(1_000, 1_000)]
+        };
+        let ttl = std::time::Duration::from_secs(60);
+        assert_eq!(cache.get_or_probe(ttl, probe()).await.len(), 1);
+        assert_eq!(cache.get_or_probe(ttl, probe()).await.len(), 1);
+        assert_eq!(probes.load(std::sync::atomic::Ordering::SeqCst), 1);
+        cache.get_or_probe(std::time::Duration::ZERO, probe()).await;
+        assert_eq!(probes.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_deliveries() {
+        let store = ExecutionStore::open(&ExecutionStoreConfig {
+            database_url: "sqlite::memory:".to_string(),
+            ..ExecutionStoreConfig::default()
+        })
+        .await
+        .unwrap();
+        let webhooks = WebhookDispatcher::new(&store, SharedConfig::new(create_test_config()))
+            .await
+            .unwrap();
+
+        let all = webhooks
+            .register(
+                "desk",
+                CreateWebhook {
+                    url: "http://127.0.0.1:9/hook".to_string(),
+                    events: Vec::new(),
+                },
+            )
+            .await
+            .unwrap();
+        let confirmed_only = webhooks
+            .register(
+                "desk",
+                CreateWebhook {
+                    url: "http://127.0.0.1:9/confirmed".to_string(),
+                    events: vec![WebhookEvent::Confirmed],
+                },
+            )
+            .await
+            .unwrap();
+        assert!(
+            all.secret
+                .as_deref()
+                .is_some_and(|s| s.starts_with("whsec_"))
+        );
+        let listed = webhooks.list("desk").await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|webhook| webhook.secret.is_none()));
+        // Webhooks are scoped per client
+        assert!(webhooks.list("other").await.unwrap().is_empty());
+        assert!(
+            webhooks
+                .deliveries("other", &all.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let planned = WebhookPayload {
+            protocol: Some(Protocol::Navi),
+            ..WebhookPayload::new(WebhookEvent::Planned, "exec-1")
+        };
+        assert_eq!(webhooks.notify(Some("desk"), &planned).await.unwrap(), 1);
+        let confirmed = WebhookPayload {
+            event: WebhookEvent::Confirmed,
+            digest: Some("digest".to_string()),
+            ..planned.clone()
+        };
+        assert_eq!(webhooks.notify(Some("desk"), &confirmed).await.unwrap(), 2);
+        assert_eq!(webhooks.notify(None, &confirmed).await.unwrap(), 0);
+
+        let now = executions::unix_now();
+        webhooks.deliver_due(now).await.unwrap();
+        let deliveries = webhooks.deliveries("desk", &all.id).await.unwrap().unwrap();
+        assert_eq!(deliveries.len(), 2);
+        for delivery in &deliveries {
+            assert_eq!(delivery.status, DeliveryStatus::Pending);
+            assert_eq!(delivery.attempts.len(), 1);
+            // Loopback receivers are refused before anything is sent
+            assert!(
+                delivery.attempts[0]
+                    .error
+                    .as_deref()
+                    .is_some_and(|e| e.contains("not a public address"))
+            );
+            assert!(delivery.next_attempt_at.is_some_and(|at| at > now));
+        }
+        assert!(deliveries.iter().any(|delivery| {
+            delivery.event == WebhookEvent::Confirmed && delivery.payload["digest"] == "digest"
+        }));
+
+        assert!(webhooks.remove("desk", &confirmed_only.id).await.unwrap());
+        assert!(!webhooks.remove("desk", &confirmed_only.id).await.unwrap());
+        assert!(
+            webhooks
+                .deliveries("desk", &confirmed_only.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_targets_must_be_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fc00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        assert!(is_public_ip("203.0.113.7".parse().unwrap()));
+        assert!(is_public_ip("2001:db8::1".parse().unwrap()));
+
+        let none: &[String] = &[];
+        for url in [
+            "http://127.0.0.1:9/hook",
+            "http://[::1]/hook",
+            "http://0x7f.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://localhost:8080/hook",
+            "ftp://203.0.113.7/hook",
+        ] {
+            assert!(check_webhook_target(url, none).await.is_err(), "{url}");
+        }
+        assert!(
+            check_webhook_target("https://203.0.113.7/hook", none)
+                .await
+                .is_ok()
+        );
+        let allowed = ["localhost".to_string()];
+        assert!(
+            check_webhook_target("http://localhost:8080/hook", &allowed)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_webhook_signature_and_backoff() {
+        // HMAC-SHA256 of "1700000000.{}" keyed with "secret"
+        let signature = sign("secret", 1_700_000_000, "{}");
+        assert!(signature.starts_with("t=1700000000,v1="));
+        assert_eq!(signature.len(), "t=1700000000,v1=".len() + 64);
+        assert_eq!(signature, sign("secret", 1_700_000_000, "{}"));
+        assert_ne!(signature, sign("other", 1_700_000_000, "{}"));
+        assert_ne!(signature, sign("secret", 1_700_000_001, "{}"));
+
+        let config = WebhookConfig {
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 5_000,
+            ..WebhookConfig::default()
+        };
+        let delays: Vec<u64> = (1..=5)
+            .map(|attempt| retry_delay(attempt, &config).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, [1_000, 2_000, 4_000, 5_000, 5_000]);
+        assert_eq!(retry_delay(64, &config).as_millis(), 5_000);
+    }
+
     #[tokio::test]
     async fn test_route_mode_enum() {
         // Test RouteMode enum
@@ -0,0 +1,627 @@
+use axum::{
+    Extension,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+use suiflash_client::v1::{
+    BatchItem, BatchRequest, BatchResponse, ErrorBody, Execution, ExecutionStatus,
+    FlashLoanReceipt, FlashLoanRequest, GasEstimate, HistoryPage, HistoryQuery, Protocol,
+    ProtocolInfo, Protocols, Quote, QuoteRequest, RegistryEntry, RouteMode, Status, Strategy,
+};
+use tracing::error;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    AppState, LoanReply,
+    auth::ApiClient,
+    config::{self, FlashLoanResponse, ProtocolData, SharedConfig, StatusResponse, StrategyKind},
+    executions::{self, IDEMPOTENT_REPLAYED_HEADER},
+    gas_policy::GasQuote,
+    rate_limit::{ClientIp, RateLimiter},
+    shutdown::Shutdown,
+};
+
+/// Executions per `GET /v1/history` page when `limit` is unset
+pub const DEFAULT_HISTORY_LIMIT: u32 = 50;
+
+/// Largest accepted `limit` of `GET /v1/history`
+pub const MAX_HISTORY_LIMIT: u32 = 200;
+
+/// Every `/v1` route, relative to the `/v1` prefix
+///
+/// Wire types come from `suiflash_client::v1`, so the server and the Rust client share one
+/// definition. Handlers convert to and from the internal types, so changing an internal type
+/// never changes what `/v1` serves.
+pub fn routes(
+    config: &SharedConfig,
+    limiter: &RateLimiter,
+    shutdown: &Shutdown,
+) -> OpenApiRouter<AppState> {
+    let loan_routes = crate::with_loan_limits(
+        OpenApiRouter::new()
+            .routes(routes!(handle_flash_loan))
+            .routes(routes!(handle_flash_loan_batch)),
+        config,
+        limiter,
+        shutdown,
+    );
+    let lookup_routes = crate::with_loans_auth(
+        OpenApiRouter::new()
+            .routes(routes!(handle_quote))
+            .routes(routes!(handle_execution))
+            .routes(routes!(handle_history)),
+        config,
+        limiter,
+    );
+    OpenApiRouter::new()
+        .routes(routes!(handle_protocols))
+        .routes(routes!(handle_status))
+        .merge(loan_routes)
+        .merge(lookup_routes)
+        .merge(crate::event_routes(config, limiter))
+}
+
+impl From<config::Protocol> for Protocol {
+    fn from(protocol: config::Protocol) -> Self {
+        match protocol {
+            config::Protocol::Navi => Self::Navi,
+            config::Protocol::Bucket => Self::Bucket,
+            config::Protocol::Scallop => Self::Scallop,
+            config::Protocol::DeepBook => Self::DeepBook,
+            config::Protocol::Suilend => Self::Suilend,
+            config::Protocol::Registered(id) => Self::Registered(id),
+        }
+    }
+}
+
+impl From<Protocol> for config::Protocol {
+    fn from(protocol: Protocol) -> Self {
+        match protocol {
+            Protocol::Navi => Self::Navi,
+            Protocol::Bucket => Self::Bucket,
+            Protocol::Scallop => Self::Scallop,
+            Protocol::DeepBook => Self::DeepBook,
+            Protocol::Suilend => Self::Suilend,
+            Protocol::Registered(id) => Self::Registered(id),
+        }
+    }
+}
+
+impl From<config::RouteMode> for RouteMode {
+    fn from(mode: config::RouteMode) -> Self {
+        match mode {
+            config::RouteMode::Explicit => Self::Explicit,
+            config::RouteMode::BestCost => Self::BestCost,
+            config::RouteMode::BestLiquidity => Self::BestLiquidity,
+        }
+    }
+}
+
+impl From<RouteMode> for config::RouteMode {
+    fn from(mode: RouteMode) -> Self {
+        match mode {
+            RouteMode::Explicit => Self::Explicit,
+            RouteMode::BestCost => Self::BestCost,
+            RouteMode::BestLiquidity => Self::BestLiquidity,
+        }
+    }
+}
+
+impl From<config::FlashLoanRequest> for FlashLoanRequest {
+    fn from(request: config::FlashLoanRequest) -> Self {
+        Self {
+            asset: request.asset,
+            amount: request.amount,
+            route_mode: request.route_mode.into(),
+            explicit_protocol: request.explicit_protocol.map(Into::into),
+            user_operation: request.user_operation,
+            callback_recipient: request.callback_recipient,
+            callback_payload: request.callback_payload,
+        }
+    }
+}
+
+impl From<FlashLoanRequest> for config::FlashLoanRequest {
+    fn from(request: FlashLoanRequest) -> Self {
+        Self {
+            asset: request.asset,
+            amount: request.amount,
+            route_mode: request.route_mode.into(),
+            explicit_protocol: request.explicit_protocol.map(Into::into),
+            user_operation: request.user_operation,
+            callback_recipient: request.callback_recipient,
+            callback_payload: request.callback_payload,
+        }
+    }
+}
+
+impl From<executions::ExecutionStatus> for ExecutionStatus {
+    fn from(status: executions::ExecutionStatus) -> Self {
+        match status {
+            executions::ExecutionStatus::Pending => Self::Pending,
+            executions::ExecutionStatus::Planned => Self::Planned,
+            executions::ExecutionStatus::Submitted => Self::Submitted,
+            executions::ExecutionStatus::Confirmed => Self::Confirmed,
+            executions::ExecutionStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<ExecutionStatus> for executions::ExecutionStatus {
+    fn from(status: ExecutionStatus) -> Self {
+        match status {
+            ExecutionStatus::Pending => Self::Pending,
+            ExecutionStatus::Planned => Self::Planned,
+            ExecutionStatus::Submitted => Self::Submitted,
+            ExecutionStatus::Confirmed => Self::Confirmed,
+            ExecutionStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<executions::Execution> for Execution {
+    fn from(execution: executions::Execution) -> Self {
+        let receipt = execution
+            .response
+            .map(|response| receipt(execution.id.clone(), &execution.request, response));
+        Self {
+            id: execution.id,
+            status: execution.status.into(),
+            request: execution.request.into(),
+            receipt,
+            error: execution.error,
+            created_at: execution.created_at,
+            updated_at: execution.updated_at,
+        }
+    }
+}
+
+impl From<ProtocolData> for ProtocolInfo {
+    fn from(data: ProtocolData) -> Self {
+        Self {
+            protocol: data.protocol.into(),
+            asset: data.asset.to_string(),
+            fee_bps: data.fee_bps,
+            available_liquidity: data.available_liquidity,
+            last_updated: data.last_updated,
+        }
+    }
+}
+
+impl From<GasQuote> for GasEstimate {
+    fn from(quote: GasQuote) -> Self {
+        Self {
+            reference_gas_price: quote.reference_gas_price,
+            gas_price: quote.gas_price,
+            computation_cost: quote.computation_cost,
+            storage_cost: quote.storage_cost,
+            gas_budget: quote.gas_budget,
+        }
+    }
+}
+
+impl From<config::RegistryEntry> for RegistryEntry {
+    fn from(entry: config::RegistryEntry) -> Self {
+        Self {
+            protocol_id: entry.protocol_id,
+            adapter_package: entry.adapter_package,
+        }
+    }
+}
+
+impl From<StatusResponse> for Status {
+    fn from(status: StatusResponse) -> Self {
+        Self {
+            strategy: match status.strategy {
+                StrategyKind::Cheapest => Strategy::Cheapest,
+                StrategyKind::HighestLiquidity => Strategy::HighestLiquidity,
+            },
+            service_fee_bps: status.service_fee_bps,
+            protocol_count: status.protocol_count,
+            last_updated_any: status.last_updated_any,
+        }
+    }
+}
+
+/// Receipt of execution `id`, which borrowed `request` and was answered with `response`
+pub fn receipt(
+    id: String,
+    request: &config::FlashLoanRequest,
+    response: FlashLoanResponse,
+) -> FlashLoanReceipt {
+    FlashLoanReceipt {
+        execution_id: id,
+        transaction_digest: response.transaction_digest,
+        protocol: response.protocol_used.into(),
+        asset: request.asset.clone(),
+        amount: request.amount,
+        protocol_fee: response.protocol_fee,
+        service_fee: response.service_fee,
+        total_fee: response.total_fee,
+    }
+}
+
+/// `next` cursor of a history page ending with `execution`
+pub fn history_cursor(execution: &Execution) -> String {
+    format!("{}.{}", execution.created_at, execution.id)
+}
+
+/// `(created_at, id)` encoded in a history cursor
+///
+/// # Errors
+///
+/// Returns an error if `cursor` was not made by [`history_cursor`]
+pub fn parse_history_cursor(cursor: &str) -> eyre::Result<(u64, &str)> {
+    let (created_at, id) = cursor
+        .split_once('.')
+        .ok_or_else(|| eyre::eyre!("Invalid history cursor '{}'", cursor))?;
+    let created_at = created_at
+        .parse()
+        .map_err(|_| eyre::eyre!("Invalid history cursor '{}'", cursor))?;
+    Ok((created_at, id))
+}
+
+/// A refused loan; rate limits answer like the rate limit middleware, with `Retry-After`
+fn failure_response(failure: crate::LoanFailure) -> Response {
+    match failure.retry_after {
+        Some(limited) => crate::rate_limit::too_many_requests(limited),
+        None => error_response(failure.status, failure.reason),
+    }
+}
+
+fn error_response(status: StatusCode, message: impl ToString) -> Response {
+    (
+        status,
+        Json(ErrorBody {
+            error: message.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+/// Answer a loan from the execution its `Idempotency-Key` started
+///
+/// Like the first answer once the loan finished; `202 Accepted` with the execution while it
+/// is still running.
+fn replay(execution: executions::Execution) -> Response {
+    let mut response = match (execution.status, execution.response) {
+        (executions::ExecutionStatus::Confirmed, Some(body)) => {
+            Json(receipt(execution.id, &execution.request, body)).into_response()
+        }
+        (executions::ExecutionStatus::Failed, _) => error_response(
+            execution
+                .http_status
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            execution.error.unwrap_or_default(),
+        ),
+        (_, response) => {
+            let location = format!("/v1/executions/{}", execution.id);
+            let execution = Execution::from(executions::Execution {
+                response,
+                ..execution
+            });
+            let mut response = (StatusCode::ACCEPTED, Json(execution)).into_response();
+            if let Ok(location) = HeaderValue::from_str(&location) {
+                response.headers_mut().insert(header::LOCATION, location);
+            }
+            response
+        }
+    };
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Price a loan without executing it
+///
+/// # Errors
+///
+/// Answers `403 Forbidden` if the caller's key may not take the loan and `400 Bad Request`
+/// if no protocol can serve it or its dry run fails
+#[utoipa::path(
+    post,
+    path = "/quote",
+    description = "Routes the loan like `POST /v1/flashloan` would, without recording or reserving anything",
+    tag = "loans",
+    request_body = QuoteRequest,
+    responses(
+        (status = 200, description = "Protocol and fees the loan would get now", body = Quote),
+        (status = 400, description = "No protocol can serve the loan, or its dry run failed", body = ErrorBody),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "The API key may not take this loan", body = ErrorBody)
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_quote(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    Json(quote): Json<QuoteRequest>,
+) -> Response {
+    let request = config::FlashLoanRequest {
+        asset: quote.asset,
+        amount: quote.amount,
+        route_mode: quote.route_mode.into(),
+        explicit_protocol: quote.explicit_protocol.map(Into::into),
+        user_operation: String::new(),
+        callback_recipient: None,
+        callback_payload: None,
+    };
+    let client = client.map(|Extension(client)| client);
+    if let Some(client) = &client
+        && let Err(e) = client.check_request(&request)
+    {
+        return error_response(StatusCode::FORBIDDEN, e);
+    }
+    let plan = match crate::plan_loan(&state, client.as_ref(), &request).await {
+        Ok(plan) => plan,
+        Err(failure) => return failure_response(failure),
+    };
+    let gas = match state.executor.quote_gas(&plan).await {
+        Ok(gas) => gas,
+        Err(e) => {
+            error!("Failed to quote gas: {}", e);
+            return error_response(StatusCode::BAD_REQUEST, e);
+        }
+    };
+    let protocol_fee = plan.total_cost - plan.amount;
+    let service_fee = state.config.current().service_fee_bps.fee_on(plan.amount);
+    let total_fee = protocol_fee + service_fee;
+    Json(Quote {
+        protocol: plan.protocol.into(),
+        asset: request.asset,
+        amount: plan.amount,
+        protocol_fee,
+        service_fee,
+        total_fee,
+        total_repayment: plan.amount + total_fee,
+        gas: gas.into(),
+    })
+    .into_response()
+}
+
+/// Execute a flash loan
+///
+/// # Errors
+///
+/// Answers with an [`ErrorBody`] when the loan is refused or fails
+#[utoipa::path(
+    post,
+    path = "/flashloan",
+    description = "Send an Idempotency-Key to make retries safe: a repeat answers with the first attempt's result, or 202 with the execution while it is still running",
+    tag = "loans",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Up to 255 printable ASCII characters, unique per loan")
+    ),
+    request_body = FlashLoanRequest,
+    responses(
+        (status = 200, description = "Loan executed", body = FlashLoanReceipt),
+        (status = 202, description = "Replay of a loan that is still running", body = Execution),
+        (status = 400, description = "Invalid request, malformed Idempotency-Key or no protocol can serve it", body = ErrorBody),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "The API key may not take this loan", body = ErrorBody),
+        (status = 409, description = "Idempotency-Key already used with a different request", body = ErrorBody),
+        (status = 429, description = "Rate limit or daily loan limit reached"),
+        (status = 500, description = "Execution failed", body = ErrorBody),
+        (status = 503, description = "The server is shutting down; retry after Retry-After seconds")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_flash_loan(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    headers: HeaderMap,
+    Json(request): Json<FlashLoanRequest>,
+) -> Response {
+    let request = config::FlashLoanRequest::from(request);
+    let client = client.map(|Extension(client)| client);
+    match crate::run_flash_loan(&state, client.as_ref(), &headers, &request).await {
+        LoanReply::Executed { id, response } => {
+            Json(receipt(id, &request, response)).into_response()
+        }
+        LoanReply::Replayed(execution) => replay(*execution),
+        LoanReply::Refused(failure) => failure_response(failure),
+    }
+}
+
+/// Plan several loans together, then execute each on its own
+///
+/// # Errors
+///
+/// Answers `400 Bad Request` if the batch is empty, too large or has a malformed
+/// Idempotency-Key
+#[utoipa::path(
+    post,
+    path = "/flashloan/batch",
+    description = "Loans are planned against one protocol snapshot, so together they never exceed a protocol's liquidity. With an Idempotency-Key, loan i is keyed `<key>/<i>`",
+    tag = "loans",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Applies to the whole batch")
+    ),
+    request_body = BatchRequest,
+    responses(
+        (status = 200, description = "Result of each loan, in request order", body = BatchResponse),
+        (status = 400, description = "Malformed Idempotency-Key, or no loans or too many", body = ErrorBody),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 429, description = "Rate limit reached"),
+        (status = 503, description = "The server is shutting down; retry after Retry-After seconds")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_flash_loan_batch(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    Json(batch): Json<BatchRequest>,
+) -> Response {
+    let loans: Vec<config::FlashLoanRequest> = batch.loans.into_iter().map(Into::into).collect();
+    let client = client.map(|Extension(client)| client);
+    let ip = ip.map(|Extension(ip)| ip);
+    let batch =
+        match crate::run_flash_loan_batch(&state, client.as_ref(), ip, &headers, &loans).await {
+            Ok(batch) => batch,
+            Err(failure) => return failure_response(failure),
+        };
+    let results = batch
+        .results
+        .into_iter()
+        .map(|item| BatchItem {
+            index: item.index,
+            status: item.status,
+            receipt: item
+                .execution_id
+                .clone()
+                .zip(item.response)
+                .map(|(id, response)| receipt(id, &loans[item.index], response)),
+            execution_id: item.execution_id,
+            error: item.error,
+            replayed: item.replayed,
+        })
+        .collect();
+    Json(BatchResponse {
+        succeeded: batch.succeeded,
+        failed: batch.failed,
+        results,
+    })
+    .into_response()
+}
+
+/// Look up one of the caller's loans
+///
+/// # Errors
+///
+/// Answers `404 Not Found` if there is no such execution or another client started it
+#[utoipa::path(
+    get,
+    path = "/executions/{id}",
+    description = "Status of a loan, including one still running after an Idempotency-Key replay",
+    tag = "loans",
+    params(("id" = String, Path, description = "Execution id")),
+    responses(
+        (status = 200, description = "The execution", body = Execution),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 404, description = "No execution with this id for the caller", body = ErrorBody)
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_execution(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    Path(id): Path<String>,
+) -> Response {
+    let client = client.map(|Extension(client)| client);
+    match crate::find_execution(&state, client.as_ref(), &id).await {
+        Ok(execution) => Json(Execution::from(execution)).into_response(),
+        Err(StatusCode::NOT_FOUND) => {
+            error_response(StatusCode::NOT_FOUND, format!("No execution '{id}'"))
+        }
+        Err(status) => error_response(status, "Failed to read the execution"),
+    }
+}
+
+/// Page through the caller's loans, newest first
+///
+/// # Errors
+///
+/// Answers `400 Bad Request` if `before` is not a cursor from an earlier page or `limit` is
+/// out of range
+#[utoipa::path(
+    get,
+    path = "/history",
+    description = "Pass `next` from a page as `before` to get the following one",
+    tag = "loans",
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "One page of executions", body = HistoryPage),
+        (status = 400, description = "Invalid cursor or limit", body = ErrorBody),
+        (status = 401, description = "Missing or unknown API key")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_history(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    if !(1..=MAX_HISTORY_LIMIT).contains(&limit) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("limit must be between 1 and {MAX_HISTORY_LIMIT}"),
+        );
+    }
+    let before = match query
+        .before
+        .as_deref()
+        .map(parse_history_cursor)
+        .transpose()
+    {
+        Ok(before) => before,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+    let client_name = client
+        .as_ref()
+        .map(|Extension(ApiClient(key))| key.name.as_str());
+    // One extra row tells whether another page follows
+    let mut executions = match state
+        .executions
+        .history(client_name, query.status.map(Into::into), before, limit + 1)
+        .await
+    {
+        Ok(executions) => executions,
+        Err(e) => {
+            error!("Failed to read execution history: {}", e);
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to read the history",
+            );
+        }
+    };
+    let more = executions.len() > limit as usize;
+    executions.truncate(limit as usize);
+    let executions: Vec<Execution> = executions.into_iter().map(Into::into).collect();
+    let next = executions.last().filter(|_| more).map(history_cursor);
+    Json(HistoryPage { executions, next }).into_response()
+}
+
+/// Latest fee and liquidity of every protocol
+#[utoipa::path(
+    get,
+    path = "/protocols",
+    description = "Registry entries without a configured adapter are listed under `unknown_protocols`",
+    tag = "protocols",
+    responses((status = 200, description = "Latest collected data per protocol", body = Protocols))
+)]
+pub async fn handle_protocols(State(state): State<AppState>) -> Json<Protocols> {
+    let collector = state.strategy.collector();
+    let mut data: Vec<_> = collector
+        .get_all_protocol_data()
+        .await
+        .into_values()
+        .collect();
+    data.sort_by_key(|data| (data.protocol, data.asset));
+    Json(Protocols {
+        protocols: data.into_iter().map(Into::into).collect(),
+        unknown_protocols: collector
+            .unknown_protocols()
+            .await
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    })
+}
+
+/// Active strategy, service fee and data freshness
+#[utoipa::path(
+    get,
+    path = "/status",
+    description = "Active strategy, service fee and data freshness",
+    tag = "operations",
+    responses((status = 200, description = "Aggregator status", body = Status))
+)]
+pub async fn handle_status(State(state): State<AppState>) -> Json<Status> {
+    Json(state.strategy.status().await.into())
+}
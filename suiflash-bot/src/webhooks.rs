@@ -0,0 +1,911 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    Extension,
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use eyre::Result;
+use hmac::{Hmac, Mac};
+use reqwest::{
+    Client,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::CONTENT_TYPE,
+    redirect::Policy,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::{Row, sqlite::SqlitePool};
+use tokio::{net::lookup_host, sync::Notify, task::JoinSet, time::sleep};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::{
+    AppState,
+    auth::ApiClient,
+    config::{Protocol, SharedConfig, WebhookConfig, check_http_url},
+    executions::{ExecutionStore, to_sql, unix_now},
+    executors::FlashLoanEvent,
+};
+
+/// `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`
+pub const SIGNATURE_HEADER: &str = "x-suiflash-signature";
+
+/// Event type of the delivery, such as `loan.confirmed`
+pub const EVENT_HEADER: &str = "x-suiflash-event";
+
+/// Delivery id, the same on every retry so receivers can drop duplicates
+pub const DELIVERY_HEADER: &str = "x-suiflash-delivery";
+
+/// How often retries are looked for when nothing new is queued
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Deliveries attempted per round
+const DELIVERY_BATCH: i64 = 32;
+
+/// Deliveries returned by the inspection endpoint
+const MAX_LISTED_DELIVERIES: i64 = 100;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS webhooks (
+    id TEXT PRIMARY KEY,
+    client TEXT NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    webhook_id TEXT NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    execution_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    next_attempt_at INTEGER,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS webhook_deliveries_due ON webhook_deliveries (status, next_attempt_at);
+CREATE TABLE IF NOT EXISTS webhook_attempts (
+    delivery_id TEXT NOT NULL REFERENCES webhook_deliveries (id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    attempted_at INTEGER NOT NULL,
+    status_code INTEGER,
+    error TEXT,
+    PRIMARY KEY (delivery_id, attempt)
+);
+";
+
+/// Loan lifecycle event a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEvent {
+    #[serde(rename = "loan.planned")]
+    Planned,
+    #[serde(rename = "loan.submitted")]
+    Submitted,
+    #[serde(rename = "loan.confirmed")]
+    Confirmed,
+    #[serde(rename = "loan.failed")]
+    Failed,
+}
+
+impl WebhookEvent {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Planned => "loan.planned",
+            Self::Submitted => "loan.submitted",
+            Self::Confirmed => "loan.confirmed",
+            Self::Failed => "loan.failed",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "loan.planned" => Ok(Self::Planned),
+            "loan.submitted" => Ok(Self::Submitted),
+            "loan.confirmed" => Ok(Self::Confirmed),
+            "loan.failed" => Ok(Self::Failed),
+            other => Err(eyre::eyre!("Unknown webhook event '{}'", other)),
+        }
+    }
+}
+
+/// Body POSTed to a webhook
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WebhookPayload {
+    #[serde(rename = "type")]
+    pub event: WebhookEvent,
+    pub execution_id: String,
+    /// Unix timestamp in seconds
+    pub created_at: u64,
+    /// Protocol the loan was routed to, once planned
+    pub protocol: Option<Protocol>,
+    /// Transaction digest, once confirmed
+    pub digest: Option<String>,
+    /// The router's event for the loan, once confirmed
+    pub flash_loan_event: Option<FlashLoanEvent>,
+    /// Why the loan failed
+    pub error: Option<String>,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEvent, execution_id: &str) -> Self {
+        Self {
+            event,
+            execution_id: execution_id.to_string(),
+            created_at: unix_now(),
+            protocol: None,
+            digest: None,
+            flash_loan_event: None,
+            error: None,
+        }
+    }
+}
+
+/// Body of `POST /webhooks`
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateWebhook {
+    /// `http` or `https` URL receiving the callbacks
+    pub url: String,
+    /// Events to deliver; empty or omitted subscribes to all
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+/// A registered webhook
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Empty means every event
+    pub events: Vec<WebhookEvent>,
+    /// HMAC key for [`SIGNATURE_HEADER`]; only returned when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: u64,
+}
+
+/// Where a delivery stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Waiting for its next attempt
+    Pending,
+    /// The receiver answered 2xx
+    Delivered,
+    /// Every attempt failed
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "delivered" => Ok(Self::Delivered),
+            "failed" => Ok(Self::Failed),
+            other => Err(eyre::eyre!("Unknown delivery status '{}'", other)),
+        }
+    }
+}
+
+/// One try at delivering a payload
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DeliveryAttempt {
+    pub attempt: u32,
+    pub attempted_at: u64,
+    /// Status the receiver answered with, if it answered
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// A payload queued for one webhook and its attempts so far
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub execution_id: String,
+    pub event: WebhookEvent,
+    pub status: DeliveryStatus,
+    #[schema(value_type = Object)]
+    pub payload: Value,
+    /// When the next attempt is due, while pending
+    pub next_attempt_at: Option<u64>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub attempts: Vec<DeliveryAttempt>,
+}
+
+/// A due delivery with what is needed to send it
+#[derive(Debug, Clone)]
+struct DueDelivery {
+    id: String,
+    url: String,
+    secret: String,
+    event: String,
+    payload: String,
+    attempts: u32,
+}
+
+/// Signature header value for `body` sent at `timestamp`
+pub fn sign(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!(
+        "t={},v1={}",
+        timestamp,
+        hex::encode(mac.finalize().into_bytes())
+    )
+}
+
+/// Wait before retrying after `attempts` failed attempts
+pub fn retry_delay(attempts: u32, config: &WebhookConfig) -> Duration {
+    let factor = 2_u64.saturating_pow(attempts.saturating_sub(1));
+    Duration::from_millis(
+        config
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(config.max_backoff_ms),
+    )
+}
+
+/// Whether webhooks may reach `ip`: not loopback, private, link-local, unspecified or
+/// multicast
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast())
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ip(IpAddr::V4(mapped)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || ip.is_unspecified()
+                    || ip.is_multicast())
+            }
+        },
+    }
+}
+
+fn is_allowed_private_host(host: &str, allowed: &[String]) -> bool {
+    allowed
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+}
+
+/// Addresses `host` resolves to, refused if any of them is not public
+async fn resolve_public(host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+    let addrs: Vec<SocketAddr> = lookup_host((host, port)).await?.collect();
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        eyre::bail!("{} resolves to non-public address {}", host, addr.ip());
+    }
+    if addrs.is_empty() {
+        eyre::bail!("{} does not resolve", host);
+    }
+    Ok(addrs)
+}
+
+/// Refuse webhook URLs that are not http(s) or reach a non-public address
+///
+/// Hosts in `allowed_private_hosts` skip the address check.
+///
+/// # Errors
+///
+/// Returns an error if the URL is invalid, its host is a non-public address or resolves
+/// to one, or it does not resolve
+pub async fn check_webhook_target(url: &str, allowed_private_hosts: &[String]) -> Result<()> {
+    check_http_url(url)?;
+    let parsed = reqwest::Url::parse(url)?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| eyre::eyre!("URL has no host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    if is_allowed_private_host(host, allowed_private_hosts) {
+        return Ok(());
+    }
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        if !is_public_ip(ip) {
+            eyre::bail!("{} is not a public address", ip);
+        }
+        return Ok(());
+    }
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    resolve_public(host, port).await.map(drop)
+}
+
+/// Resolver of the delivery client
+///
+/// Checks the addresses it connects to, so a host that passed [`check_webhook_target`]
+/// cannot rebind to an internal address before the request is sent.
+struct PublicResolver {
+    config: SharedConfig,
+}
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let allowed =
+            is_allowed_private_host(&host, &self.config.current().webhooks.allowed_private_hosts);
+        Box::pin(async move {
+            // reqwest replaces the port with the URL's
+            let addrs = if allowed {
+                lookup_host((host.as_str(), 0)).await?.collect()
+            } else {
+                resolve_public(&host, 0).await?
+            };
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Registers webhooks and delivers loan lifecycle events to them
+///
+/// Deliveries are queued in the execution store, so retries survive a restart.
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher {
+    pool: SqlitePool,
+    config: SharedConfig,
+    http: Client,
+    wake: Arc<Notify>,
+}
+
+impl WebhookDispatcher {
+    /// Create the webhook tables in `store` if missing
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tables cannot be created
+    pub async fn new(store: &ExecutionStore, config: SharedConfig) -> Result<Self> {
+        let pool = store.pool().clone();
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+        // Redirects are not followed, since their targets skip the address check
+        let http = Client::builder()
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(PublicResolver {
+                config: config.clone(),
+            }))
+            .build()?;
+        Ok(Self {
+            pool,
+            config,
+            http,
+            wake: Arc::new(Notify::new()),
+        })
+    }
+
+    /// Register a webhook for `client`, returning it with its signing secret
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written
+    pub async fn register(&self, client: &str, request: CreateWebhook) -> Result<Webhook> {
+        let webhook = Webhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: request.url,
+            events: request.events,
+            secret: Some(format!(
+                "whsec_{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            )),
+            created_at: unix_now(),
+        };
+        sqlx::query(
+            "INSERT INTO webhooks (id, client, url, secret, events, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&webhook.id)
+        .bind(client)
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(serde_json::to_string(&webhook.events)?)
+        .bind(to_sql(webhook.created_at))
+        .execute(&self.pool)
+        .await?;
+        info!("Registered webhook {} for {}", webhook.id, webhook.url);
+        Ok(webhook)
+    }
+
+    /// Webhooks `client` registered, without their secrets
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read
+    pub async fn list(&self, client: &str) -> Result<Vec<Webhook>> {
+        sqlx::query(
+            "SELECT id, url, events, created_at FROM webhooks WHERE client = ? ORDER BY created_at",
+        )
+        .bind(client)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            Ok(Webhook {
+                id: row.try_get("id")?,
+                url: row.try_get("url")?,
+                events: serde_json::from_str(row.try_get("events")?)?,
+                secret: None,
+                created_at: row.try_get("created_at")?,
+            })
+        })
+        .collect()
+    }
+
+    /// Remove `client`'s webhook `id` and its queued deliveries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written
+    pub async fn remove(&self, client: &str, id: &str) -> Result<bool> {
+        let removed = sqlx::query("DELETE FROM webhooks WHERE id = ? AND client = ?")
+            .bind(id)
+            .bind(client)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(removed > 0)
+    }
+
+    /// Deliveries still waiting for an attempt or a retry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read
+    pub async fn pending_deliveries(&self) -> Result<u64> {
+        let pending: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM webhook_deliveries WHERE status = ?")
+                .bind(DeliveryStatus::Pending.as_str())
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(pending.unsigned_abs())
+    }
+
+    /// Latest deliveries of `client`'s webhook `id` with their attempts, or `None` if it is
+    /// not theirs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read
+    pub async fn deliveries(&self, client: &str, id: &str) -> Result<Option<Vec<WebhookDelivery>>> {
+        let owned = sqlx::query("SELECT 1 FROM webhooks WHERE id = ? AND client = ?")
+            .bind(id)
+            .bind(client)
+            .fetch_optional(&self.pool)
+            .await?;
+        if owned.is_none() {
+            return Ok(None);
+        }
+
+        let rows = sqlx::query(
+            "SELECT * FROM webhook_deliveries WHERE webhook_id = ?
+             ORDER BY created_at DESC, rowid DESC LIMIT ?",
+        )
+        .bind(id)
+        .bind(MAX_LISTED_DELIVERIES)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut deliveries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id: String = row.try_get("id")?;
+            let attempts = sqlx::query(
+                "SELECT * FROM webhook_attempts WHERE delivery_id = ? ORDER BY attempt",
+            )
+            .bind(&id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|attempt| {
+                Ok(DeliveryAttempt {
+                    attempt: attempt.try_get("attempt")?,
+                    attempted_at: attempt.try_get("attempted_at")?,
+                    status_code: attempt.try_get("status_code")?,
+                    error: attempt.try_get("error")?,
+                })
+            })
+            .collect::<Result<_>>()?;
+            deliveries.push(WebhookDelivery {
+                id,
+                execution_id: row.try_get("execution_id")?,
+                event: row.try_get::<&str, _>("event")?.parse()?,
+                status: row.try_get::<&str, _>("status")?.parse()?,
+                payload: serde_json::from_str(row.try_get("payload")?)?,
+                next_attempt_at: row.try_get("next_attempt_at")?,
+                created_at: row.try_get("created_at")?,
+                updated_at: row.try_get("updated_at")?,
+                attempts,
+            });
+        }
+        Ok(Some(deliveries))
+    }
+
+    /// Queue `payload` for every webhook of `client` subscribed to its event
+    ///
+    /// Unauthenticated loans have no webhooks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written
+    pub async fn notify(&self, client: Option<&str>, payload: &WebhookPayload) -> Result<usize> {
+        let Some(client) = client else {
+            return Ok(0);
+        };
+        let body = serde_json::to_string(payload)?;
+        let now = to_sql(unix_now());
+        let mut tx = self.pool.begin().await?;
+        let webhooks = sqlx::query("SELECT id, events FROM webhooks WHERE client = ?")
+            .bind(client)
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut queued = 0;
+        for webhook in webhooks {
+            let events: Vec<WebhookEvent> = serde_json::from_str(webhook.try_get("events")?)?;
+            if !events.is_empty() && !events.contains(&payload.event) {
+                continue;
+            }
+            sqlx::query(
+                "INSERT INTO webhook_deliveries (id, webhook_id, execution_id, event, payload,
+                     status, attempts, next_attempt_at, created_at, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?, ?)",
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(webhook.try_get::<&str, _>("id")?)
+            .bind(&payload.execution_id)
+            .bind(payload.event.as_str())
+            .bind(&body)
+            .bind(DeliveryStatus::Pending.as_str())
+            .bind(now)
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            queued += 1;
+        }
+        tx.commit().await?;
+        if queued > 0 {
+            self.wake.notify_one();
+        }
+        Ok(queued)
+    }
+
+    /// Deliver queued payloads until the task is dropped
+    pub async fn run(&self) {
+        loop {
+            if let Err(e) = self.deliver_due(unix_now()).await {
+                warn!("Webhook delivery round failed: {}", e);
+            }
+            // New deliveries wake the loop; retries are picked up by polling
+            tokio::select! {
+                () = self.wake.notified() => {}
+                () = sleep(POLL_INTERVAL) => {}
+            }
+        }
+    }
+
+    /// Attempt every delivery due at `now`, concurrently
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the due deliveries cannot be read
+    pub async fn deliver_due(&self, now: u64) -> Result<()> {
+        let due = sqlx::query(
+            "SELECT d.id, d.event, d.payload, d.attempts, w.url, w.secret
+             FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id
+             WHERE d.status = ? AND d.next_attempt_at <= ?
+             ORDER BY d.next_attempt_at LIMIT ?",
+        )
+        .bind(DeliveryStatus::Pending.as_str())
+        .bind(to_sql(now))
+        .bind(DELIVERY_BATCH)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut attempts = JoinSet::new();
+        for row in due {
+            let delivery = DueDelivery {
+                id: row.try_get("id")?,
+                url: row.try_get("url")?,
+                secret: row.try_get("secret")?,
+                event: row.try_get("event")?,
+                payload: row.try_get("payload")?,
+                attempts: row.try_get("attempts")?,
+            };
+            let dispatcher = self.clone();
+            attempts.spawn(async move { dispatcher.attempt(delivery).await });
+        }
+        while let Some(result) = attempts.join_next().await {
+            match result {
+                Ok(Err(e)) => warn!("Failed to record webhook attempt: {}", e),
+                Err(e) => warn!("Webhook attempt panicked: {}", e),
+                Ok(Ok(())) => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn attempt(&self, delivery: DueDelivery) -> Result<()> {
+        let config = self.config.current().webhooks.clone();
+        let attempted_at = unix_now();
+        // Checked again on every attempt: the host may resolve elsewhere since registration.
+        // IP literals never reach the resolver, so this is their only check.
+        let result = match check_webhook_target(&delivery.url, &config.allowed_private_hosts).await
+        {
+            Ok(()) => self
+                .http
+                .post(&delivery.url)
+                .timeout(Duration::from_millis(config.timeout_ms))
+                .header(CONTENT_TYPE, "application/json")
+                .header(
+                    SIGNATURE_HEADER,
+                    sign(&delivery.secret, attempted_at, &delivery.payload),
+                )
+                .header(EVENT_HEADER, &delivery.event)
+                .header(DELIVERY_HEADER, &delivery.id)
+                .body(delivery.payload.clone())
+                .send()
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Refused target: {e}")),
+        };
+        let (status_code, error) = match result {
+            Ok(response) if response.status().is_success() => (Some(response.status()), None),
+            Ok(response) => (
+                Some(response.status()),
+                Some(format!("Receiver answered {}", response.status())),
+            ),
+            Err(e) => (None, Some(e)),
+        };
+
+        let attempt = delivery.attempts + 1;
+        let (status, next_attempt_at) = match &error {
+            None => (DeliveryStatus::Delivered, None),
+            Some(_) if attempt >= config.max_attempts => (DeliveryStatus::Failed, None),
+            Some(_) => {
+                let delay = retry_delay(attempt, &config).as_secs().max(1);
+                (DeliveryStatus::Pending, Some(attempted_at + delay))
+            }
+        };
+        match &error {
+            None => debug!("Delivered {} to {}", delivery.event, delivery.url),
+            Some(e) => warn!(
+                "Webhook attempt {} of {} to {} failed: {}",
+                attempt, delivery.event, delivery.url, e
+            ),
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO webhook_attempts (delivery_id, attempt, attempted_at, status_code, error)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&delivery.id)
+        .bind(attempt)
+        .bind(to_sql(attempted_at))
+        .bind(status_code.map(|status| status.as_u16()))
+        .bind(&error)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE webhook_deliveries SET status = ?, attempts = ?, next_attempt_at = ?,
+                 updated_at = ?
+             WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(attempt)
+        .bind(next_attempt_at.map(to_sql))
+        .bind(to_sql(unix_now()))
+        .bind(&delivery.id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Name of the calling client
+///
+/// Webhooks belong to an API key, so they are refused while `auth.disabled` is set.
+fn client_name(client: Option<&Extension<ApiClient>>) -> Result<&str, StatusCode> {
+    client
+        .map(|Extension(ApiClient(key))| key.name.as_str())
+        .ok_or(StatusCode::FORBIDDEN)
+}
+
+/// Register a webhook for the caller's loans
+///
+/// # Errors
+///
+/// Returns `StatusCode::BAD_REQUEST` if the URL is not http(s) or reaches a non-public
+/// address, `StatusCode::FORBIDDEN` without an API key and
+/// `StatusCode::INTERNAL_SERVER_ERROR` if the webhook cannot be stored
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    description = "The response carries the signing secret; it is not shown again",
+    tag = "webhooks",
+    request_body = CreateWebhook,
+    responses(
+        (status = 201, description = "Webhook registered", body = Webhook),
+        (status = 400, description = "The URL is not http(s) or reaches a loopback, private or link-local address"),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "Authentication is disabled; webhooks need an API key")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_create_webhook(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    Json(request): Json<CreateWebhook>,
+) -> Result<(StatusCode, Json<Webhook>), (StatusCode, String)> {
+    let client = client_name(client.as_ref()).map_err(|status| {
+        (
+            status,
+            "Webhooks need an API key; authentication is disabled".to_string(),
+        )
+    })?;
+    let allowed = state
+        .config
+        .current()
+        .webhooks
+        .allowed_private_hosts
+        .clone();
+    check_webhook_target(&request.url, &allowed)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid webhook url '{}': {}", request.url, e),
+            )
+        })?;
+    let webhook = state
+        .webhooks
+        .register(client, request)
+        .await
+        .map_err(|e| {
+            warn!("Failed to register webhook: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+        })?;
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+/// List the caller's webhooks
+///
+/// # Errors
+///
+/// Returns `StatusCode::FORBIDDEN` without an API key and
+/// `StatusCode::INTERNAL_SERVER_ERROR` if the store cannot be read
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    description = "Secrets are left out",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Registered webhooks", body = [Webhook]),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "Authentication is disabled; webhooks need an API key")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_list_webhooks(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+) -> Result<Json<Vec<Webhook>>, StatusCode> {
+    state
+        .webhooks
+        .list(client_name(client.as_ref())?)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            warn!("Failed to list webhooks: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Remove one of the caller's webhooks and its queued deliveries
+///
+/// # Errors
+///
+/// Returns `StatusCode::FORBIDDEN` without an API key, `StatusCode::NOT_FOUND` if the
+/// caller has no such webhook and
+/// `StatusCode::INTERNAL_SERVER_ERROR` if the store cannot be written
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    description = "Pending deliveries are dropped",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook removed"),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "Authentication is disabled; webhooks need an API key"),
+        (status = 404, description = "No webhook with this id for the caller")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_delete_webhook(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    let client = match client_name(client.as_ref()) {
+        Ok(client) => client,
+        Err(status) => return status,
+    };
+    match state.webhooks.remove(client, &id).await {
+        Ok(true) => StatusCode::NO_CONTENT,
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(e) => {
+            warn!("Failed to remove webhook {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Latest deliveries of one of the caller's webhooks, with every attempt
+///
+/// # Errors
+///
+/// Returns `StatusCode::FORBIDDEN` without an API key, `StatusCode::NOT_FOUND` if the
+/// caller has no such webhook and
+/// `StatusCode::INTERNAL_SERVER_ERROR` if the store cannot be read
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    description = "Newest first, with the payload and the outcome of each attempt",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Deliveries", body = [WebhookDelivery]),
+        (status = 401, description = "Missing or unknown API key"),
+        (status = 403, description = "Authentication is disabled; webhooks need an API key"),
+        (status = 404, description = "No webhook with this id for the caller")
+    ),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub async fn handle_webhook_deliveries(
+    State(state): State<AppState>,
+    client: Option<Extension<ApiClient>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<WebhookDelivery>>, StatusCode> {
+    state
+        .webhooks
+        .deliveries(client_name(client.as_ref())?, &id)
+        .await
+        .map_err(|e| {
+            warn!("Failed to read deliveries of webhook {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
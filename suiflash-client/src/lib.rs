@@ -0,0 +1,236 @@
+//! Typed client for the SuiFlash `/v1` HTTP API
+//!
+//! ```no_run
+//! use suiflash_client::{
+//!     Client, LoanOutcome,
+//!     v1::{FlashLoanRequest, RouteMode},
+//! };
+//!
+//! # async fn run() -> suiflash_client::Result<()> {
+//! let client = Client::new("http://localhost:3000")?.with_api_key("sk_live_...");
+//! let request = FlashLoanRequest {
+//!     asset: "SUI".to_string(),
+//!     amount: 1_000_000_000,
+//!     route_mode: RouteMode::BestCost,
+//!     explicit_protocol: None,
+//!     user_operation: "arbitrage".to_string(),
+//!     callback_recipient: None,
+//!     callback_payload: None,
+//! };
+//! match client.flash_loan(&request, Some("order-42")).await? {
+//!     LoanOutcome::Executed(receipt) => println!("{}", receipt.transaction_digest),
+//!     LoanOutcome::Running(execution) => println!("still running: {}", execution.id),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod v1;
+
+#[cfg(test)]
+mod tests;
+
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use url::Url;
+use v1::{
+    BatchRequest, BatchResponse, ErrorBody, Execution, FlashLoanReceipt, FlashLoanRequest,
+    HistoryPage, HistoryQuery, Protocols, Quote, QuoteRequest, Status,
+};
+
+/// Header making a loan request safe to retry
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid SuiFlash URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("SuiFlash URL must use http or https, not {0}")]
+    Scheme(String),
+    #[error("request to SuiFlash failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The server answered with an error status
+    #[error("SuiFlash answered {status}: {message}")]
+    Api { status: StatusCode, message: String },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Answer to a loan request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoanOutcome {
+    /// The loan went through
+    Executed(FlashLoanReceipt),
+    /// An earlier request with the same idempotency key is still running; poll
+    /// [`Client::execution`] for its result
+    Running(Execution),
+}
+
+/// Client for one SuiFlash server
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    api_key: Option<String>,
+}
+
+impl Client {
+    /// Client for the server at `base_url`, such as `https://suiflash.example.com`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `base_url` is not an absolute http(s) URL
+    pub fn new(base_url: &str) -> Result<Self> {
+        let base_url = Url::parse(base_url)?;
+        if base_url.cannot_be_a_base() {
+            return Err(url::ParseError::RelativeUrlWithCannotBeABaseBase.into());
+        }
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(Error::Scheme(base_url.scheme().to_string()));
+        }
+        Ok(Self {
+            http: reqwest::Client::new(),
+            base_url,
+            api_key: None,
+        })
+    }
+
+    /// Send `api_key` as a bearer token with every request
+    #[must_use]
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Use `http` for requests, for custom timeouts, proxies or TLS settings
+    #[must_use]
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Price a loan without executing it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or no protocol can serve the loan
+    pub async fn quote(&self, request: &QuoteRequest) -> Result<Quote> {
+        let builder = self.request(Method::POST, &["quote"]).json(request);
+        parse(send(builder).await?).await
+    }
+
+    /// Execute a loan
+    ///
+    /// With an `idempotency_key`, retrying after a timeout or dropped connection returns the
+    /// first attempt's result instead of borrowing again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the loan is refused or fails
+    pub async fn flash_loan(
+        &self,
+        request: &FlashLoanRequest,
+        idempotency_key: Option<&str>,
+    ) -> Result<LoanOutcome> {
+        let mut builder = self.request(Method::POST, &["flashloan"]).json(request);
+        if let Some(key) = idempotency_key {
+            builder = builder.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        let response = send(builder).await?;
+        if response.status() == StatusCode::ACCEPTED {
+            Ok(LoanOutcome::Running(parse(response).await?))
+        } else {
+            Ok(LoanOutcome::Executed(parse(response).await?))
+        }
+    }
+
+    /// Plan several loans together and execute each on its own
+    ///
+    /// Loans that fail are reported in their [`v1::BatchItem`]; the call only errors when
+    /// the batch as a whole is refused.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the batch is empty or too large
+    pub async fn flash_loan_batch(
+        &self,
+        batch: &BatchRequest,
+        idempotency_key: Option<&str>,
+    ) -> Result<BatchResponse> {
+        let mut builder = self
+            .request(Method::POST, &["flashloan", "batch"])
+            .json(batch);
+        if let Some(key) = idempotency_key {
+            builder = builder.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        parse(send(builder).await?).await
+    }
+
+    /// Look up one of the caller's loans
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or there is no such execution
+    pub async fn execution(&self, id: &str) -> Result<Execution> {
+        parse(send(self.request(Method::GET, &["executions", id])).await?).await
+    }
+
+    /// The caller's loans, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or `query.before` is not a valid cursor
+    pub async fn history(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        let builder = self.request(Method::GET, &["history"]).query(query);
+        parse(send(builder).await?).await
+    }
+
+    /// Latest fee and liquidity of every protocol
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails
+    pub async fn protocols(&self) -> Result<Protocols> {
+        parse(send(self.request(Method::GET, &["protocols"])).await?).await
+    }
+
+    /// Active strategy, service fee and data freshness
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails
+    pub async fn status(&self) -> Result<Status> {
+        parse(send(self.request(Method::GET, &["status"])).await?).await
+    }
+
+    /// Request to `/v1/<segments>`, each segment percent-encoded
+    fn request(&self, method: Method, segments: &[&str]) -> RequestBuilder {
+        let mut url = self.base_url.clone();
+        // Checked in `new`: the base URL can take path segments
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().push("v1").extend(segments);
+        }
+        let builder = self.http.request(method, url);
+        match &self.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+}
+
+/// Send `request`, turning error statuses into [`Error::Api`]
+async fn send(request: RequestBuilder) -> Result<Response> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    // `/v1` errors carry an `ErrorBody`; proxies and older servers may send plain text
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<ErrorBody>(&body).map_or(body, |body| body.error);
+    Err(Error::Api { status, message })
+}
+
+async fn parse<T: DeserializeOwned>(response: Response) -> Result<T> {
+    Ok(response.json().await?)
+}
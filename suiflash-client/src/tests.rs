@@ -0,0 +1,215 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use tokio::net::TcpListener;
+
+use crate::{
+    Client, Error, IDEMPOTENCY_KEY_HEADER, LoanOutcome,
+    v1::{
+        ErrorBody, Execution, ExecutionStatus, FlashLoanReceipt, FlashLoanRequest, GasEstimate,
+        HistoryPage, HistoryQuery, Protocol, Quote, QuoteRequest, RouteMode,
+    },
+};
+
+/// Serve `router` on a free local port, returning its base URL
+async fn serve(router: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
+fn request() -> FlashLoanRequest {
+    FlashLoanRequest {
+        asset: "SUI".to_string(),
+        amount: 1_000,
+        route_mode: RouteMode::BestCost,
+        explicit_protocol: None,
+        user_operation: "arbitrage".to_string(),
+        callback_recipient: None,
+        callback_payload: None,
+    }
+}
+
+fn receipt() -> FlashLoanReceipt {
+    FlashLoanReceipt {
+        execution_id: "exec-1".to_string(),
+        transaction_digest: "digest".to_string(),
+        protocol: Protocol::Navi,
+        asset: "SUI".to_string(),
+        amount: 1_000,
+        protocol_fee: 1,
+        service_fee: 4,
+        total_fee: 5,
+    }
+}
+
+fn execution(status: ExecutionStatus) -> Execution {
+    Execution {
+        id: "exec-1".to_string(),
+        status,
+        request: request(),
+        receipt: None,
+        error: None,
+        created_at: 100,
+        updated_at: 100,
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(ErrorBody {
+            error: message.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+#[tokio::test]
+async fn test_flash_loan_outcomes() {
+    let router = Router::new().route(
+        "/v1/flashloan",
+        post(
+            |headers: HeaderMap, Json(body): Json<FlashLoanRequest>| async move {
+                assert_eq!(body, request());
+                if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer key")
+                {
+                    return error(StatusCode::UNAUTHORIZED, "Missing or unknown API key");
+                }
+                match headers.get(IDEMPOTENCY_KEY_HEADER) {
+                    Some(key) if key == "running" => (
+                        StatusCode::ACCEPTED,
+                        Json(execution(ExecutionStatus::Submitted)),
+                    )
+                        .into_response(),
+                    _ => Json(receipt()).into_response(),
+                }
+            },
+        ),
+    );
+    let base_url = serve(router).await;
+    let client = Client::new(&base_url).unwrap().with_api_key("key");
+
+    assert_eq!(
+        client.flash_loan(&request(), None).await.unwrap(),
+        LoanOutcome::Executed(receipt())
+    );
+    assert_eq!(
+        client
+            .flash_loan(&request(), Some("running"))
+            .await
+            .unwrap(),
+        LoanOutcome::Running(execution(ExecutionStatus::Submitted))
+    );
+
+    let anonymous = Client::new(&base_url).unwrap();
+    match anonymous.flash_loan(&request(), None).await {
+        Err(Error::Api { status, message }) => {
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(message, "Missing or unknown API key");
+        }
+        other => panic!("expected an API error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_paths_queries_and_errors() {
+    let api = Router::new()
+        .route(
+            "/v1/quote",
+            post(|Json(body): Json<QuoteRequest>| async move {
+                Json(Quote {
+                    protocol: body.explicit_protocol.unwrap_or(Protocol::Bucket),
+                    asset: body.asset,
+                    amount: body.amount,
+                    protocol_fee: 1,
+                    service_fee: 4,
+                    total_fee: 5,
+                    total_repayment: body.amount + 5,
+                    gas: GasEstimate {
+                        reference_gas_price: 750,
+                        gas_price: 750,
+                        computation_cost: 1_000_000,
+                        storage_cost: 988_000,
+                        gas_budget: 2_982_000,
+                    },
+                })
+            }),
+        )
+        .route(
+            "/v1/history",
+            get(|Query(query): Query<HistoryQuery>| async move {
+                assert_eq!(query.limit, Some(2));
+                assert_eq!(query.status, Some(ExecutionStatus::Confirmed));
+                Json(HistoryPage {
+                    executions: vec![execution(ExecutionStatus::Confirmed)],
+                    next: query.before.map(|before| format!("after-{before}")),
+                })
+            }),
+        )
+        .route(
+            "/v1/executions/{id}",
+            get(|Path(id): Path<String>| async move {
+                error(StatusCode::NOT_FOUND, &format!("No execution {id}"))
+            }),
+        )
+        .route(
+            "/v1/status",
+            get(|| async { (StatusCode::BAD_GATEWAY, "upstream down") }),
+        );
+    // Servers mounted under a path prefix keep it
+    let base_url = serve(Router::new().nest("/api", api)).await;
+    let client = Client::new(&format!("{base_url}/api/")).unwrap();
+
+    let quote = client
+        .quote(&QuoteRequest {
+            asset: "SUI".to_string(),
+            amount: 1_000,
+            route_mode: RouteMode::Explicit,
+            explicit_protocol: Some(Protocol::Registered(7)),
+        })
+        .await
+        .unwrap();
+    assert_eq!(quote.protocol, Protocol::Registered(7));
+    assert_eq!(quote.total_repayment, 1_005);
+
+    let page = client
+        .history(&HistoryQuery {
+            limit: Some(2),
+            before: Some("cursor".to_string()),
+            status: Some(ExecutionStatus::Confirmed),
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.executions.len(), 1);
+    assert_eq!(page.next.as_deref(), Some("after-cursor"));
+
+    // Ids are percent-encoded into a single path segment
+    match client.execution("a/b").await {
+        Err(Error::Api { status, message }) => {
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(message, "No execution a/b");
+        }
+        other => panic!("expected an API error, got {other:?}"),
+    }
+    // Plain-text errors are passed through
+    match client.status().await {
+        Err(Error::Api { status, message }) => {
+            assert_eq!(status, StatusCode::BAD_GATEWAY);
+            assert_eq!(message, "upstream down");
+        }
+        other => panic!("expected an API error, got {other:?}"),
+    }
+
+    assert!(matches!(Client::new("localhost:3000"), Err(Error::Url(_))));
+    assert!(matches!(Client::new("not a url"), Err(Error::Url(_))));
+    assert!(matches!(
+        Client::new("ftp://localhost:3000"),
+        Err(Error::Scheme(scheme)) if scheme == "ftp"
+    ));
+}
@@ -0,0 +1,261 @@
+//! Wire types of the `/v1` API
+//!
+//! Later releases may add fields, but existing fields keep their names and meaning for as
+//! long as `/v1` is served. Changes that would break them go to a new version.
+
+use serde::{Deserialize, Serialize};
+
+/// Flash loan source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum Protocol {
+    Navi,
+    Bucket,
+    Scallop,
+    DeepBook,
+    Suilend,
+    /// Listed in the on-chain registry under this id
+    Registered(u64),
+}
+
+/// How the server picks the protocol for a loan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum RouteMode {
+    /// Use `explicit_protocol`
+    Explicit,
+    /// Lowest fee among protocols with enough liquidity
+    BestCost,
+    /// Most available liquidity
+    BestLiquidity,
+}
+
+/// Loan to price with `POST /v1/quote`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuoteRequest {
+    /// Asset symbol, such as `SUI`
+    pub asset: String,
+    /// In the asset's base units
+    pub amount: u64,
+    pub route_mode: RouteMode,
+    /// Required when `route_mode` is `Explicit`
+    #[serde(default)]
+    pub explicit_protocol: Option<Protocol>,
+}
+
+/// What a loan would cost right now; nothing is reserved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Quote {
+    pub protocol: Protocol,
+    pub asset: String,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub service_fee: u64,
+    pub total_fee: u64,
+    /// Amount plus every fee, due in the same transaction
+    pub total_repayment: u64,
+    /// Gas the loan's transaction used in a dry run, paid in SUI on top of the fees
+    pub gas: GasEstimate,
+}
+
+/// Gas price and budget for one loan, in MIST
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GasEstimate {
+    pub reference_gas_price: u64,
+    /// Reference price with the priority fee applied
+    pub gas_price: u64,
+    pub computation_cost: u64,
+    pub storage_cost: u64,
+    /// Computation plus storage with the safety factor applied
+    pub gas_budget: u64,
+}
+
+/// Loan to execute with `POST /v1/flashloan`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FlashLoanRequest {
+    /// Asset symbol, such as `SUI`
+    pub asset: String,
+    /// In the asset's base units
+    pub amount: u64,
+    pub route_mode: RouteMode,
+    /// Required when `route_mode` is `Explicit`
+    #[serde(default)]
+    pub explicit_protocol: Option<Protocol>,
+    /// Operation run with the borrowed funds
+    pub user_operation: String,
+    /// Hex address of the contract handling the callback
+    #[serde(default)]
+    pub callback_recipient: Option<String>,
+    /// Base64 or hex encoded payload
+    #[serde(default)]
+    pub callback_payload: Option<String>,
+}
+
+/// A loan that went through
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FlashLoanReceipt {
+    /// Look it up again with `GET /v1/executions/{id}`
+    pub execution_id: String,
+    pub transaction_digest: String,
+    pub protocol: Protocol,
+    pub asset: String,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub service_fee: u64,
+    pub total_fee: u64,
+}
+
+/// Where a loan is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    /// Accepted, not yet routed
+    Pending,
+    /// Routed to a protocol
+    Planned,
+    /// Handed to the executor; from here on the loan may have reached the chain
+    Submitted,
+    Confirmed,
+    Failed,
+}
+
+/// A loan and what became of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Execution {
+    pub id: String,
+    pub status: ExecutionStatus,
+    pub request: FlashLoanRequest,
+    /// Set once confirmed
+    pub receipt: Option<FlashLoanReceipt>,
+    /// Why the loan failed
+    pub error: Option<String>,
+    /// Unix timestamps in seconds
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Loans to plan together with `POST /v1/flashloan/batch`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BatchRequest {
+    /// Planned together, then executed one after another in this order
+    pub loans: Vec<FlashLoanRequest>,
+}
+
+/// Outcome of one loan in a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BatchItem {
+    /// Position of the loan in `loans`
+    pub index: usize,
+    /// Status `POST /v1/flashloan` would have answered this loan with
+    pub status: u16,
+    /// Set once the loan was recorded
+    pub execution_id: Option<String>,
+    /// Set when the loan went through
+    pub receipt: Option<FlashLoanReceipt>,
+    /// Why the loan was refused or failed
+    pub error: Option<String>,
+    /// Answered from an earlier batch sent with the same `Idempotency-Key`
+    pub replayed: bool,
+}
+
+/// Result of every loan in a batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BatchResponse {
+    /// Loans that went through, including replayed ones
+    pub succeeded: usize,
+    /// Loans refused or failed; the rest are still running from an earlier batch
+    pub failed: usize,
+    /// One entry per requested loan, in request order
+    pub results: Vec<BatchItem>,
+}
+
+/// Latest fee and liquidity of one protocol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProtocolInfo {
+    pub protocol: Protocol,
+    /// Asset symbol the fee and liquidity are quoted for, such as `SUI`
+    pub asset: String,
+    pub fee_bps: u64,
+    pub available_liquidity: u64,
+    /// Unix timestamp in seconds
+    pub last_updated: u64,
+}
+
+/// Adapter package in the on-chain registry that the server cannot route to yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RegistryEntry {
+    pub protocol_id: u64,
+    pub adapter_package: String,
+}
+
+/// Answer of `GET /v1/protocols`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Protocols {
+    pub protocols: Vec<ProtocolInfo>,
+    pub unknown_protocols: Vec<RegistryEntry>,
+}
+
+/// Rule used for loans that do not name a protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    Cheapest,
+    HighestLiquidity,
+}
+
+/// Answer of `GET /v1/status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Status {
+    pub strategy: Strategy,
+    pub service_fee_bps: u64,
+    pub protocol_count: usize,
+    /// Unix timestamp of the freshest protocol data
+    pub last_updated_any: Option<u64>,
+}
+
+/// Filter and page of `GET /v1/history`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct HistoryQuery {
+    /// Executions per page; 50 when unset, at most 200
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// `next` of the previous page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    /// Only executions in this status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ExecutionStatus>,
+}
+
+/// The caller's executions, newest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HistoryPage {
+    pub executions: Vec<Execution>,
+    /// Pass as `before` for the next page; unset on the last one
+    pub next: Option<String>,
+}
+
+/// Body of every `/v1` error response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorBody {
+    pub error: String,
+}